@@ -0,0 +1,429 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::catalogue::{in_range, ValueRange};
+use crate::state::{Biome, BiomeBlend};
+
+const BUILTIN_BIOMES_JSON: &str = include_str!("../assets/biomes.json");
+
+/// Biomes whose mature canopy reaches this height count as woody.
+pub const WOODY_CANOPY_M: f32 = 2.0;
+
+/// Distance over which a rule fades from full to no membership when blending,
+/// per classifier input, at `ecotone_width` 1.
+const TRANSITION_WIDTH: BiomeSample = BiomeSample {
+    temperature: 3.0,
+    rainfall: 0.05,
+    elevation: 0.02,
+    wetness: 0.08,
+    coast_distance: 1.0,
+    slope: 15.0,
+    exposure: 0.1,
+};
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BiomeKind {
+    #[default]
+    Land,
+    /// Land biome for shoreline strips; never smoothed into its neighbours.
+    Coastal,
+    /// Ocean-floor biome chosen by rules; unmatched ocean tiles stay `Ocean`.
+    Marine,
+    Ocean,
+    Lake,
+}
+
+/// One way a tile can qualify for a biome. Every range that is present must
+/// contain the tile's value; elevation is measured above `sea_level` (negative
+/// offshore), `coast_distance` in tiles from the shoreline, `slope` in metres
+/// of drop to the steepest neighbour and `exposure` as 0..1 wave fetch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BiomeRule {
+    pub priority: i32,
+    pub temperature: Option<ValueRange>,
+    pub rainfall: Option<ValueRange>,
+    pub elevation: Option<ValueRange>,
+    pub wetness: Option<ValueRange>,
+    pub coast_distance: Option<ValueRange>,
+    pub slope: Option<ValueRange>,
+    pub exposure: Option<ValueRange>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BiomeDef {
+    pub name: String,
+    #[serde(default)]
+    pub kind: BiomeKind,
+    pub color: [u8; 3],
+    #[serde(default)]
+    pub rules: Vec<BiomeRule>,
+    /// Leaf area index of a mature, unstressed stand.
+    #[serde(default)]
+    pub max_lai: f32,
+    /// Canopy height in metres of a mature stand on deep soil.
+    #[serde(default)]
+    pub canopy_height_m: f32,
+}
+
+impl BiomeDef {
+    pub fn is_woody(&self) -> bool {
+        self.canopy_height_m >= WOODY_CANOPY_M
+    }
+}
+
+#[derive(Deserialize)]
+struct BiomeFile {
+    fallback: String,
+    biomes: Vec<BiomeDef>,
+}
+
+/// Classifier inputs for a single tile.
+#[derive(Clone, Copy, Debug)]
+pub struct BiomeSample {
+    pub temperature: f32,
+    pub rainfall: f32,
+    pub elevation: f32,
+    pub wetness: f32,
+    pub coast_distance: f32,
+    pub slope: f32,
+    pub exposure: f32,
+}
+
+/// Biome definitions in file order; a `Biome` is an index into this table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BiomeTable {
+    defs: Vec<BiomeDef>,
+    ocean: Biome,
+    lake: Biome,
+    fallback: Biome,
+}
+
+impl BiomeTable {
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_BIOMES_JSON).expect("built-in biome table is valid")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading biome definitions {}", path.display()))?;
+        Self::from_json(&text)
+            .with_context(|| format!("parsing biome definitions {}", path.display()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let file: BiomeFile = serde_json::from_str(text)?;
+        if file.biomes.len() > usize::from(u8::MAX) + 1 {
+            bail!(
+                "at most 256 biomes are supported, got {}",
+                file.biomes.len()
+            );
+        }
+
+        let mut ocean = None;
+        let mut lake = None;
+        let mut fallback = None;
+        for (i, def) in file.biomes.iter().enumerate() {
+            let id = Biome(i as u8);
+            if file.biomes[..i].iter().any(|d| d.name == def.name) {
+                bail!("duplicate biome name {:?}", def.name);
+            }
+            match def.kind {
+                BiomeKind::Ocean if ocean.replace(id).is_some() => {
+                    bail!("more than one biome has kind \"ocean\"")
+                }
+                BiomeKind::Lake if lake.replace(id).is_some() => {
+                    bail!("more than one biome has kind \"lake\"")
+                }
+                _ => {}
+            }
+            if def.name == file.fallback {
+                if def.kind != BiomeKind::Land {
+                    bail!("fallback biome {:?} must be a land biome", def.name);
+                }
+                fallback = Some(id);
+            }
+        }
+
+        Ok(Self {
+            defs: file.biomes,
+            ocean: ocean.context("no biome has kind \"ocean\"")?,
+            lake: lake.context("no biome has kind \"lake\"")?,
+            fallback: fallback
+                .with_context(|| format!("fallback biome {:?} is not defined", file.fallback))?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    pub fn ocean(&self) -> Biome {
+        self.ocean
+    }
+
+    pub fn lake(&self) -> Biome {
+        self.lake
+    }
+
+    pub fn is_water(&self, biome: Biome) -> bool {
+        biome == self.ocean || biome == self.lake
+    }
+
+    /// Only plain land biomes take part in majority smoothing.
+    pub fn is_smoothed(&self, biome: Biome) -> bool {
+        self.def(biome).kind == BiomeKind::Land
+    }
+
+    pub fn def(&self, biome: Biome) -> &BiomeDef {
+        &self.defs[usize::from(biome.as_u8())]
+    }
+
+    pub fn name(&self, biome: Biome) -> &str {
+        &self.def(biome).name
+    }
+
+    pub fn color(&self, biome: Biome) -> [u8; 3] {
+        self.def(biome).color
+    }
+
+    /// Weighted mix of the blend's biome colours.
+    pub fn blend_color(&self, blend: &BiomeBlend) -> [u8; 3] {
+        let mut rgb = [0.0f32; 3];
+        for (biome, w) in blend.iter() {
+            for (acc, c) in rgb.iter_mut().zip(self.color(biome)) {
+                *acc += f32::from(c) * w;
+            }
+        }
+        rgb.map(|v| v.round().clamp(0.0, 255.0) as u8)
+    }
+
+    pub fn find(&self, name: &str) -> Option<Biome> {
+        self.defs
+            .iter()
+            .position(|d| d.name == name)
+            .map(|i| Biome(i as u8))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Biome, &BiomeDef)> + '_ {
+        self.defs
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (Biome(i as u8), d))
+    }
+
+    /// Highest-priority land or coastal rule matching `sample`; earlier
+    /// definitions win ties.
+    pub fn classify_land(&self, sample: &BiomeSample) -> Biome {
+        self.classify(sample, self.fallback, |kind| {
+            matches!(kind, BiomeKind::Land | BiomeKind::Coastal)
+        })
+    }
+
+    /// Highest-priority marine rule matching `sample`, else the ocean biome.
+    pub fn classify_marine(&self, sample: &BiomeSample) -> Biome {
+        self.classify(sample, self.ocean, |kind| kind == BiomeKind::Marine)
+    }
+
+    /// Soft version of `classify_land`: rules are visited in priority order and
+    /// each takes its membership share of the weight still unclaimed, so with a
+    /// zero `width` this reduces to the hard classification.
+    pub fn blend_land(&self, sample: &BiomeSample, width: f32) -> Vec<(Biome, f32)> {
+        self.blend(sample, width, self.fallback, |kind| {
+            matches!(kind, BiomeKind::Land | BiomeKind::Coastal)
+        })
+    }
+
+    pub fn blend_marine(&self, sample: &BiomeSample, width: f32) -> Vec<(Biome, f32)> {
+        self.blend(sample, width, self.ocean, |kind| kind == BiomeKind::Marine)
+    }
+
+    fn blend(
+        &self,
+        sample: &BiomeSample,
+        width: f32,
+        fallback: Biome,
+        kinds: impl Fn(BiomeKind) -> bool,
+    ) -> Vec<(Biome, f32)> {
+        let mut rules: Vec<(Biome, &BiomeRule)> = self
+            .iter()
+            .filter(|(_, def)| kinds(def.kind))
+            .flat_map(|(biome, def)| def.rules.iter().map(move |r| (biome, r)))
+            .collect();
+        // Stable sort keeps definition order among equal priorities.
+        rules.sort_by_key(|(_, r)| std::cmp::Reverse(r.priority));
+
+        let mut weights: Vec<(Biome, f32)> = Vec::new();
+        let mut remaining = 1.0f32;
+        let mut add = |biome: Biome, w: f32| match weights.iter_mut().find(|(b, _)| *b == biome) {
+            Some((_, acc)) => *acc += w,
+            None => weights.push((biome, w)),
+        };
+        for (biome, rule) in rules {
+            let w = remaining * rule.membership(sample, width);
+            if w > 0.0 {
+                add(biome, w);
+                remaining -= w;
+            }
+            if remaining <= 1e-6 {
+                break;
+            }
+        }
+        if remaining > 1e-6 {
+            add(fallback, remaining);
+        }
+        weights
+    }
+
+    fn classify(
+        &self,
+        sample: &BiomeSample,
+        fallback: Biome,
+        kinds: impl Fn(BiomeKind) -> bool,
+    ) -> Biome {
+        let mut best = fallback;
+        let mut best_priority = i32::MIN;
+        for (biome, def) in self.iter() {
+            if !kinds(def.kind) {
+                continue;
+            }
+            for rule in &def.rules {
+                if rule.priority > best_priority && rule.matches(sample) {
+                    best = biome;
+                    best_priority = rule.priority;
+                }
+            }
+        }
+        best
+    }
+}
+
+impl BiomeRule {
+    fn matches(&self, s: &BiomeSample) -> bool {
+        in_range(self.temperature, s.temperature)
+            && in_range(self.rainfall, s.rainfall)
+            && in_range(self.elevation, s.elevation)
+            && in_range(self.wetness, s.wetness)
+            && in_range(self.coast_distance, s.coast_distance)
+            && in_range(self.slope, s.slope)
+            && in_range(self.exposure, s.exposure)
+    }
+
+    /// 1 well inside every range, 0.5 on a boundary, 0 one transition width
+    /// outside the nearest violated bound.
+    fn membership(&self, s: &BiomeSample, width: f32) -> f32 {
+        let w = width.max(1e-6);
+        let fit = [
+            range_margin(
+                self.temperature,
+                s.temperature,
+                TRANSITION_WIDTH.temperature * w,
+            ),
+            range_margin(self.rainfall, s.rainfall, TRANSITION_WIDTH.rainfall * w),
+            range_margin(self.elevation, s.elevation, TRANSITION_WIDTH.elevation * w),
+            range_margin(self.wetness, s.wetness, TRANSITION_WIDTH.wetness * w),
+            range_margin(
+                self.coast_distance,
+                s.coast_distance,
+                TRANSITION_WIDTH.coast_distance * w,
+            ),
+            range_margin(self.slope, s.slope, TRANSITION_WIDTH.slope * w),
+            range_margin(self.exposure, s.exposure, TRANSITION_WIDTH.exposure * w),
+        ]
+        .into_iter()
+        .fold(f32::INFINITY, f32::min);
+        (0.5 + 0.5 * fit).clamp(0.0, 1.0)
+    }
+}
+
+/// Signed distance to the nearest bound in units of `scale`; positive inside.
+fn range_margin(range: Option<ValueRange>, v: f32, scale: f32) -> f32 {
+    let Some([min, max]) = range else {
+        return f32::INFINITY;
+    };
+    let lo = min.map_or(f32::INFINITY, |m| v - m);
+    let hi = max.map_or(f32::INFINITY, |m| m - v);
+    lo.min(hi) / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BiomeSample, BiomeTable};
+
+    #[test]
+    fn builtin_table_matches_legacy_thresholds() {
+        let table = BiomeTable::builtin();
+        let classify = |temperature: f32, rainfall: f32, elevation: f32, wetness: f32| {
+            let b = table.classify_land(&BiomeSample {
+                temperature,
+                rainfall,
+                elevation,
+                wetness,
+                coast_distance: 50.0,
+                slope: 0.0,
+                exposure: 0.0,
+            });
+            table.name(b).to_string()
+        };
+
+        assert_eq!(classify(-20.0, 0.1, 0.05, 0.1), "Polar Desert");
+        assert_eq!(classify(-20.0, 0.3, 0.05, 0.1), "Tundra");
+        assert_eq!(classify(0.0, 0.2, 0.05, 0.1), "Tundra");
+        assert_eq!(classify(0.0, 0.5, 0.05, 0.1), "Boreal Forest");
+        assert_eq!(classify(10.0, 0.3, 0.05, 0.1), "Mediterranean");
+        assert_eq!(classify(25.0, 0.1, 0.05, 0.1), "Hot Desert");
+        assert_eq!(classify(25.0, 0.9, 0.05, 0.1), "Tropical Rainforest");
+        assert_eq!(classify(25.0, 0.9, 0.4, 0.1), "Alpine");
+        assert_eq!(classify(25.0, 0.9, 0.4, 0.9), "Wetland");
+    }
+
+    #[test]
+    fn builtin_table_has_coastal_and_marine_biomes() {
+        let table = BiomeTable::builtin();
+        let sample = |elevation: f32, slope: f32, exposure: f32| BiomeSample {
+            temperature: 24.0,
+            rainfall: 0.5,
+            elevation,
+            wetness: 0.3,
+            coast_distance: 1.0,
+            slope,
+            exposure,
+        };
+
+        let name = |b| table.name(b).to_string();
+        assert_eq!(name(table.classify_land(&sample(0.01, 5.0, 0.6))), "Beach");
+        assert_eq!(
+            name(table.classify_land(&sample(0.02, 120.0, 0.6))),
+            "Rocky Cliff"
+        );
+        assert_eq!(
+            name(table.classify_land(&sample(0.01, 5.0, 0.1))),
+            "Mangrove"
+        );
+        assert_eq!(
+            name(table.classify_marine(&sample(-0.01, 0.0, 0.5))),
+            "Coral Reef"
+        );
+        assert_eq!(
+            table.classify_marine(&sample(-0.3, 0.0, 1.0)),
+            table.ocean()
+        );
+    }
+
+    #[test]
+    fn custom_table_requires_water_biomes() {
+        let json = r#"{ "fallback": "Ashlands", "biomes": [
+            { "name": "Ashlands", "color": [60, 50, 50] }
+        ] }"#;
+        assert!(BiomeTable::from_json(json).is_err());
+    }
+}
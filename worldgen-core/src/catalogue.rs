@@ -0,0 +1,24 @@
+/// Half-open `[min, max)` interval; a `null` bound is unbounded on that side.
+pub type ValueRange = [Option<f32>; 2];
+
+/// Whether `v` lies in `range`; an absent range admits every value.
+pub(crate) fn in_range(range: Option<ValueRange>, v: f32) -> bool {
+    match range {
+        None => true,
+        Some([min, max]) => min.is_none_or(|m| v >= m) && max.is_none_or(|m| v < m),
+    }
+}
+
+/// Lower-case ASCII key for a catalogue name, with every other character
+/// replaced by `_`.
+pub(crate) fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::catalogue::ValueRange;
+
+const BUILTIN_CREATURES_JSON: &str = include_str!("../assets/creatures.json");
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CreatureHabitat {
+    #[default]
+    Land,
+    /// Lakes and river channels.
+    Freshwater,
+    Marine,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreatureDef {
+    pub name: String,
+    #[serde(default)]
+    pub habitat: CreatureHabitat,
+    /// Suitability 0..1 per biome name; biomes not listed, or not present in
+    /// the world's biome table, count as 0.
+    pub biomes: BTreeMap<String, f32>,
+    /// Tolerated mean annual temperature, °C.
+    #[serde(default)]
+    pub temperature: Option<ValueRange>,
+    /// 0 (common) ..1 (legendary); scales down how many sites are sampled.
+    #[serde(default)]
+    pub rarity: f32,
+    /// Typical head count of one population site.
+    #[serde(default = "default_group_size")]
+    pub group_size: u32,
+    /// Sites are lairs (dens, nests, hoards) rather than herds or shoals.
+    #[serde(default)]
+    pub lairs: bool,
+}
+
+fn default_group_size() -> u32 {
+    20
+}
+
+#[derive(Deserialize)]
+struct CreatureFile {
+    creatures: Vec<CreatureDef>,
+}
+
+/// Creature definitions in file order; species ids index into this list.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreatureCatalogue {
+    defs: Vec<CreatureDef>,
+}
+
+impl CreatureCatalogue {
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_CREATURES_JSON).expect("built-in creature catalogue is valid")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading creature catalogue {}", path.display()))?;
+        Self::from_json(&text)
+            .with_context(|| format!("parsing creature catalogue {}", path.display()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let file: CreatureFile = serde_json::from_str(text)?;
+        for (i, def) in file.creatures.iter().enumerate() {
+            if file.creatures[..i].iter().any(|d| d.name == def.name) {
+                bail!("duplicate creature name {:?}", def.name);
+            }
+            if !(0.0..=1.0).contains(&def.rarity) {
+                bail!("creature {:?} rarity must be within 0..=1", def.name);
+            }
+        }
+        Ok(Self {
+            defs: file.creatures,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    pub fn def(&self, species: usize) -> &CreatureDef {
+        &self.defs[species]
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.defs.iter().position(|d| d.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &CreatureDef)> + '_ {
+        self.defs.iter().enumerate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CreatureCatalogue;
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let json = r#"{ "creatures": [
+            { "name": "Wolf", "biomes": { "Tundra": 1.0 } },
+            { "name": "Wolf", "biomes": { "Boreal Forest": 1.0 } }
+        ] }"#;
+        assert!(CreatureCatalogue::from_json(json).is_err());
+        assert!(!CreatureCatalogue::builtin().is_empty());
+    }
+}
@@ -4,11 +4,20 @@ use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
-use image::{ImageBuffer, Rgb};
+use image::{ImageBuffer, Rgb, Rgba};
 use serde::Serialize;
 
+use crate::catalogue::slug;
+use crate::creature_catalogue::CreatureHabitat;
 use crate::grid::Grid2D;
-use crate::state::{Biome, RiverClass, Step, WorldState};
+use crate::state::{
+    Alignment, CaveOpening, CreatureSite, FaultKind, Hazard, IceCover, MountainPass, MountainRange,
+    OceanZone, OreDeposit, Peak, PlaceName, Region, RiverClass, RiverNavigation, Savagery, Step,
+    Volcano, WorldState, BIOME_BLEND_SLOTS,
+};
+use crate::systems::{
+    bathymetry, caves, cryosphere, hydro_finalize, life_zones, ore, regions, soils, wilderness,
+};
 
 #[derive(Serialize)]
 struct ExportMeta<'a> {
@@ -19,9 +28,138 @@ struct ExportMeta<'a> {
     timings_ms: &'a BTreeMap<Step, f64>,
     checksum: &'a str,
     layer_hashes: &'a BTreeMap<String, String>,
+    biomes: Vec<CategoryLegend<'a>>,
+    wilderness: Vec<WildernessLegend>,
+    rocks: Vec<CategoryLegend<'a>>,
+    minerals: Vec<MineralLegend<'a>>,
     timestamp_unix_s: u64,
 }
 
+/// One entry of a catalogue-defined category: a biome or a rock.
+#[derive(Serialize)]
+struct CategoryLegend<'a> {
+    id: u8,
+    name: &'a str,
+    color: [u8; 3],
+}
+
+/// One catalogue mineral; `key` names its files under `minerals/`.
+#[derive(Serialize)]
+struct MineralLegend<'a> {
+    id: u8,
+    name: &'a str,
+    key: &'a str,
+    color: [u8; 3],
+}
+
+/// One class of `wilderness.png`: pixel colour and the adjective it adds to
+/// biome names.
+#[derive(Serialize)]
+struct WildernessLegend {
+    savagery: Savagery,
+    alignment: Alignment,
+    adjective: Option<&'static str>,
+    color: [u8; 3],
+}
+
+#[derive(Serialize)]
+struct RegionsExport<'a> {
+    regions: &'a [Region],
+    places: &'a [PlaceName],
+}
+
+/// Peaks, passes and ranges for label placement and route finding.
+#[derive(Serialize)]
+struct FeaturesExport<'a> {
+    peaks: &'a [Peak],
+    passes: &'a [MountainPass],
+    ranges: &'a [MountainRange],
+}
+
+#[derive(Serialize)]
+struct DepositsExport<'a> {
+    deposits: &'a [OreDeposit],
+}
+
+#[derive(Serialize)]
+struct VolcanoesExport<'a> {
+    volcanoes: &'a [Volcano],
+}
+
+#[derive(Serialize)]
+struct CavesExport<'a> {
+    layers: Vec<CaveLayerExport>,
+    openings: &'a [CaveOpening],
+}
+
+#[derive(Serialize)]
+struct CaveLayerExport {
+    top_m: f32,
+    bottom_m: f32,
+    file: String,
+}
+
+/// Fault traces and river reaches as GeoJSON `FeatureCollection`s of
+/// `LineString`s in tile coordinates.
+#[derive(Serialize)]
+struct FeatureCollection<F> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    features: Vec<F>,
+}
+
+#[derive(Serialize)]
+struct LineFeature<'a, P> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    geometry: LineString<'a>,
+    properties: P,
+}
+
+#[derive(Serialize)]
+struct LineString<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    coordinates: &'a [[f32; 2]],
+}
+
+#[derive(Serialize)]
+struct FaultProperties {
+    id: u32,
+    kind: FaultKind,
+    throw_m: f32,
+    upthrown_side: &'static str,
+}
+
+#[derive(Serialize)]
+struct RiverProperties {
+    class: RiverClass,
+    navigation: RiverNavigation,
+    navigable: bool,
+    crossable: bool,
+    discharge: f32,
+}
+
+#[derive(Serialize)]
+struct FaunaExport<'a> {
+    species: Vec<SpeciesSummary<'a>>,
+    sites: &'a [CreatureSite],
+}
+
+#[derive(Serialize)]
+struct SpeciesSummary<'a> {
+    id: usize,
+    name: &'a str,
+    habitat: CreatureHabitat,
+    rarity: f32,
+    /// Tiles with any habitat suitability.
+    range_tiles: usize,
+    sites: usize,
+    population: u64,
+    /// Suitability map, relative to the export directory.
+    map: String,
+}
+
 pub fn export_snapshot(state: &WorldState, dir: impl AsRef<Path>) -> Result<()> {
     let dir = dir.as_ref();
     fs::create_dir_all(dir).with_context(|| format!("creating export dir {}", dir.display()))?;
@@ -29,10 +167,94 @@ pub fn export_snapshot(state: &WorldState, dir: impl AsRef<Path>) -> Result<()>
     write_float_layer_png(&state.elevation, dir.join("elevation.png"))?;
     write_float_layer_png(&state.temperature, dir.join("temperature.png"))?;
     write_float_layer_png(&state.rainfall, dir.join("rainfall.png"))?;
+    write_category_png(state, dir.join("ocean_zone.png"), |x, y| {
+        bathymetry::ocean_zone_color(*state.ocean_zone.get(x, y))
+    })?;
+    write_float_layer_png(&state.wave_exposure, dir.join("wave_exposure.png"))?;
     write_float_layer_png(&state.accumulation, dir.join("accumulation.png"))?;
+    write_float_layer_png(&state.discharge, dir.join("discharge.png"))?;
     write_river_png(state, dir.join("river_class.png"))?;
+    write_category_png(state, dir.join("river_navigation.png"), |x, y| {
+        hydro_finalize::navigation_color(*state.river_navigation.get(x, y))
+    })?;
+    write_rivers(state, dir)?;
+    write_category_png(state, dir.join("fluvial_features.png"), |x, y| {
+        hydro_finalize::fluvial_color(*state.fluvial_feature.get(x, y))
+    })?;
     write_ocean_lake_png(state, dir.join("water_masks.png"))?;
-    write_biome_png(&state.biome, dir.join("biome.png"))?;
+    write_category_png(state, dir.join("ice.png"), |x, y| {
+        cryosphere::ice_color(*state.ice_cover.get(x, y))
+    })?;
+    write_float_layer_png(&state.glacier_thickness, dir.join("glacier.png"))?;
+    write_biome_png(state, dir.join("biome.png"))?;
+    write_biome_blend_png(state, dir.join("biome_blend.png"))?;
+    write_biome_blend_bin(state, dir.join("biome_blend.bin"))?;
+    write_category_png(state, dir.join("whittaker.png"), |x, y| {
+        life_zones::whittaker_color(*state.whittaker_biome.get(x, y))
+    })?;
+    write_category_png(state, dir.join("holdridge.png"), |x, y| {
+        life_zones::holdridge_color(*state.holdridge_zone.get(x, y))
+    })?;
+    write_float_layer_png(&state.fire_frequency, dir.join("fire_frequency.png"))?;
+    write_float_layer_png(&state.burn_scar_age, dir.join("burn_scar_age.png"))?;
+    write_float_layer_png(
+        &state.fire_return_interval,
+        dir.join("fire_return_interval.png"),
+    )?;
+    write_category_png(state, dir.join("soil.png"), |x, y| {
+        soils::soil_color(*state.soil.get(x, y))
+    })?;
+    write_float_layer_png(&state.soil_depth, dir.join("soil_depth.png"))?;
+    write_float_layer_png(&state.leaf_area_index, dir.join("leaf_area_index.png"))?;
+    write_float_layer_png(
+        &state.vegetation_density,
+        dir.join("vegetation_density.png"),
+    )?;
+    write_float_layer_png(&state.canopy_height, dir.join("canopy_height.png"))?;
+    write_float_layer_png(&state.forest_age, dir.join("forest_age.png"))?;
+    write_float_layer_png(&state.savagery, dir.join("savagery.png"))?;
+    write_float_layer_png(&state.alignment, dir.join("alignment.png"))?;
+    write_category_png(state, dir.join("wilderness.png"), |x, y| {
+        let wp = &state.params.wilderness;
+        wilderness::wilderness_color(
+            wilderness::savagery_class(*state.savagery.get(x, y), wp),
+            wilderness::alignment_class(*state.alignment.get(x, y), wp),
+        )
+    })?;
+    write_minerals(state, dir)?;
+    write_faults(state, dir)?;
+    fs::write(
+        dir.join("volcanoes.json"),
+        serde_json::to_string_pretty(&VolcanoesExport {
+            volcanoes: &state.volcanoes,
+        })?,
+    )?;
+    write_float_layer_png(&state.lava_thickness_m, dir.join("lava_thickness.png"))?;
+    write_caves(state, dir)?;
+    write_hazards(state, dir)?;
+    write_fauna(state, dir)?;
+    fs::write(
+        dir.join("features.json"),
+        serde_json::to_string_pretty(&FeaturesExport {
+            peaks: &state.peaks,
+            passes: &state.mountain_passes,
+            ranges: &state.mountain_ranges,
+        })?,
+    )?;
+    write_category_png(state, dir.join("regions.png"), |x, y| {
+        // Empty until step 7 has run.
+        state
+            .regions
+            .get(*state.region_id.get(x, y) as usize)
+            .map_or([0, 0, 0], regions::region_color)
+    })?;
+    fs::write(
+        dir.join("regions.json"),
+        serde_json::to_string_pretty(&RegionsExport {
+            regions: &state.regions,
+            places: &state.place_names,
+        })?,
+    )?;
     write_final_map_png(state, dir.join("final_map.png"))?;
 
     let meta = ExportMeta {
@@ -43,6 +265,47 @@ pub fn export_snapshot(state: &WorldState, dir: impl AsRef<Path>) -> Result<()>
         timings_ms: &state.step_timings_ms,
         checksum: &state.diagnostics.checksum,
         layer_hashes: &state.diagnostics.layer_hashes,
+        biomes: state
+            .biome_table
+            .iter()
+            .map(|(biome, def)| CategoryLegend {
+                id: biome.as_u8(),
+                name: &def.name,
+                color: def.color,
+            })
+            .collect(),
+        wilderness: [Savagery::Calm, Savagery::Wild, Savagery::Savage]
+            .into_iter()
+            .flat_map(|savagery| {
+                [Alignment::Evil, Alignment::Neutral, Alignment::Good].map(|alignment| {
+                    WildernessLegend {
+                        savagery,
+                        alignment,
+                        adjective: wilderness::region_adjective(savagery, alignment),
+                        color: wilderness::wilderness_color(savagery, alignment),
+                    }
+                })
+            })
+            .collect(),
+        rocks: state
+            .geology_catalogue
+            .rocks()
+            .map(|(rock, def)| CategoryLegend {
+                id: rock.as_u8(),
+                name: &def.name,
+                color: def.color,
+            })
+            .collect(),
+        minerals: state
+            .geology_catalogue
+            .minerals()
+            .map(|(mineral, def)| MineralLegend {
+                id: mineral.as_u8(),
+                name: &def.name,
+                key: state.geology_catalogue.mineral_key(mineral),
+                color: def.color,
+            })
+            .collect(),
         timestamp_unix_s: SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map_or(0, |d| d.as_secs()),
@@ -52,6 +315,177 @@ pub fn export_snapshot(state: &WorldState, dir: impl AsRef<Path>) -> Result<()>
     Ok(())
 }
 
+/// `deposits.json` plus, per mineral under `minerals/`, a deposit map tinted
+/// by kind and the grade and reserve grids as greyscale PNGs and raw floats.
+fn write_minerals(state: &WorldState, dir: &Path) -> Result<()> {
+    let mineral_dir = dir.join("minerals");
+    fs::create_dir_all(&mineral_dir)
+        .with_context(|| format!("creating export dir {}", mineral_dir.display()))?;
+    for (key, ids) in &state.ore_deposit_id {
+        write_category_png(state, mineral_dir.join(format!("{key}.png")), |x, y| {
+            let id = *ids.get(x, y) as usize;
+            id.checked_sub(1)
+                .and_then(|i| state.ore_deposits.get(i))
+                .map_or([0, 0, 0], |d| ore::deposit_color(d.kind))
+        })?;
+    }
+    for (key, grade) in &state.mineral_grade {
+        write_float_layer_png(grade, mineral_dir.join(format!("{key}_grade.png")))?;
+        write_f32_raw(grade, mineral_dir.join(format!("{key}_grade.f32")))?;
+    }
+    for (key, reserve) in &state.mineral_reserve_t {
+        write_float_layer_png(reserve, mineral_dir.join(format!("{key}_reserve.png")))?;
+        write_f32_raw(reserve, mineral_dir.join(format!("{key}_reserve.f32")))?;
+    }
+    fs::write(
+        dir.join("deposits.json"),
+        serde_json::to_string_pretty(&DepositsExport {
+            deposits: &state.ore_deposits,
+        })?,
+    )?;
+    Ok(())
+}
+
+/// `faults.geojson` plus the distance to the nearest trace as
+/// `fault_distance.png` and raw floats.
+fn write_faults(state: &WorldState, dir: &Path) -> Result<()> {
+    let collection = FeatureCollection {
+        kind: "FeatureCollection",
+        features: state
+            .faults
+            .iter()
+            .map(|f| LineFeature {
+                kind: "Feature",
+                geometry: LineString {
+                    kind: "LineString",
+                    coordinates: &f.points,
+                },
+                properties: FaultProperties {
+                    id: f.id,
+                    kind: f.kind,
+                    throw_m: f.throw_m,
+                    upthrown_side: if f.upthrown_left { "left" } else { "right" },
+                },
+            })
+            .collect(),
+    };
+    fs::write(
+        dir.join("faults.geojson"),
+        serde_json::to_string_pretty(&collection)?,
+    )?;
+    write_float_layer_png(&state.fault_distance, dir.join("fault_distance.png"))?;
+    write_f32_raw(&state.fault_distance, dir.join("fault_distance.f32"))?;
+    Ok(())
+}
+
+/// River reaches with their class and navigation attributes, so routes can
+/// be planned from the vector export without resampling the rasters.
+fn write_rivers(state: &WorldState, dir: &Path) -> Result<()> {
+    let reaches = hydro_finalize::river_reaches(state);
+    let collection = FeatureCollection {
+        kind: "FeatureCollection",
+        features: reaches
+            .iter()
+            .map(|r| LineFeature {
+                kind: "Feature",
+                geometry: LineString {
+                    kind: "LineString",
+                    coordinates: &r.points,
+                },
+                properties: RiverProperties {
+                    class: r.class,
+                    navigation: r.navigation,
+                    navigable: r.navigation.is_navigable(),
+                    crossable: r.navigation.is_crossable(),
+                    discharge: r.discharge,
+                },
+            })
+            .collect(),
+    };
+    fs::write(
+        dir.join("rivers.geojson"),
+        serde_json::to_string_pretty(&collection)?,
+    )?;
+    Ok(())
+}
+
+/// `caves.json` (depth bands and surface openings) plus one map per band
+/// under `caves/`, coloured by cave kind.
+fn write_caves(state: &WorldState, dir: &Path) -> Result<()> {
+    let cave_dir = dir.join("caves");
+    fs::create_dir_all(&cave_dir)
+        .with_context(|| format!("creating export dir {}", cave_dir.display()))?;
+    let mut layers = Vec::with_capacity(state.cave_layers.len());
+    for (i, layer) in state.cave_layers.iter().enumerate() {
+        let file = format!("caves/layer_{i}.png");
+        write_category_png(state, dir.join(&file), |x, y| {
+            caves::cave_color(*layer.cave.get(x, y))
+        })?;
+        layers.push(CaveLayerExport {
+            top_m: layer.top_m,
+            bottom_m: layer.bottom_m,
+            file,
+        });
+    }
+    fs::write(
+        dir.join("caves.json"),
+        serde_json::to_string_pretty(&CavesExport {
+            layers,
+            openings: &state.cave_openings,
+        })?,
+    )?;
+    Ok(())
+}
+
+/// One greyscale map and raw float grid per hazard layer under `hazards/`.
+fn write_hazards(state: &WorldState, dir: &Path) -> Result<()> {
+    let hazard_dir = dir.join("hazards");
+    fs::create_dir_all(&hazard_dir)
+        .with_context(|| format!("creating export dir {}", hazard_dir.display()))?;
+    for hazard in Hazard::ALL {
+        let grid = state.hazard(hazard);
+        write_float_layer_png(grid, hazard_dir.join(format!("{}.png", hazard.key())))?;
+        write_f32_raw(grid, hazard_dir.join(format!("{}.f32", hazard.key())))?;
+    }
+    Ok(())
+}
+
+/// `fauna.json` (per-species summary and every sampled site) plus one
+/// suitability map per species under `habitat/`.
+fn write_fauna(state: &WorldState, dir: &Path) -> Result<()> {
+    let habitat_dir = dir.join("habitat");
+    fs::create_dir_all(&habitat_dir)
+        .with_context(|| format!("creating export dir {}", habitat_dir.display()))?;
+
+    let mut species = Vec::with_capacity(state.habitat_suitability.len());
+    for (id, grid) in state.habitat_suitability.iter().enumerate() {
+        let def = state.creature_catalogue.def(id);
+        let map = format!("habitat/{}.png", slug(&def.name));
+        write_float_layer_png(grid, dir.join(&map))?;
+        let sites = state.creature_sites.iter().filter(|s| s.species == id);
+        species.push(SpeciesSummary {
+            id,
+            name: &def.name,
+            habitat: def.habitat,
+            rarity: def.rarity,
+            range_tiles: grid.as_slice().iter().filter(|&&v| v > 0.0).count(),
+            sites: sites.clone().count(),
+            population: sites.map(|s| u64::from(s.population)).sum(),
+            map,
+        });
+    }
+
+    let fauna = FaunaExport {
+        species,
+        sites: &state.creature_sites,
+    };
+    fs::write(
+        dir.join("fauna.json"),
+        serde_json::to_string_pretty(&fauna)?,
+    )?;
+    Ok(())
+}
+
 fn write_float_layer_png(grid: &Grid2D<f32>, path: impl AsRef<Path>) -> Result<()> {
     let min = grid
         .as_slice()
@@ -77,6 +511,18 @@ fn write_float_layer_png(grid: &Grid2D<f32>, path: impl AsRef<Path>) -> Result<(
     Ok(())
 }
 
+/// Little-endian `u32` width and height, then the values row by row as `f32`.
+fn write_f32_raw(grid: &Grid2D<f32>, path: impl AsRef<Path>) -> Result<()> {
+    let mut bytes = Vec::with_capacity(8 + grid.len() * 4);
+    bytes.extend_from_slice(&(grid.width() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(grid.height() as u32).to_le_bytes());
+    for v in grid.as_slice() {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
 fn write_river_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()> {
     let mut img = ImageBuffer::new(state.width as u32, state.height as u32);
     for y in 0..state.height {
@@ -112,11 +558,11 @@ fn write_ocean_lake_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()
     Ok(())
 }
 
-fn write_biome_png(grid: &Grid2D<Biome>, path: impl AsRef<Path>) -> Result<()> {
-    let mut img = ImageBuffer::new(grid.width() as u32, grid.height() as u32);
-    for y in 0..grid.height() {
-        for x in 0..grid.width() {
-            let c = biome_color(*grid.get(x, y));
+fn write_biome_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()> {
+    let mut img = ImageBuffer::new(state.width as u32, state.height as u32);
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let c = state.biome_table.color(*state.biome.get(x, y));
             img.put_pixel(x as u32, y as u32, Rgb(c));
         }
     }
@@ -124,12 +570,65 @@ fn write_biome_png(grid: &Grid2D<Biome>, path: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+/// RGBA where each channel is the weight of one blend slot; the matching biome
+/// ids are in `biome_blend.bin`.
+fn write_biome_blend_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()> {
+    let mut img = ImageBuffer::new(state.width as u32, state.height as u32);
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let blend = state.biome_blend.get(x, y);
+            let c = blend
+                .weights
+                .map(|w| (w * 255.0).round().clamp(0.0, 255.0) as u8);
+            img.put_pixel(x as u32, y as u32, Rgba(c));
+        }
+    }
+    img.save(path)?;
+    Ok(())
+}
+
+/// Little-endian `u32` width, height and slot count, then per tile (row-major)
+/// the slot biome ids as `u8` followed by the slot weights as `f32`.
+fn write_biome_blend_bin(state: &WorldState, path: impl AsRef<Path>) -> Result<()> {
+    let slots = BIOME_BLEND_SLOTS;
+    let mut bytes = Vec::with_capacity(12 + state.width * state.height * slots * 5);
+    bytes.extend_from_slice(&(state.width as u32).to_le_bytes());
+    bytes.extend_from_slice(&(state.height as u32).to_le_bytes());
+    bytes.extend_from_slice(&(slots as u32).to_le_bytes());
+    for blend in state.biome_blend.as_slice() {
+        bytes.extend(blend.biomes.iter().map(|b| b.as_u8()));
+        for w in blend.weights {
+            bytes.extend_from_slice(&w.to_le_bytes());
+        }
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+fn write_category_png(
+    state: &WorldState,
+    path: impl AsRef<Path>,
+    color: impl Fn(usize, usize) -> [u8; 3],
+) -> Result<()> {
+    let mut img = ImageBuffer::new(state.width as u32, state.height as u32);
+    for y in 0..state.height {
+        for x in 0..state.width {
+            img.put_pixel(x as u32, y as u32, Rgb(color(x, y)));
+        }
+    }
+    img.save(path)?;
+    Ok(())
+}
+
 fn write_final_map_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()> {
+    let sea_level = state.params.base.sea_level;
+    let max_depth = state.params.base.trench_depth.max(1e-3);
     let mut img = ImageBuffer::new(state.width as u32, state.height as u32);
 
     for y in 0..state.height {
         for x in 0..state.width {
-            let mut c = biome_color(*state.biome.get(x, y));
+            let biome = *state.biome.get(x, y);
+            let mut c = state.biome_table.blend_color(state.biome_blend.get(x, y));
 
             let grad_x = if x > 0 && x + 1 < state.width {
                 *state.elevation.get(x + 1, y) - *state.elevation.get(x - 1, y)
@@ -145,11 +644,27 @@ fn write_final_map_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()>
             c = scale_rgb(c, shade);
 
             if *state.ocean_mask.get(x, y) {
-                c = [12, 44, 118];
+                let depth = (sea_level - *state.elevation.get(x, y)).max(0.0);
+                let t = (depth / max_depth).clamp(0.0, 1.0).sqrt();
+                c = blend_rgb([64, 150, 200], [4, 18, 70], t);
+                if biome != state.biome_table.ocean() {
+                    // Marine biomes such as coral reefs show through the water.
+                    c = blend_rgb(c, state.biome_table.color(biome), 0.6);
+                } else if *state.ocean_zone.get(x, y) == OceanZone::ReefShallows {
+                    c = blend_rgb(c, [80, 220, 210], 0.35);
+                }
+                c = scale_rgb(c, 0.8 + shade * 0.4);
             } else if *state.lake_id.get(x, y) > 0 {
                 c = [44, 108, 196];
             }
 
+            c = match *state.ice_cover.get(x, y) {
+                IceCover::None => c,
+                IceCover::Snowpack => blend_rgb(c, [240, 244, 250], 0.5),
+                IceCover::Glacier => blend_rgb(c, [220, 236, 250], 0.75),
+                IceCover::SeaIce => blend_rgb(c, [226, 236, 246], *state.sea_ice.get(x, y) * 0.85),
+            };
+
             c = match *state.river_class.get(x, y) {
                 RiverClass::None => c,
                 RiverClass::Ephemeral => blend_rgb(c, [92, 170, 252], 0.18),
@@ -165,25 +680,6 @@ fn write_final_map_png(state: &WorldState, path: impl AsRef<Path>) -> Result<()>
     Ok(())
 }
 
-fn biome_color(biome: Biome) -> [u8; 3] {
-    match biome {
-        Biome::Ocean => [18, 52, 120],
-        Biome::Lake => [42, 98, 190],
-        Biome::PolarDesert => [230, 230, 218],
-        Biome::Tundra => [176, 190, 156],
-        Biome::BorealForest => [66, 102, 63],
-        Biome::TemperateGrassland => [158, 186, 102],
-        Biome::TemperateForest => [74, 136, 80],
-        Biome::Mediterranean => [146, 152, 84],
-        Biome::Savanna => [186, 172, 78],
-        Biome::TropicalSeasonalForest => [88, 144, 70],
-        Biome::TropicalRainforest => [42, 116, 52],
-        Biome::HotDesert => [214, 188, 126],
-        Biome::Alpine => [136, 136, 140],
-        Biome::Wetland => [76, 138, 120],
-    }
-}
-
 fn scale_rgb(c: [u8; 3], k: f32) -> [u8; 3] {
     [
         (f32::from(c[0]) * k).clamp(0.0, 255.0) as u8,
@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::catalogue::{in_range, slug, ValueRange};
+use crate::state::{DepositKind, GeologicProvince, Mineral, RockType, VolcanoKind};
+
+const BUILTIN_GEOLOGY_JSON: &str = include_str!("../assets/geology.json");
+
+/// Land share of ore for a mineral of rarity 0 at `ore_richness` 0.5.
+const MAX_ORE_SHARE: f32 = 0.05;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RockDef {
+    pub name: String,
+    pub color: [u8; 3],
+    /// Relative rate at which the rock weathers into soil; 1 is average.
+    #[serde(default = "default_weathering")]
+    pub weathering: f32,
+    /// Porous or fractured enough to hold and yield groundwater.
+    #[serde(default)]
+    pub aquifer: bool,
+    /// Dissolves in groundwater, so karst caves form in it.
+    #[serde(default)]
+    pub soluble: bool,
+    /// Extrusive igneous rock; weathers into andosols.
+    #[serde(default)]
+    pub volcanic: bool,
+    /// Relative weight of the rock among the strata of each province.
+    #[serde(default)]
+    pub provinces: BTreeMap<GeologicProvince, f32>,
+    /// Depths in metres where layers of this rock may start.
+    #[serde(default)]
+    pub depth_m: Option<ValueRange>,
+    /// Kinds of volcano whose flows are made of this rock.
+    #[serde(default)]
+    pub lava: Vec<VolcanoKind>,
+}
+
+fn default_weathering() -> f32 {
+    1.0
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MineralDef {
+    pub name: String,
+    pub color: [u8; 3],
+    /// `vein` or `stratiform`.
+    pub style: DepositKind,
+    /// 0 (common) ..1 (legendary); scales down the share of land with ore.
+    #[serde(default)]
+    pub rarity: f32,
+    /// Mass fraction of the mineral in typical ore.
+    pub grade: f32,
+    /// Typical grade of the placers the mineral's veins shed downstream;
+    /// absent for minerals that form none.
+    #[serde(default)]
+    pub placer_grade: Option<f32>,
+    /// Favourability bonus per host rock name.
+    #[serde(default)]
+    pub hosts: BTreeMap<String, f32>,
+    /// Favourability bonus per province.
+    #[serde(default)]
+    pub provinces: BTreeMap<GeologicProvince, f32>,
+    /// Depths in metres where the ore may lie.
+    #[serde(default)]
+    pub depth_m: Option<ValueRange>,
+}
+
+impl MineralDef {
+    /// Share of land tiles carrying ore of this mineral; `ore_richness` 0.5
+    /// gives the base share and 0 a barren world.
+    pub fn ore_share(&self, ore_richness: f32) -> f32 {
+        MAX_ORE_SHARE * (1.0 - self.rarity).powi(2) * 2.0 * ore_richness.max(0.0)
+    }
+
+    pub fn province_bonus(&self, province: GeologicProvince) -> f32 {
+        self.provinces.get(&province).copied().unwrap_or(0.0)
+    }
+
+    /// Typical grade of deposits of `kind`.
+    pub fn typical_grade(&self, kind: DepositKind) -> f32 {
+        match kind {
+            DepositKind::Placer => self.placer_grade.unwrap_or(self.grade),
+            DepositKind::Vein | DepositKind::Stratiform => self.grade,
+        }
+    }
+
+    /// Leanest typical grade over the deposit kinds the mineral forms.
+    pub fn leanest_grade(&self) -> f32 {
+        self.placer_grade.map_or(self.grade, |p| p.min(self.grade))
+    }
+}
+
+#[derive(Deserialize)]
+struct GeologyFile {
+    rocks: Vec<RockDef>,
+    minerals: Vec<MineralDef>,
+}
+
+/// Rock and mineral definitions in file order; a `RockType` or `Mineral` is
+/// an index into its list.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeologyCatalogue {
+    rocks: Vec<RockDef>,
+    minerals: Vec<MineralDef>,
+    /// Grid key per mineral: its name in lowercase with other characters
+    /// replaced by `_`.
+    keys: Vec<String>,
+    /// Host bonus by mineral, then rock.
+    host_bonus: Vec<Vec<f32>>,
+}
+
+impl GeologyCatalogue {
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_GEOLOGY_JSON).expect("built-in geology catalogue is valid")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading geology catalogue {}", path.display()))?;
+        Self::from_json(&text)
+            .with_context(|| format!("parsing geology catalogue {}", path.display()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let file: GeologyFile = serde_json::from_str(text)?;
+        let limit = usize::from(u8::MAX) + 1;
+        if file.rocks.is_empty() || file.rocks.len() > limit {
+            bail!("1 to 256 rocks are supported, got {}", file.rocks.len());
+        }
+        if file.minerals.len() > limit {
+            bail!(
+                "at most 256 minerals are supported, got {}",
+                file.minerals.len()
+            );
+        }
+        for (i, rock) in file.rocks.iter().enumerate() {
+            if file.rocks[..i].iter().any(|r| r.name == rock.name) {
+                bail!("duplicate rock name {:?}", rock.name);
+            }
+        }
+        for province in GeologicProvince::ALL {
+            if !file
+                .rocks
+                .iter()
+                .any(|r| r.provinces.get(&province).is_some_and(|&w| w > 0.0))
+            {
+                bail!("no rock occurs in province {province:?}");
+            }
+        }
+
+        let mut keys: Vec<String> = Vec::with_capacity(file.minerals.len());
+        let mut host_bonus = Vec::with_capacity(file.minerals.len());
+        for mineral in &file.minerals {
+            let key = slug(&mineral.name);
+            if keys.contains(&key) {
+                bail!("duplicate mineral name {:?}", mineral.name);
+            }
+            keys.push(key);
+            if !(0.0..=1.0).contains(&mineral.rarity) {
+                bail!("mineral {:?} rarity must be within 0..=1", mineral.name);
+            }
+            if mineral.style == DepositKind::Placer {
+                bail!(
+                    "mineral {:?} style must be \"vein\" or \"stratiform\"; placers come from placer_grade",
+                    mineral.name
+                );
+            }
+            if mineral.grade <= 0.0 || mineral.placer_grade.is_some_and(|g| g <= 0.0) {
+                bail!("mineral {:?} grades must be positive", mineral.name);
+            }
+            let mut bonus = vec![0.0; file.rocks.len()];
+            for (host, &b) in &mineral.hosts {
+                let Some(r) = file.rocks.iter().position(|r| &r.name == host) else {
+                    bail!(
+                        "mineral {:?} names unknown host rock {host:?}",
+                        mineral.name
+                    );
+                };
+                bonus[r] = b;
+            }
+            host_bonus.push(bonus);
+        }
+
+        Ok(Self {
+            rocks: file.rocks,
+            minerals: file.minerals,
+            keys,
+            host_bonus,
+        })
+    }
+
+    pub fn rock(&self, rock: RockType) -> &RockDef {
+        &self.rocks[usize::from(rock.as_u8())]
+    }
+
+    pub fn mineral(&self, mineral: Mineral) -> &MineralDef {
+        &self.minerals[usize::from(mineral.as_u8())]
+    }
+
+    pub fn rock_count(&self) -> usize {
+        self.rocks.len()
+    }
+
+    pub fn mineral_count(&self) -> usize {
+        self.minerals.len()
+    }
+
+    pub fn rocks(&self) -> impl Iterator<Item = (RockType, &RockDef)> + '_ {
+        self.rocks
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (RockType(i as u8), d))
+    }
+
+    pub fn minerals(&self) -> impl Iterator<Item = (Mineral, &MineralDef)> + '_ {
+        self.minerals
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (Mineral(i as u8), d))
+    }
+
+    pub fn find_rock(&self, name: &str) -> Option<RockType> {
+        self.rocks
+            .iter()
+            .position(|d| d.name == name)
+            .map(|i| RockType(i as u8))
+    }
+
+    pub fn find_mineral(&self, name: &str) -> Option<Mineral> {
+        self.minerals
+            .iter()
+            .position(|d| d.name == name)
+            .map(|i| Mineral(i as u8))
+    }
+
+    /// Key of the mineral's grids in `WorldState::mineral_masks` and its
+    /// siblings.
+    pub fn mineral_key(&self, mineral: Mineral) -> &str {
+        &self.keys[usize::from(mineral.as_u8())]
+    }
+
+    pub fn host_bonus(&self, mineral: Mineral, rock: RockType) -> f32 {
+        self.host_bonus[usize::from(mineral.as_u8())][usize::from(rock.as_u8())]
+    }
+
+    /// Rock of a layer starting at `depth_m` in `province`, chosen by
+    /// `n` (0..1) over the weights of the rocks allowed there. When no rock
+    /// allows the depth, the depth limits are ignored.
+    pub fn pick_rock(&self, province: GeologicProvince, depth_m: f32, n: f32) -> RockType {
+        let weight = |def: &RockDef, check_depth: bool| {
+            let w = def
+                .provinces
+                .get(&province)
+                .copied()
+                .unwrap_or(0.0)
+                .max(0.0);
+            if check_depth && !in_range(def.depth_m, depth_m) {
+                0.0
+            } else {
+                w
+            }
+        };
+        let mut total: f32 = self.rocks.iter().map(|d| weight(d, true)).sum();
+        let check_depth = total > 0.0;
+        if !check_depth {
+            total = self.rocks.iter().map(|d| weight(d, false)).sum();
+        }
+        let mut target = n.clamp(0.0, 1.0) * total;
+        let mut last = RockType(0);
+        for (rock, def) in self.rocks() {
+            let w = weight(def, check_depth);
+            if w <= 0.0 {
+                continue;
+            }
+            last = rock;
+            if target < w {
+                return rock;
+            }
+            target -= w;
+        }
+        last
+    }
+
+    /// Rock of a lava flow from a volcano of `kind`, chosen by `n` (0..1)
+    /// among the rocks listing it; `None` when no rock does.
+    pub fn lava_rock(&self, kind: VolcanoKind, n: f32) -> Option<RockType> {
+        let flows: Vec<RockType> = self
+            .rocks()
+            .filter(|(_, d)| d.lava.contains(&kind))
+            .map(|(r, _)| r)
+            .collect();
+        let i = (n.clamp(0.0, 1.0) * flows.len() as f32) as usize;
+        flows.get(i.min(flows.len().saturating_sub(1))).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeologyCatalogue;
+
+    #[test]
+    fn unknown_host_rocks_are_rejected() {
+        let json = r#"{
+            "rocks": [ { "name": "Obsidian", "color": [20, 20, 30], "volcanic": true,
+                         "provinces": { "Oceanic": 1, "Craton": 1, "Orogen": 1,
+                                        "Basin": 1, "VolcanicArc": 1 } } ],
+            "minerals": [ { "name": "Mithril", "color": [200, 220, 255], "style": "vein",
+                            "grade": 1e-6, "hosts": { "Adamantine": 0.2 } } ]
+        }"#;
+        assert!(GeologyCatalogue::from_json(json).is_err());
+
+        let fixed = json.replace("Adamantine", "Obsidian");
+        let catalogue = GeologyCatalogue::from_json(&fixed).expect("valid catalogue");
+        let mithril = catalogue.find_mineral("Mithril").expect("mithril defined");
+        assert_eq!(catalogue.mineral_key(mithril), "mithril");
+        assert!(GeologyCatalogue::builtin().mineral_count() > 0);
+    }
+}
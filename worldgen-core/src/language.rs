@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::rng::SplitMix64;
+use crate::state::PlaceKind;
+
+const BUILTIN_LANGUAGES_JSON: &str = include_str!("../assets/languages.json");
+
+/// Name templates per feature kind, ordered from the smallest features to
+/// the largest. `{name}` is replaced by a generated word and `{biome}` by the
+/// region's biome name.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LanguageForms {
+    pub region: Vec<String>,
+    pub lake: Vec<String>,
+    pub ocean: Vec<String>,
+    pub river: Vec<String>,
+    pub mountains: Vec<String>,
+    #[serde(default = "default_peak_forms")]
+    pub peak: Vec<String>,
+    #[serde(default = "default_pass_forms")]
+    pub pass: Vec<String>,
+}
+
+fn default_peak_forms() -> Vec<String> {
+    vec!["{name} Hill".to_string(), "Mount {name}".to_string()]
+}
+
+fn default_pass_forms() -> Vec<String> {
+    vec!["{name} Gap".to_string(), "{name} Pass".to_string()]
+}
+
+/// A naming culture: syllable tables that build words, and the forms those
+/// words are set in.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Language {
+    pub name: String,
+    pub onsets: Vec<String>,
+    pub nuclei: Vec<String>,
+    /// May include `""` for open syllables.
+    pub codas: Vec<String>,
+    /// Inclusive `[min, max]` syllables per word.
+    pub syllables: [u32; 2],
+    pub forms: LanguageForms,
+}
+
+impl Language {
+    /// One capitalised word.
+    pub fn word(&self, rng: &mut SplitMix64) -> String {
+        let [min, max] = self.syllables;
+        let count = min + (rng.next_u64() % u64::from(max - min + 1)) as u32;
+        let mut word = String::new();
+        for _ in 0..count {
+            word.push_str(pick(&self.onsets, rng));
+            word.push_str(pick(&self.nuclei, rng));
+            word.push_str(pick(&self.codas, rng));
+        }
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => word,
+        }
+    }
+
+    pub fn forms(&self, kind: PlaceKind) -> &[String] {
+        match kind {
+            PlaceKind::Region => &self.forms.region,
+            PlaceKind::Lake => &self.forms.lake,
+            PlaceKind::Ocean => &self.forms.ocean,
+            PlaceKind::River => &self.forms.river,
+            PlaceKind::Mountains => &self.forms.mountains,
+            PlaceKind::Peak => &self.forms.peak,
+            PlaceKind::Pass => &self.forms.pass,
+        }
+    }
+}
+
+fn pick<'a>(options: &'a [String], rng: &mut SplitMix64) -> &'a str {
+    &options[(rng.next_u64() % options.len() as u64) as usize]
+}
+
+#[derive(Deserialize)]
+struct LanguageFile {
+    languages: Vec<Language>,
+}
+
+/// Languages in file order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LanguageCatalogue {
+    languages: Vec<Language>,
+}
+
+impl LanguageCatalogue {
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_LANGUAGES_JSON).expect("built-in language catalogue is valid")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading language catalogue {}", path.display()))?;
+        Self::from_json(&text)
+            .with_context(|| format!("parsing language catalogue {}", path.display()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let file: LanguageFile = serde_json::from_str(text)?;
+        if file.languages.is_empty() {
+            bail!("language catalogue defines no languages");
+        }
+        for lang in &file.languages {
+            if lang.onsets.is_empty() || lang.nuclei.is_empty() || lang.codas.is_empty() {
+                bail!("language {:?} needs onsets, nuclei and codas", lang.name);
+            }
+            let [min, max] = lang.syllables;
+            if min == 0 || min > max {
+                bail!("language {:?} syllables must be 1 <= min <= max", lang.name);
+            }
+            for kind in PlaceKind::ALL {
+                let forms = lang.forms(kind);
+                if forms.is_empty() || forms.iter().any(|f| !f.contains("{name}")) {
+                    bail!(
+                        "language {:?} {kind:?} forms must be non-empty and contain {{name}}",
+                        lang.name
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            languages: file.languages,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.languages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+
+    pub fn language(&self, index: usize) -> &Language {
+        &self.languages[index]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Language> + '_ {
+        self.languages.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LanguageCatalogue;
+    use crate::rng::SplitMix64;
+
+    #[test]
+    fn forms_without_name_placeholder_are_rejected() {
+        let json = r#"{ "languages": [ {
+            "name": "Test", "onsets": ["k"], "nuclei": ["a"], "codas": [""],
+            "syllables": [1, 2],
+            "forms": { "region": ["{biome}"], "lake": ["{name}"], "ocean": ["{name}"],
+                       "river": ["{name}"], "mountains": ["{name}"] }
+        } ] }"#;
+        assert!(LanguageCatalogue::from_json(json).is_err());
+
+        let builtin = LanguageCatalogue::builtin();
+        let word = |seed| builtin.language(0).word(&mut SplitMix64::new(seed));
+        assert_eq!(word(7), word(7));
+    }
+}
@@ -1,9 +1,15 @@
+pub mod biome_table;
+pub mod catalogue;
+pub mod creature_catalogue;
 pub mod export;
+pub mod geology_catalogue;
 pub mod grid;
+pub mod language;
 pub mod params;
 pub mod rng;
 pub mod scheduler;
 pub mod state;
+pub mod strata;
 pub mod systems;
 pub mod time;
 
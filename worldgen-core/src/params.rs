@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -18,52 +20,477 @@ impl MapSizePreset {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BaseFieldParams {
     pub sea_level: f32,
     pub octaves: u32,
     pub frequency: f32,
     pub warp_strength: f32,
     pub lapse_rate_c_per_km: f32,
+    pub shelf_width: f32,
+    pub shelf_depth: f32,
+    pub abyssal_depth: f32,
+    pub trench_depth: f32,
+    pub seamount_density: f32,
+    pub reef_min_temp_c: f32,
+}
+
+impl Default for BaseFieldParams {
+    fn default() -> Self {
+        Self {
+            sea_level: 0.5,
+            octaves: 6,
+            frequency: 2.1,
+            warp_strength: 0.03,
+            lapse_rate_c_per_km: 6.5,
+            shelf_width: 0.025,
+            shelf_depth: 0.02,
+            abyssal_depth: 0.34,
+            trench_depth: 0.5,
+            seamount_density: 0.5,
+            reef_min_temp_c: 20.0,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ErosionParams {
     pub iterations: u32,
     pub erosion_rate: f32,
     pub deposition_rate: f32,
     pub thermal_rate: f32,
     pub min_slope: f32,
+    pub runoff_coefficient: f32,
+    pub evapotranspiration_rate: f32,
+    pub channel_loss_rate: f32,
+}
+
+impl Default for ErosionParams {
+    fn default() -> Self {
+        Self {
+            iterations: 24,
+            erosion_rate: 0.035,
+            deposition_rate: 0.02,
+            thermal_rate: 0.015,
+            min_slope: 0.0008,
+            runoff_coefficient: 1.5,
+            evapotranspiration_rate: 0.5,
+            channel_loss_rate: 0.01,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CryosphereParams {
+    pub snowline_temp_c: f32,
+    pub sea_ice_temp_c: f32,
+    pub melt_rate: f32,
+    pub ice_thickness_m: f32,
+    pub carve_depth: f32,
+}
+
+impl Default for CryosphereParams {
+    fn default() -> Self {
+        Self {
+            snowline_temp_c: -6.0,
+            sea_ice_temp_c: -4.0,
+            melt_rate: 0.08,
+            ice_thickness_m: 120.0,
+            carve_depth: 0.012,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BiomeParams {
     pub smoothing_passes: u32,
     pub wetness_weight: f32,
+    /// JSON biome definitions; `None` uses the built-in table.
+    pub definitions: Option<PathBuf>,
+    /// Also classify into the `whittaker_biome` comparison layer.
+    pub whittaker: bool,
+    /// Also classify into the `holdridge_zone` comparison layer.
+    pub holdridge: bool,
+    /// Precipitation of an average land tile, used to convert normalized
+    /// rainfall into mm/year for the Whittaker and Holdridge schemes.
+    pub mean_precip_mm: f32,
+    /// Longest open-water fetch considered for wave exposure, as a fraction
+    /// of map width.
+    pub wave_fetch: f32,
+    /// Fill `biome_blend` with soft ecotone weights; when off every tile
+    /// carries only its dominant biome.
+    pub ecotones: bool,
+    /// Scales how far past a classification threshold a biome keeps weight.
+    pub ecotone_width: f32,
+    /// Biomes kept per tile in the blend, 1..=4.
+    pub blend_slots: usize,
+}
+
+impl Default for BiomeParams {
+    fn default() -> Self {
+        Self {
+            smoothing_passes: 2,
+            wetness_weight: 0.4,
+            definitions: None,
+            whittaker: true,
+            holdridge: true,
+            mean_precip_mm: 900.0,
+            wave_fetch: 0.08,
+            ecotones: true,
+            ecotone_width: 1.0,
+            blend_slots: 4,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FireParams {
+    /// Years of fire history to simulate; 0 disables the simulation.
+    pub simulated_years: u32,
+    /// Annual ignition chance of a tile with full fuel in maximum fire weather.
+    pub ignition_rate: f32,
+    /// Chance that fire crosses into a neighbour with full fuel and dryness.
+    pub spread_rate: f32,
+    /// How strongly fires run downwind rather than spreading evenly.
+    pub wind_influence: f32,
+}
+
+impl Default for FireParams {
+    fn default() -> Self {
+        Self {
+            simulated_years: 300,
+            ignition_rate: 0.002,
+            spread_rate: 0.55,
+            wind_influence: 0.8,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HydroFinalizeParams {
     pub ephemeral_threshold: f32,
     pub perennial_threshold: f32,
     pub major_threshold: f32,
+    pub lake_evaporation: f32,
+    pub floodplain_gradient: f32,
+    pub delta_threshold: f32,
+    pub waterfall_drop_m: f32,
+    pub rapids_gradient_m: f32,
+    /// Rivers below this discharge can be forded; at or above it they carry
+    /// small boats, so the two classes meet without a gap.
+    pub small_boat_min_discharge: f32,
+    pub ship_min_discharge: f32,
+}
+
+impl Default for HydroFinalizeParams {
+    fn default() -> Self {
+        Self {
+            ephemeral_threshold: 80.0,
+            perennial_threshold: 260.0,
+            major_threshold: 900.0,
+            lake_evaporation: 0.6,
+            floodplain_gradient: 0.0015,
+            delta_threshold: 1.0,
+            waterfall_drop_m: 150.0,
+            rapids_gradient_m: 90.0,
+            small_boat_min_discharge: 260.0,
+            ship_min_discharge: 900.0,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GeologyParams {
+    /// JSON rock and mineral catalogue; `None` uses the built-in catalogue.
+    pub catalogue: Option<PathBuf>,
     pub strata_layers: u8,
+    /// Scales fault throw and the width of the fault zones veins fill; 0
+    /// leaves faults inert.
     pub fault_strength: f32,
+    /// Thrust faults traced along the strike of mountain belts, where plate
+    /// boundaries would lie.
+    pub boundary_faults: u32,
+    /// Faults along seeded regional lineaments, in two conjugate sets.
+    pub lineament_faults: u32,
     pub ore_richness: f32,
+    /// Edge length of a tile in metres, for ore tonnages.
+    pub tile_size_m: f32,
+    /// Ore bodies smaller than this many tiles are dropped.
+    pub min_deposit_tiles: u32,
+    /// `mineral_masks` flags tiles graded at least this share of the
+    /// mineral's typical ore grade; below 0.2 trace showings count too.
+    pub mask_cutoff: f32,
+}
+
+impl Default for GeologyParams {
+    fn default() -> Self {
+        Self {
+            catalogue: None,
+            strata_layers: 6,
+            fault_strength: 0.5,
+            boundary_faults: 6,
+            lineament_faults: 10,
+            ore_richness: 0.35,
+            tile_size_m: 1000.0,
+            min_deposit_tiles: 3,
+            mask_cutoff: 0.5,
+        }
+    }
+}
+
+/// Stratovolcanoes along the arcs behind boundary faults and shield
+/// volcanoes in tracks over seeded hotspots.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolcanoParams {
+    /// Tiles between stratovolcanoes along an arc.
+    pub arc_spacing_tiles: f32,
+    /// Tiles from a boundary fault to its arc, on the raised side.
+    pub arc_offset_tiles: f32,
+    /// Share of arc volcanoes that are active.
+    pub active_share: f32,
+    /// Share of arc volcanoes that are dormant; the rest are extinct.
+    pub dormant_share: f32,
+    pub hotspots: u32,
+    /// Volcanoes per hotspot track, youngest over the hotspot.
+    pub chain_length: u32,
+    /// Tiles between successive volcanoes of a track.
+    pub chain_spacing_tiles: f32,
+}
+
+impl Default for VolcanoParams {
+    fn default() -> Self {
+        Self {
+            arc_spacing_tiles: 14.0,
+            arc_offset_tiles: 6.0,
+            active_share: 0.25,
+            dormant_share: 0.25,
+            hotspots: 3,
+            chain_length: 6,
+            chain_spacing_tiles: 16.0,
+        }
+    }
+}
+
+/// Cave networks carved in step 5: karst and lava tubes in a near-surface
+/// band, and cavern layers deeper down.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaveParams {
+    /// Depth in metres of the near-surface band holding karst and lava tubes.
+    pub shallow_depth_m: f32,
+    /// Share of tiles with soluble rock in the band that carry passages.
+    pub karst_density: f32,
+    /// Soluble rock within this many metres of the surface can open
+    /// sinkholes.
+    pub sinkhole_depth_m: f32,
+    /// Chance that a karst tile away from streams holds a collapse sinkhole.
+    pub doline_density: f32,
+    pub lava_tubes_per_volcano: u32,
+    /// Tops of the deep cavern layers, metres below the surface.
+    pub cavern_depths_m: Vec<f32>,
+    pub cavern_thickness_m: f32,
+    /// Share of each cavern layer that is open.
+    pub cavern_coverage: f32,
+}
+
+impl Default for CaveParams {
+    fn default() -> Self {
+        Self {
+            shallow_depth_m: 150.0,
+            karst_density: 0.4,
+            sinkhole_depth_m: 25.0,
+            doline_density: 0.01,
+            lava_tubes_per_volcano: 3,
+            cavern_depths_m: vec![300.0, 800.0, 1500.0],
+            cavern_thickness_m: 60.0,
+            cavern_coverage: 0.25,
+        }
+    }
+}
+
+/// Geohazard layers derived at the end of step 5.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HazardParams {
+    /// Tiles over which shaking from a fault falls off by a factor of e.
+    pub quake_reach_tiles: f32,
+    /// Cone radii from a vent that eruptions threaten.
+    pub volcanic_reach_radii: f32,
+    /// Drop to the lowest neighbour, in metres, at which slopes are fully
+    /// prone to failure.
+    pub landslide_drop_m: f32,
+    /// Land higher than this above the sea is out of a tsunami's reach.
+    pub tsunami_height_m: f32,
+    /// Tiles inland a tsunami can run up.
+    pub tsunami_reach_tiles: u32,
+}
+
+impl Default for HazardParams {
+    fn default() -> Self {
+        Self {
+            quake_reach_tiles: 12.0,
+            volcanic_reach_radii: 3.0,
+            landslide_drop_m: 250.0,
+            tsunami_height_m: 40.0,
+            tsunami_reach_tiles: 6,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EcologyParams {
+    pub max_soil_depth_m: f32,
+    /// Mean years since the last stand-replacing disturbance on gentle terrain.
+    pub mean_stand_age_years: f32,
+    /// Noise frequency of disturbance patches; higher gives smaller stands.
+    pub disturbance_patch_scale: f32,
 }
 
+impl Default for EcologyParams {
+    fn default() -> Self {
+        Self {
+            max_soil_depth_m: 6.0,
+            mean_stand_age_years: 120.0,
+            disturbance_patch_scale: 24.0,
+        }
+    }
+}
+
+/// Dwarf Fortress-style savagery and good/evil fields. Coverages are shares
+/// of land tiles; the rest of each field is calm/wild or neutral.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WildernessParams {
+    pub calm_coverage: f32,
+    pub savage_coverage: f32,
+    pub good_coverage: f32,
+    pub evil_coverage: f32,
+    /// 0 = pure noise, 1 = set entirely by distance from fertile lowland.
+    pub remoteness_bias: f32,
+    /// Noise frequency in cycles across the map; higher gives smaller regions.
+    pub patch_scale: f32,
+}
+
+impl Default for WildernessParams {
+    fn default() -> Self {
+        Self {
+            calm_coverage: 0.35,
+            savage_coverage: 0.2,
+            good_coverage: 0.1,
+            evil_coverage: 0.1,
+            remoteness_bias: 0.5,
+            patch_scale: 5.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaunaParams {
+    /// JSON creature catalogue; `None` uses the built-in catalogue.
+    pub catalogue: Option<PathBuf>,
+    /// Sites sampled per unit of summed habitat suitability for a common species.
+    pub site_density: f32,
+    /// Minimum distance in tiles between two sites of the same species.
+    pub min_site_spacing: f32,
+}
+
+impl Default for FaunaParams {
+    fn default() -> Self {
+        Self {
+            catalogue: None,
+            site_density: 0.01,
+            min_site_spacing: 6.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegionParams {
+    /// Land regions smaller than this many tiles merge into the neighbour
+    /// they share the longest border with. Lakes and oceans never merge.
+    pub min_region_tiles: u32,
+}
+
+impl Default for RegionParams {
+    fn default() -> Self {
+        Self {
+            min_region_tiles: 24,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MountainParams {
+    /// Summits with less prominence are folded into their higher neighbour.
+    pub min_peak_prominence_m: f32,
+    /// Peaks at least this prominent head their own range.
+    pub range_prominence_m: f32,
+    /// Ground below this height (metres above sea level) is not part of a
+    /// range, and range heads below it are only hills.
+    pub range_base_m: f32,
+}
+
+impl Default for MountainParams {
+    fn default() -> Self {
+        Self {
+            min_peak_prominence_m: 50.0,
+            range_prominence_m: 400.0,
+            range_base_m: 750.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingParams {
+    /// JSON language catalogue; `None` uses the built-in languages.
+    pub languages: Option<PathBuf>,
+    /// Regions, lakes, rivers and ranges smaller than this many tiles stay
+    /// unnamed. Oceans are always named.
+    pub min_feature_tiles: u32,
+}
+
+impl Default for NamingParams {
+    fn default() -> Self {
+        Self {
+            languages: None,
+            min_feature_tiles: 16,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GenerationParams {
     pub seed: u64,
     pub size: MapSizePreset,
     pub base: BaseFieldParams,
     pub erosion: ErosionParams,
+    pub cryosphere: CryosphereParams,
     pub biomes: BiomeParams,
+    pub fire: FireParams,
     pub hydro: HydroFinalizeParams,
     pub geology: GeologyParams,
+    pub volcanoes: VolcanoParams,
+    pub caves: CaveParams,
+    pub hazards: HazardParams,
+    pub ecology: EcologyParams,
+    pub wilderness: WildernessParams,
+    pub fauna: FaunaParams,
+    pub regions: RegionParams,
+    pub mountains: MountainParams,
+    pub naming: NamingParams,
 }
 
 impl Default for GenerationParams {
@@ -71,34 +498,88 @@ impl Default for GenerationParams {
         Self {
             seed: 42,
             size: MapSizePreset::S512,
-            base: BaseFieldParams {
-                sea_level: 0.5,
-                octaves: 6,
-                frequency: 2.1,
-                warp_strength: 0.03,
-                lapse_rate_c_per_km: 6.5,
-            },
-            erosion: ErosionParams {
-                iterations: 24,
-                erosion_rate: 0.035,
-                deposition_rate: 0.02,
-                thermal_rate: 0.015,
-                min_slope: 0.0008,
-            },
-            biomes: BiomeParams {
-                smoothing_passes: 2,
-                wetness_weight: 0.4,
-            },
-            hydro: HydroFinalizeParams {
-                ephemeral_threshold: 80.0,
-                perennial_threshold: 260.0,
-                major_threshold: 900.0,
-            },
-            geology: GeologyParams {
-                strata_layers: 6,
-                fault_strength: 0.5,
-                ore_richness: 0.35,
-            },
+            base: BaseFieldParams::default(),
+            erosion: ErosionParams::default(),
+            cryosphere: CryosphereParams::default(),
+            biomes: BiomeParams::default(),
+            fire: FireParams::default(),
+            hydro: HydroFinalizeParams::default(),
+            geology: GeologyParams::default(),
+            volcanoes: VolcanoParams::default(),
+            caves: CaveParams::default(),
+            hazards: HazardParams::default(),
+            ecology: EcologyParams::default(),
+            wilderness: WildernessParams::default(),
+            fauna: FaunaParams::default(),
+            regions: RegionParams::default(),
+            mountains: MountainParams::default(),
+            naming: NamingParams::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A params file saved before any of the optional passes existed.
+    const BASELINE_PARAMS: &str = r#"{
+        "seed": 7,
+        "size": "S256",
+        "base": {
+            "sea_level": 0.45,
+            "octaves": 5,
+            "frequency": 2.1,
+            "warp_strength": 0.03,
+            "lapse_rate_c_per_km": 6.5
+        },
+        "erosion": {
+            "iterations": 12,
+            "erosion_rate": 0.035,
+            "deposition_rate": 0.02,
+            "thermal_rate": 0.015,
+            "min_slope": 0.0008
+        },
+        "biomes": { "smoothing_passes": 3, "wetness_weight": 0.4 },
+        "hydro": {
+            "ephemeral_threshold": 80.0,
+            "perennial_threshold": 260.0,
+            "major_threshold": 900.0
+        },
+        "geology": { "strata_layers": 4, "fault_strength": 0.5, "ore_richness": 0.35 }
+    }"#;
+
+    #[test]
+    fn baseline_params_file_loads_with_defaults() {
+        let params: GenerationParams =
+            serde_json::from_str(BASELINE_PARAMS).expect("baseline params");
+        let defaults = GenerationParams::default();
+
+        assert_eq!(params.seed, 7);
+        assert_eq!(params.size, MapSizePreset::S256);
+        assert_eq!(params.base.sea_level, 0.45);
+        assert_eq!(params.erosion.iterations, 12);
+        assert_eq!(params.biomes.smoothing_passes, 3);
+        assert_eq!(params.geology.strata_layers, 4);
+
+        assert_eq!(
+            params.erosion.runoff_coefficient,
+            defaults.erosion.runoff_coefficient
+        );
+        assert_eq!(
+            params.erosion.channel_loss_rate,
+            defaults.erosion.channel_loss_rate
+        );
+        assert_eq!(
+            params.hydro.lake_evaporation,
+            defaults.hydro.lake_evaporation
+        );
+
+        let saved = serde_json::to_string(&params).expect("serialize");
+        let reloaded: GenerationParams = serde_json::from_str(&saved).expect("reload");
+        assert_eq!(
+            serde_json::to_value(&reloaded).expect("value"),
+            serde_json::to_value(&params).expect("value")
+        );
+    }
+}
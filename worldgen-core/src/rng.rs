@@ -27,6 +27,25 @@ impl SplitMix64 {
     pub fn gen_range_f32(&mut self, min: f32, max: f32) -> f32 {
         min + (max - min) * self.next_f32()
     }
+
+    /// Poisson-distributed count with the given mean, by Knuth's product of
+    /// uniforms. Large means are summed in chunks so `exp(-mean)` never
+    /// underflows.
+    pub fn poisson(&mut self, mean: f32) -> u32 {
+        let mut remaining = mean.max(0.0);
+        let mut count = 0;
+        while remaining > 0.0 {
+            let chunk = remaining.min(16.0);
+            remaining -= chunk;
+            let limit = (-chunk).exp();
+            let mut p = self.next_f32();
+            while p > limit {
+                count += 1;
+                p *= self.next_f32();
+            }
+        }
+        count
+    }
 }
 
 #[inline]
@@ -46,3 +65,25 @@ pub fn hash_2d(seed: u64, x: i32, y: i32) -> f32 {
     let v = ((h >> 40) & 0xFF_FFFF) as u32;
     (v as f32) / ((1u32 << 24) as f32)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SplitMix64;
+
+    #[test]
+    fn poisson_matches_its_mean() {
+        let mut rng = SplitMix64::new(7);
+        for mean in [0.3f32, 4.0, 250.0] {
+            let draws = 2_000;
+            let total: u32 = (0..draws).map(|_| rng.poisson(mean)).sum();
+            let sample_mean = total as f32 / draws as f32;
+            // Five standard errors of the sample mean.
+            let tolerance = 5.0 * (mean / draws as f32).sqrt();
+            assert!(
+                (sample_mean - mean).abs() < tolerance,
+                "mean {mean}, sampled {sample_mean}"
+            );
+        }
+        assert_eq!(rng.poisson(0.0), 0);
+    }
+}
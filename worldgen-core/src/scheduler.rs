@@ -11,9 +11,11 @@ pub fn run_step(state: &mut WorldState, step: Step, params: &GenerationParams) -
     match step {
         Step::BaseFields => systems::base_fields::run(state, params),
         Step::ErosionHydrology => systems::erosion_hydrology::run(state, params),
-        Step::Biomes => systems::biomes::run(state, params),
+        Step::Biomes => systems::biomes::run(state, params)?,
         Step::HydroFinalize => systems::hydro_finalize::run(state, params),
-        Step::Geology => systems::geology::run(state, params),
+        Step::Geology => systems::geology::run(state, params)?,
+        Step::Ecology => systems::ecology::run(state, params)?,
+        Step::Regions => systems::regions::run(state, params)?,
     }
     state.current_step = Some(step);
     state.step_timings_ms.insert(step, timer.elapsed_ms());
@@ -28,7 +30,9 @@ pub fn run_next_step(state: &mut WorldState, params: &GenerationParams) -> Resul
         Some(Step::ErosionHydrology) => Some(Step::Biomes),
         Some(Step::Biomes) => Some(Step::HydroFinalize),
         Some(Step::HydroFinalize) => Some(Step::Geology),
-        Some(Step::Geology) => None,
+        Some(Step::Geology) => Some(Step::Ecology),
+        Some(Step::Ecology) => Some(Step::Regions),
+        Some(Step::Regions) => None,
     };
 
     if let Some(step) = next {
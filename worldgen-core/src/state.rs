@@ -3,6 +3,9 @@ use std::collections::BTreeMap;
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
 
+use crate::biome_table::BiomeTable;
+use crate::creature_catalogue::CreatureCatalogue;
+use crate::geology_catalogue::GeologyCatalogue;
 use crate::grid::Grid2D;
 use crate::params::GenerationParams;
 
@@ -13,15 +16,19 @@ pub enum Step {
     Biomes,
     HydroFinalize,
     Geology,
+    Ecology,
+    Regions,
 }
 
 impl Step {
-    pub const ALL: [Step; 5] = [
+    pub const ALL: [Step; 7] = [
         Step::BaseFields,
         Step::ErosionHydrology,
         Step::Biomes,
         Step::HydroFinalize,
         Step::Geology,
+        Step::Ecology,
+        Step::Regions,
     ];
 
     pub fn index(self) -> usize {
@@ -31,6 +38,8 @@ impl Step {
             Step::Biomes => 3,
             Step::HydroFinalize => 4,
             Step::Geology => 5,
+            Step::Ecology => 6,
+            Step::Regions => 7,
         }
     }
 }
@@ -55,45 +64,495 @@ impl RiverClass {
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub enum Biome {
-    Ocean,
-    Lake,
-    PolarDesert,
+pub enum OceanZone {
+    Land,
+    Shelf,
+    ReefShallows,
+    DeepOcean,
+    Trench,
+}
+
+impl OceanZone {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Land => 0,
+            Self::Shelf => 1,
+            Self::ReefShallows => 2,
+            Self::DeepOcean => 3,
+            Self::Trench => 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IceCover {
+    None,
+    Snowpack,
+    Glacier,
+    SeaIce,
+}
+
+impl IceCover {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Snowpack => 1,
+            Self::Glacier => 2,
+            Self::SeaIce => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RiverNavigation {
+    None,
+    Ford,
+    SmallBoats,
+    Ships,
+    Rapids,
+    Waterfall,
+}
+
+impl RiverNavigation {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Ford => 1,
+            Self::SmallBoats => 2,
+            Self::Ships => 3,
+            Self::Rapids => 4,
+            Self::Waterfall => 5,
+        }
+    }
+
+    pub fn is_navigable(self) -> bool {
+        matches!(self, Self::SmallBoats | Self::Ships)
+    }
+
+    pub fn is_crossable(self) -> bool {
+        matches!(self, Self::Ford)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FluvialFeature {
+    None,
+    Floodplain,
+    MeanderBelt,
+    Delta,
+    Estuary,
+}
+
+impl FluvialFeature {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Floodplain => 1,
+            Self::MeanderBelt => 2,
+            Self::Delta => 3,
+            Self::Estuary => 4,
+        }
+    }
+}
+
+/// Index into the world's `BiomeTable`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Biome(pub u8);
+
+impl Biome {
+    pub fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+/// Number of biomes kept per tile in `WorldState::biome_blend`.
+pub const BIOME_BLEND_SLOTS: usize = 4;
+
+/// Up to `BIOME_BLEND_SLOTS` biomes and their weights, strongest first.
+/// Weights sum to 1; unused slots have weight 0.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BiomeBlend {
+    pub biomes: [Biome; BIOME_BLEND_SLOTS],
+    pub weights: [f32; BIOME_BLEND_SLOTS],
+}
+
+impl BiomeBlend {
+    /// Full weight on a single biome.
+    pub fn single(biome: Biome) -> Self {
+        let mut weights = [0.0; BIOME_BLEND_SLOTS];
+        weights[0] = 1.0;
+        Self {
+            biomes: [biome; BIOME_BLEND_SLOTS],
+            weights,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Biome, f32)> + '_ {
+        self.biomes
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .filter(|&(_, w)| w > 0.0)
+    }
+}
+
+/// Whittaker biome from mean annual temperature and precipitation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WhittakerBiome {
+    None,
     Tundra,
     BorealForest,
-    TemperateGrassland,
-    TemperateForest,
-    Mediterranean,
-    Savanna,
-    TropicalSeasonalForest,
+    TemperateGrasslandDesert,
+    WoodlandShrubland,
+    TemperateSeasonalForest,
+    TemperateRainforest,
+    SubtropicalDesert,
+    TropicalSeasonalForestSavanna,
     TropicalRainforest,
-    HotDesert,
-    Alpine,
-    Wetland,
 }
 
-impl Biome {
+impl WhittakerBiome {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Tundra => 1,
+            Self::BorealForest => 2,
+            Self::TemperateGrasslandDesert => 3,
+            Self::WoodlandShrubland => 4,
+            Self::TemperateSeasonalForest => 5,
+            Self::TemperateRainforest => 6,
+            Self::SubtropicalDesert => 7,
+            Self::TropicalSeasonalForestSavanna => 8,
+            Self::TropicalRainforest => 9,
+        }
+    }
+}
+
+/// Holdridge latitudinal region / altitudinal belt, by biotemperature.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HoldridgeBelt {
+    Polar,
+    Subpolar,
+    Boreal,
+    CoolTemperate,
+    WarmTemperate,
+    Subtropical,
+    Tropical,
+}
+
+impl HoldridgeBelt {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Polar => 0,
+            Self::Subpolar => 1,
+            Self::Boreal => 2,
+            Self::CoolTemperate => 3,
+            Self::WarmTemperate => 4,
+            Self::Subtropical => 5,
+            Self::Tropical => 6,
+        }
+    }
+}
+
+/// Holdridge humidity province, by potential evapotranspiration ratio.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HumidityProvince {
+    Superhumid,
+    Perhumid,
+    Humid,
+    Subhumid,
+    Semiarid,
+    Arid,
+    Perarid,
+    Superarid,
+}
+
+impl HumidityProvince {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Superhumid => 0,
+            Self::Perhumid => 1,
+            Self::Humid => 2,
+            Self::Subhumid => 3,
+            Self::Semiarid => 4,
+            Self::Arid => 5,
+            Self::Perarid => 6,
+            Self::Superarid => 7,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HoldridgeZone {
+    None,
+    Zone {
+        belt: HoldridgeBelt,
+        humidity: HumidityProvince,
+    },
+}
+
+impl HoldridgeZone {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Zone { belt, humidity } => 1 + belt.as_u8() * 8 + humidity.as_u8(),
+        }
+    }
+}
+
+/// Soil order, derived from climate, parent rock, slope and drainage.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SoilOrder {
+    None,
+    /// Thin, stony soil on steep or freshly exposed rock.
+    Leptosol,
+    /// Waterlogged organic soil.
+    Peat,
+    /// River-deposited sediment.
+    Alluvium,
+    /// Young soil on volcanic rock.
+    Andosol,
+    /// Dry-land soil with little organic matter.
+    Aridisol,
+    /// Deep, leached iron-rich tropical soil.
+    Laterite,
+    /// Acidic, leached soil under cool, humid forest.
+    Podzol,
+    /// Dark, humus-rich grassland soil.
+    Chernozem,
+    /// Moderately developed temperate brown earth.
+    Cambisol,
+}
+
+impl SoilOrder {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Leptosol => 1,
+            Self::Peat => 2,
+            Self::Alluvium => 3,
+            Self::Andosol => 4,
+            Self::Aridisol => 5,
+            Self::Laterite => 6,
+            Self::Podzol => 7,
+            Self::Chernozem => 8,
+            Self::Cambisol => 9,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Savagery {
+    Calm,
+    Wild,
+    Savage,
+}
+
+impl Savagery {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Calm => 0,
+            Self::Wild => 1,
+            Self::Savage => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Alignment {
+    Evil,
+    Neutral,
+    Good,
+}
+
+impl Alignment {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Evil => 0,
+            Self::Neutral => 1,
+            Self::Good => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionKind {
+    Land,
+    Lake,
+    Ocean,
+}
+
+impl RegionKind {
     pub fn as_u8(self) -> u8 {
         match self {
-            Self::Ocean => 0,
+            Self::Land => 0,
             Self::Lake => 1,
-            Self::PolarDesert => 2,
-            Self::Tundra => 3,
-            Self::BorealForest => 4,
-            Self::TemperateGrassland => 5,
-            Self::TemperateForest => 6,
-            Self::Mediterranean => 7,
-            Self::Savanna => 8,
-            Self::TropicalSeasonalForest => 9,
-            Self::TropicalRainforest => 10,
-            Self::HotDesert => 11,
-            Self::Alpine => 12,
-            Self::Wetland => 13,
+            Self::Ocean => 2,
         }
     }
 }
 
+/// A contiguous patch of one biome, lake or ocean.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Region {
+    /// Index into `WorldState::regions` and value in `region_id`.
+    pub id: u32,
+    pub kind: RegionKind,
+    /// Most common biome; small patches merged in may differ.
+    pub biome: Biome,
+    /// `lake_id` of a lake region, else 0.
+    pub lake_id: u32,
+    /// Tile count.
+    pub area: u32,
+    /// Mean tile position (x, y); may fall outside a concave region.
+    pub centroid: [f32; 2],
+    /// Set when the region is large enough to be named.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// A summit with at least `MountainParams::min_peak_prominence_m` of
+/// topographic prominence.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Peak {
+    /// Index into `WorldState::peaks`.
+    pub id: u32,
+    pub x: usize,
+    pub y: usize,
+    /// Metres above sea level.
+    pub height_m: f32,
+    /// Metres above the key col, or above sea level for a landmass's top.
+    pub prominence_m: f32,
+    /// `MountainRange::id`, or 0 for a hill outside any range.
+    pub range: u32,
+    /// Key col leading to higher ground; `None` for a landmass's top.
+    pub pass: Option<u32>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// The saddle (key col) between a peak and the higher ground beyond it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MountainPass {
+    /// Index into `WorldState::mountain_passes`.
+    pub id: u32,
+    pub x: usize,
+    pub y: usize,
+    /// Metres above sea level.
+    pub height_m: f32,
+    /// Climb from the pass to the lower peak: that peak's prominence.
+    pub prominence_m: f32,
+    /// Lower and higher peak ids.
+    pub peaks: [u32; 2],
+    /// Range of the lower peak, or 0.
+    pub range: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Peaks whose key cols chain up to one head peak of at least
+/// `MountainParams::range_prominence_m`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MountainRange {
+    /// Value in `WorldState::mountain_range`; ids start at 1 and index
+    /// `mountain_ranges[id - 1]`.
+    pub id: u32,
+    pub highest_peak: u32,
+    pub peaks: Vec<u32>,
+    pub passes: Vec<u32>,
+    /// Ridge skeleton: peak, key col, higher peak, for ridges within the range.
+    pub ridges: Vec<[[usize; 2]; 3]>,
+    /// Tiles above `MountainParams::range_base_m` belonging to the range.
+    pub area: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceKind {
+    Region,
+    Lake,
+    Ocean,
+    River,
+    Mountains,
+    Peak,
+    Pass,
+}
+
+impl PlaceKind {
+    pub const ALL: [Self; 7] = [
+        Self::Region,
+        Self::Lake,
+        Self::Ocean,
+        Self::River,
+        Self::Mountains,
+        Self::Peak,
+        Self::Pass,
+    ];
+
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Region => 0,
+            Self::Lake => 1,
+            Self::Ocean => 2,
+            Self::River => 3,
+            Self::Mountains => 4,
+            Self::Peak => 5,
+            Self::Pass => 6,
+        }
+    }
+}
+
+/// A named geographic feature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlaceName {
+    pub kind: PlaceKind,
+    /// Region id for regions, lakes and oceans, river index, or the id of the
+    /// range, peak or pass.
+    pub feature_id: u32,
+    pub name: String,
+    pub language: String,
+    /// Tile count of the feature, or metres of prominence for peaks and
+    /// passes; decides which form the name takes.
+    pub size: u32,
+    /// Tile to label: the centroid region tile, river mouth, highest peak of
+    /// a range, or the peak or pass itself.
+    pub anchor: [usize; 2],
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CreatureSiteKind {
+    Population,
+    Lair,
+}
+
+impl CreatureSiteKind {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Population => 0,
+            Self::Lair => 1,
+        }
+    }
+}
+
+/// A sampled herd, shoal or lair of one species.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CreatureSite {
+    /// Index into `WorldState::creature_catalogue`.
+    pub species: usize,
+    pub kind: CreatureSiteKind,
+    pub x: usize,
+    pub y: usize,
+    pub population: u32,
+    pub suitability: f32,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum GeologicProvince {
     Oceanic,
     Craton,
@@ -112,72 +571,279 @@ impl GeologicProvince {
             Self::VolcanicArc => 4,
         }
     }
+
+    pub const ALL: [GeologicProvince; 5] = [
+        GeologicProvince::Oceanic,
+        GeologicProvince::Craton,
+        GeologicProvince::Orogen,
+        GeologicProvince::Basin,
+        GeologicProvince::VolcanicArc,
+    ];
+}
+
+/// Index into the rocks of the world's `GeologyCatalogue`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RockType(pub u8);
+
+impl RockType {
+    pub fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+/// Index into the minerals of the world's `GeologyCatalogue`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mineral(pub u8);
+
+impl Mineral {
+    pub fn as_u8(self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositKind {
+    /// Steep sheet filling a fault zone, cutting across strata.
+    Vein,
+    /// Bed confined to one stratum.
+    Stratiform,
+    /// Heavy grains reworked downstream into river gravels.
+    Placer,
+}
+
+impl DepositKind {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Vein => 0,
+            Self::Stratiform => 1,
+            Self::Placer => 2,
+        }
+    }
+}
+
+/// A connected ore body. Grade is the mass fraction of the mineral in ore
+/// (so gold at 5 g/t is `5e-6`), tonnage the mass of ore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OreDeposit {
+    /// 1-based, as in `WorldState::ore_deposit_id`.
+    pub id: u32,
+    pub mineral: Mineral,
+    pub kind: DepositKind,
+    pub grade: f32,
+    pub tonnage_t: f32,
+    pub tiles: u32,
+    /// Tile of the richest ore.
+    pub anchor: [usize; 2],
+    /// Depth range below the surface, metres.
+    pub top_m: f32,
+    pub bottom_m: f32,
+    /// Stratum rock of a stratiform deposit.
+    pub host: Option<RockType>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub enum RockType {
-    Basalt,
-    Gabbro,
-    Granite,
-    Sandstone,
-    Limestone,
-    Schist,
-    Gneiss,
-    Shale,
-    Rhyolite,
+pub enum FaultKind {
+    /// Extensional; one block dropped against the other.
+    Normal,
+    /// Thrust along a mountain belt; the hanging wall rides up.
+    Reverse,
+    /// Mostly sideways slip with little vertical throw.
+    StrikeSlip,
 }
 
-impl RockType {
+impl FaultKind {
     pub fn as_u8(self) -> u8 {
         match self {
-            Self::Basalt => 0,
-            Self::Gabbro => 1,
-            Self::Granite => 2,
-            Self::Sandstone => 3,
-            Self::Limestone => 4,
-            Self::Schist => 5,
-            Self::Gneiss => 6,
-            Self::Shale => 7,
-            Self::Rhyolite => 8,
+            Self::Normal => 0,
+            Self::Reverse => 1,
+            Self::StrikeSlip => 2,
         }
     }
 }
 
+/// A fault trace on the map. Throw peaks at mid-length and tapers to zero
+/// at both tips.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Fault {
+    /// 1-based, as in `WorldState::fault_id`.
+    pub id: u32,
+    pub kind: FaultKind,
+    /// Trace vertices in tile coordinates, `[x, y]`.
+    pub points: Vec<[f32; 2]>,
+    /// Greatest vertical offset of strata across the trace, metres.
+    pub throw_m: f32,
+    /// Whether the raised block lies left of the trace walked in point
+    /// order, with y pointing down the map as in the grids.
+    pub upthrown_left: bool,
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub enum Mineral {
-    Iron,
-    Copper,
-    Gold,
-    Tin,
-    Coal,
-    Gem,
+#[serde(rename_all = "snake_case")]
+pub enum VolcanoKind {
+    /// Steep cone of viscous lava and ash above a subduction arc.
+    Stratovolcano,
+    /// Broad dome of fluid basalt over a hotspot.
+    Shield,
 }
 
-impl Mineral {
+impl VolcanoKind {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Stratovolcano => 0,
+            Self::Shield => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VolcanoActivity {
+    Active,
+    Dormant,
+    Extinct,
+}
+
+impl VolcanoActivity {
     pub fn as_u8(self) -> u8 {
         match self {
-            Self::Iron => 0,
-            Self::Copper => 1,
-            Self::Gold => 2,
-            Self::Tin => 3,
-            Self::Coal => 4,
-            Self::Gem => 5,
-        }
-    }
-
-    pub const ALL: [Mineral; 6] = [
-        Mineral::Iron,
-        Mineral::Copper,
-        Mineral::Gold,
-        Mineral::Tin,
-        Mineral::Coal,
-        Mineral::Gem,
+            Self::Active => 0,
+            Self::Dormant => 1,
+            Self::Extinct => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Volcano {
+    /// 1-based, as in `WorldState::volcano_id`.
+    pub id: u32,
+    pub kind: VolcanoKind,
+    pub activity: VolcanoActivity,
+    pub x: usize,
+    pub y: usize,
+    /// Height of the cone above the ground it was built on, metres.
+    pub cone_m: f32,
+    pub radius_tiles: f32,
+    /// Hotspot track, counting from 1; 0 for arc volcanoes.
+    pub hotspot: u32,
+    /// Built up from the sea floor, so its land is oceanic crust.
+    pub oceanic: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CaveKind {
+    None,
+    /// Solution passages in soluble rock.
+    Karst,
+    /// Drained conduits in basalt flows.
+    LavaTube,
+    /// Deep chambers independent of the strata.
+    Cavern,
+}
+
+impl CaveKind {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Karst => 1,
+            Self::LavaTube => 2,
+            Self::Cavern => 3,
+        }
+    }
+}
+
+/// A depth band of the underground with the caves in it per tile; see
+/// `crate::strata` for where within the band a cave lies.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CaveLayer {
+    /// Metres below the ground surface.
+    pub top_m: f32,
+    pub bottom_m: f32,
+    pub cave: Grid2D<CaveKind>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaveOpeningKind {
+    /// Where surface water drains into karst.
+    Sinkhole,
+    /// Where karst water returns to the surface.
+    Spring,
+}
+
+impl CaveOpeningKind {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Sinkhole => 0,
+            Self::Spring => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CaveOpening {
+    /// 1-based.
+    pub id: u32,
+    pub kind: CaveOpeningKind,
+    pub x: usize,
+    pub y: usize,
+    /// The spring a stream sinking here reappears at, or the first sinkhole
+    /// feeding a spring; 0 for isolated sinkholes.
+    pub linked: u32,
+    /// Discharge of the stream swallowed or released, in the units of
+    /// `WorldState::discharge`.
+    pub discharge: f32,
+}
+
+/// Derived geohazard layers, each a risk or exposure from 0 (none) to 1.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Hazard {
+    Earthquake,
+    Volcanic,
+    Landslide,
+    Flood,
+    Tsunami,
+}
+
+impl Hazard {
+    pub const ALL: [Self; 5] = [
+        Self::Earthquake,
+        Self::Volcanic,
+        Self::Landslide,
+        Self::Flood,
+        Self::Tsunami,
     ];
+
+    /// Position of the layer in `ALL` and `WorldState::hazards`.
+    pub fn index(self) -> usize {
+        match self {
+            Self::Earthquake => 0,
+            Self::Volcanic => 1,
+            Self::Landslide => 2,
+            Self::Flood => 3,
+            Self::Tsunami => 4,
+        }
+    }
+
+    /// File and hash key of the layer.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Earthquake => "earthquake_risk",
+            Self::Volcanic => "volcanic_risk",
+            Self::Landslide => "landslide_risk",
+            Self::Flood => "flood_risk",
+            Self::Tsunami => "tsunami_exposure",
+        }
+    }
 }
 
+/// One layer of a column, top first; see `crate::strata` for depth queries.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct StrataLayer {
     pub rock: RockType,
+    /// Metres.
     pub thickness: f32,
 }
 
@@ -207,21 +873,94 @@ pub struct WorldState {
     pub pressure: Grid2D<f32>,
     pub wind_u: Grid2D<f32>,
     pub wind_v: Grid2D<f32>,
+    pub ocean_zone: Grid2D<OceanZone>,
+    pub coast_distance: Grid2D<u32>,
+    pub wave_exposure: Grid2D<f32>,
 
     pub flow_dir: Grid2D<u8>,
     pub accumulation: Grid2D<f32>,
     pub discharge: Grid2D<f32>,
     pub river_class: Grid2D<RiverClass>,
+    pub river_navigation: Grid2D<RiverNavigation>,
+    pub fluvial_feature: Grid2D<FluvialFeature>,
     pub lake_id: Grid2D<u32>,
     pub ocean_mask: Grid2D<bool>,
 
+    pub ice_cover: Grid2D<IceCover>,
+    pub glacier_thickness: Grid2D<f32>,
+    pub sea_ice: Grid2D<f32>,
+    pub meltwater: Grid2D<f32>,
+
+    pub biome_table: BiomeTable,
     pub biome: Grid2D<Biome>,
+    pub biome_blend: Grid2D<BiomeBlend>,
     pub fertility: Grid2D<f32>,
+    pub whittaker_biome: Grid2D<WhittakerBiome>,
+    pub holdridge_zone: Grid2D<HoldridgeZone>,
+    pub fire_frequency: Grid2D<f32>,
+    pub burn_scar_age: Grid2D<f32>,
+    pub fire_return_interval: Grid2D<f32>,
 
+    /// Rocks and minerals the geology step draws from.
+    pub geology_catalogue: GeologyCatalogue,
     pub geologic_province: Grid2D<GeologicProvince>,
     pub strata: Grid2D<Vec<StrataLayer>>,
     pub rock_type: Grid2D<RockType>,
+    /// Tiles whose grade reaches `GeologyParams::mask_cutoff`.
     pub mineral_masks: BTreeMap<String, Grid2D<bool>>,
+    /// Mass fraction of each mineral, keyed like `mineral_masks`; trace
+    /// showings outside deposits are graded too.
+    pub mineral_grade: BTreeMap<String, Grid2D<f32>>,
+    /// Tonnes of contained mineral per deposit tile.
+    pub mineral_reserve_t: BTreeMap<String, Grid2D<f32>>,
+    /// Deposit id per tile, keyed like `mineral_masks`; 0 outside deposits.
+    pub ore_deposit_id: BTreeMap<String, Grid2D<u32>>,
+    pub ore_deposits: Vec<OreDeposit>,
+    pub faults: Vec<Fault>,
+    /// Distance in tiles to the nearest fault trace.
+    pub fault_distance: Grid2D<f32>,
+    /// Id of the nearest fault, 0 when there are none.
+    pub fault_id: Grid2D<u32>,
+    /// Metres the tile's block was raised by faulting, and so stripped from
+    /// the top of its strata column; 0 on dropped blocks.
+    pub fault_uplift_m: Grid2D<f32>,
+    pub volcanoes: Vec<Volcano>,
+    /// Volcano whose cone or lava field covers the tile, 0 for none.
+    pub volcano_id: Grid2D<u32>,
+    /// Thickness of the lava flows capping the strata column, metres.
+    pub lava_thickness_m: Grid2D<f32>,
+    /// Near-surface band first, then the deep cavern layers.
+    pub cave_layers: Vec<CaveLayer>,
+    pub cave_openings: Vec<CaveOpening>,
+    /// Hazard layers indexed like `Hazard::ALL`; zero over water.
+    pub hazards: Vec<Grid2D<f32>>,
+
+    pub soil: Grid2D<SoilOrder>,
+    pub soil_depth: Grid2D<f32>,
+    pub leaf_area_index: Grid2D<f32>,
+    pub vegetation_density: Grid2D<f32>,
+    pub canopy_height: Grid2D<f32>,
+    pub forest_age: Grid2D<f32>,
+
+    /// Share of land tiles that are less savage, 0..1.
+    pub savagery: Grid2D<f32>,
+    /// -1 (most evil) ..1 (most good); the land share below a value is
+    /// `(alignment + 1) / 2`.
+    pub alignment: Grid2D<f32>,
+
+    pub creature_catalogue: CreatureCatalogue,
+    /// Habitat suitability 0..1 per species, indexed like the catalogue.
+    pub habitat_suitability: Vec<Grid2D<f32>>,
+    pub creature_sites: Vec<CreatureSite>,
+
+    pub region_id: Grid2D<u32>,
+    pub regions: Vec<Region>,
+    /// Mountain range id per tile, 0 outside ranges.
+    pub mountain_range: Grid2D<u32>,
+    pub mountain_ranges: Vec<MountainRange>,
+    pub peaks: Vec<Peak>,
+    pub mountain_passes: Vec<MountainPass>,
+    pub place_names: Vec<PlaceName>,
 
     pub current_step: Option<Step>,
     pub step_timings_ms: BTreeMap<Step, f64>,
@@ -232,12 +971,18 @@ pub struct WorldState {
 impl WorldState {
     pub fn new(params: GenerationParams) -> Self {
         let (width, height) = params.size.dimensions();
+        let biome_table = BiomeTable::builtin();
         let mut mineral_masks = BTreeMap::new();
-        for mineral in Mineral::ALL {
-            mineral_masks.insert(
-                format!("{:?}", mineral).to_lowercase(),
-                Grid2D::new(width, height, false),
-            );
+        let mut ore_deposit_id = BTreeMap::new();
+        let mut mineral_grade = BTreeMap::new();
+        let mut mineral_reserve_t = BTreeMap::new();
+        let geology_catalogue = GeologyCatalogue::builtin();
+        for (mineral, _) in geology_catalogue.minerals() {
+            let key = geology_catalogue.mineral_key(mineral).to_string();
+            mineral_masks.insert(key.clone(), Grid2D::new(width, height, false));
+            ore_deposit_id.insert(key.clone(), Grid2D::new(width, height, 0));
+            mineral_grade.insert(key.clone(), Grid2D::new(width, height, 0.0));
+            mineral_reserve_t.insert(key, Grid2D::new(width, height, 0.0));
         }
 
         Self {
@@ -249,18 +994,70 @@ impl WorldState {
             pressure: Grid2D::new(width, height, 0.0),
             wind_u: Grid2D::new(width, height, 0.0),
             wind_v: Grid2D::new(width, height, 0.0),
+            ocean_zone: Grid2D::new(width, height, OceanZone::Land),
+            coast_distance: Grid2D::new(width, height, 0),
+            wave_exposure: Grid2D::new(width, height, 0.0),
             flow_dir: Grid2D::new(width, height, 255),
             accumulation: Grid2D::new(width, height, 0.0),
             discharge: Grid2D::new(width, height, 0.0),
             river_class: Grid2D::new(width, height, RiverClass::None),
+            river_navigation: Grid2D::new(width, height, RiverNavigation::None),
+            fluvial_feature: Grid2D::new(width, height, FluvialFeature::None),
             lake_id: Grid2D::new(width, height, 0),
             ocean_mask: Grid2D::new(width, height, false),
-            biome: Grid2D::new(width, height, Biome::Ocean),
+            ice_cover: Grid2D::new(width, height, IceCover::None),
+            glacier_thickness: Grid2D::new(width, height, 0.0),
+            sea_ice: Grid2D::new(width, height, 0.0),
+            meltwater: Grid2D::new(width, height, 0.0),
+            biome: Grid2D::new(width, height, biome_table.ocean()),
+            biome_blend: Grid2D::new(width, height, BiomeBlend::single(biome_table.ocean())),
+            biome_table,
             fertility: Grid2D::new(width, height, 0.0),
+            whittaker_biome: Grid2D::new(width, height, WhittakerBiome::None),
+            holdridge_zone: Grid2D::new(width, height, HoldridgeZone::None),
+            fire_frequency: Grid2D::new(width, height, 0.0),
+            burn_scar_age: Grid2D::new(width, height, 0.0),
+            fire_return_interval: Grid2D::new(width, height, 0.0),
+            geology_catalogue,
             geologic_province: Grid2D::new(width, height, GeologicProvince::Craton),
             strata: Grid2D::new(width, height, Vec::new()),
-            rock_type: Grid2D::new(width, height, RockType::Granite),
+            rock_type: Grid2D::new(width, height, RockType(0)),
             mineral_masks,
+            mineral_grade,
+            mineral_reserve_t,
+            ore_deposit_id,
+            ore_deposits: Vec::new(),
+            faults: Vec::new(),
+            fault_distance: Grid2D::new(width, height, 0.0),
+            fault_id: Grid2D::new(width, height, 0),
+            fault_uplift_m: Grid2D::new(width, height, 0.0),
+            volcanoes: Vec::new(),
+            volcano_id: Grid2D::new(width, height, 0),
+            lava_thickness_m: Grid2D::new(width, height, 0.0),
+            cave_layers: Vec::new(),
+            cave_openings: Vec::new(),
+            hazards: Hazard::ALL
+                .iter()
+                .map(|_| Grid2D::new(width, height, 0.0))
+                .collect(),
+            soil: Grid2D::new(width, height, SoilOrder::None),
+            soil_depth: Grid2D::new(width, height, 0.0),
+            leaf_area_index: Grid2D::new(width, height, 0.0),
+            vegetation_density: Grid2D::new(width, height, 0.0),
+            canopy_height: Grid2D::new(width, height, 0.0),
+            forest_age: Grid2D::new(width, height, 0.0),
+            savagery: Grid2D::new(width, height, 0.0),
+            alignment: Grid2D::new(width, height, 0.0),
+            creature_catalogue: CreatureCatalogue::builtin(),
+            habitat_suitability: Vec::new(),
+            creature_sites: Vec::new(),
+            region_id: Grid2D::new(width, height, 0),
+            regions: Vec::new(),
+            mountain_range: Grid2D::new(width, height, 0),
+            mountain_ranges: Vec::new(),
+            peaks: Vec::new(),
+            mountain_passes: Vec::new(),
+            place_names: Vec::new(),
             current_step: None,
             step_timings_ms: BTreeMap::new(),
             params,
@@ -268,11 +1065,43 @@ impl WorldState {
         }
     }
 
+    pub fn hazard(&self, hazard: Hazard) -> &Grid2D<f32> {
+        &self.hazards[hazard.index()]
+    }
+
+    /// Species whose habitat covers tile (x, y), most suitable first.
+    pub fn fauna_at(&self, x: usize, y: usize) -> Vec<(&str, f32)> {
+        let mut found: Vec<(&str, f32)> = self
+            .habitat_suitability
+            .iter()
+            .enumerate()
+            .map(|(species, grid)| {
+                let name = self.creature_catalogue.def(species).name.as_str();
+                (name, *grid.get(x, y))
+            })
+            .filter(|&(_, s)| s > 0.0)
+            .collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1));
+        found
+    }
+
     pub fn update_diagnostics(&mut self) {
         let mut hashes = BTreeMap::new();
         hashes.insert("elevation".to_string(), self.hash_f32(&self.elevation));
         hashes.insert("temperature".to_string(), self.hash_f32(&self.temperature));
         hashes.insert("rainfall".to_string(), self.hash_f32(&self.rainfall));
+        hashes.insert(
+            "ocean_zone".to_string(),
+            self.hash_ocean_zone(&self.ocean_zone),
+        );
+        hashes.insert(
+            "coast_distance".to_string(),
+            self.hash_u32(&self.coast_distance),
+        );
+        hashes.insert(
+            "wave_exposure".to_string(),
+            self.hash_f32(&self.wave_exposure),
+        );
         hashes.insert(
             "accumulation".to_string(),
             self.hash_f32(&self.accumulation),
@@ -283,19 +1112,139 @@ impl WorldState {
             "river_class".to_string(),
             self.hash_river_class(&self.river_class),
         );
+        hashes.insert(
+            "river_navigation".to_string(),
+            self.hash_river_navigation(&self.river_navigation),
+        );
+        hashes.insert(
+            "fluvial_feature".to_string(),
+            self.hash_fluvial_feature(&self.fluvial_feature),
+        );
         hashes.insert("lake_id".to_string(), self.hash_u32(&self.lake_id));
         hashes.insert("ocean_mask".to_string(), self.hash_bool(&self.ocean_mask));
+        hashes.insert(
+            "ice_cover".to_string(),
+            self.hash_ice_cover(&self.ice_cover),
+        );
+        hashes.insert(
+            "glacier_thickness".to_string(),
+            self.hash_f32(&self.glacier_thickness),
+        );
+        hashes.insert("sea_ice".to_string(), self.hash_f32(&self.sea_ice));
+        hashes.insert("meltwater".to_string(), self.hash_f32(&self.meltwater));
         hashes.insert("biome".to_string(), self.hash_biome(&self.biome));
+        hashes.insert(
+            "biome_blend".to_string(),
+            self.hash_biome_blend(&self.biome_blend),
+        );
         hashes.insert("fertility".to_string(), self.hash_f32(&self.fertility));
+        hashes.insert(
+            "whittaker_biome".to_string(),
+            self.hash_whittaker(&self.whittaker_biome),
+        );
+        hashes.insert(
+            "holdridge_zone".to_string(),
+            self.hash_holdridge(&self.holdridge_zone),
+        );
+        hashes.insert(
+            "fire_frequency".to_string(),
+            self.hash_f32(&self.fire_frequency),
+        );
+        hashes.insert(
+            "burn_scar_age".to_string(),
+            self.hash_f32(&self.burn_scar_age),
+        );
+        hashes.insert(
+            "fire_return_interval".to_string(),
+            self.hash_f32(&self.fire_return_interval),
+        );
         hashes.insert(
             "province".to_string(),
             self.hash_province(&self.geologic_province),
         );
         hashes.insert("rock_type".to_string(), self.hash_rock(&self.rock_type));
+        hashes.insert("strata".to_string(), self.hash_strata());
 
         for (name, mask) in &self.mineral_masks {
             hashes.insert(format!("mineral_{name}"), self.hash_bool(mask));
         }
+        for (name, grade) in &self.mineral_grade {
+            hashes.insert(format!("mineral_grade_{name}"), self.hash_f32(grade));
+        }
+        for (name, reserve) in &self.mineral_reserve_t {
+            hashes.insert(format!("mineral_reserve_{name}"), self.hash_f32(reserve));
+        }
+        for (name, ids) in &self.ore_deposit_id {
+            hashes.insert(format!("ore_deposit_{name}"), self.hash_u32(ids));
+        }
+        hashes.insert(
+            "ore_deposits".to_string(),
+            self.hash_ore_deposits(&self.ore_deposits),
+        );
+        hashes.insert("faults".to_string(), self.hash_faults(&self.faults));
+        hashes.insert(
+            "fault_distance".to_string(),
+            self.hash_f32(&self.fault_distance),
+        );
+        hashes.insert("fault_id".to_string(), self.hash_u32(&self.fault_id));
+        hashes.insert(
+            "fault_uplift_m".to_string(),
+            self.hash_f32(&self.fault_uplift_m),
+        );
+        hashes.insert(
+            "volcanoes".to_string(),
+            self.hash_volcanoes(&self.volcanoes),
+        );
+        hashes.insert("volcano_id".to_string(), self.hash_u32(&self.volcano_id));
+        hashes.insert(
+            "lava_thickness_m".to_string(),
+            self.hash_f32(&self.lava_thickness_m),
+        );
+        hashes.insert("cave_layers".to_string(), self.hash_cave_layers());
+        for hazard in Hazard::ALL {
+            hashes.insert(hazard.key().to_string(), self.hash_f32(self.hazard(hazard)));
+        }
+        hashes.insert(
+            "cave_openings".to_string(),
+            self.hash_cave_openings(&self.cave_openings),
+        );
+
+        hashes.insert("soil".to_string(), self.hash_soil(&self.soil));
+        hashes.insert("soil_depth".to_string(), self.hash_f32(&self.soil_depth));
+        hashes.insert(
+            "leaf_area_index".to_string(),
+            self.hash_f32(&self.leaf_area_index),
+        );
+        hashes.insert(
+            "vegetation_density".to_string(),
+            self.hash_f32(&self.vegetation_density),
+        );
+        hashes.insert(
+            "canopy_height".to_string(),
+            self.hash_f32(&self.canopy_height),
+        );
+        hashes.insert("forest_age".to_string(), self.hash_f32(&self.forest_age));
+        hashes.insert("savagery".to_string(), self.hash_f32(&self.savagery));
+        hashes.insert("alignment".to_string(), self.hash_f32(&self.alignment));
+        for (species, grid) in self.habitat_suitability.iter().enumerate() {
+            let name = &self.creature_catalogue.def(species).name;
+            hashes.insert(format!("habitat_{name}"), self.hash_f32(grid));
+        }
+        hashes.insert(
+            "creature_sites".to_string(),
+            self.hash_creature_sites(&self.creature_sites),
+        );
+        hashes.insert("region_id".to_string(), self.hash_u32(&self.region_id));
+        hashes.insert("regions".to_string(), self.hash_regions(&self.regions));
+        hashes.insert(
+            "mountain_range".to_string(),
+            self.hash_u32(&self.mountain_range),
+        );
+        hashes.insert("mountains".to_string(), self.hash_mountains());
+        hashes.insert(
+            "place_names".to_string(),
+            self.hash_place_names(&self.place_names),
+        );
 
         let mut combined = Hasher::new();
         for (name, hash) in &hashes {
@@ -336,6 +1285,14 @@ impl WorldState {
         h.finalize().to_hex().to_string()
     }
 
+    fn hash_ocean_zone(&self, grid: &Grid2D<OceanZone>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
     fn hash_river_class(&self, grid: &Grid2D<RiverClass>) -> String {
         let mut h = Hasher::new();
         for v in grid.as_slice() {
@@ -344,6 +1301,30 @@ impl WorldState {
         h.finalize().to_hex().to_string()
     }
 
+    fn hash_ice_cover(&self, grid: &Grid2D<IceCover>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_river_navigation(&self, grid: &Grid2D<RiverNavigation>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_fluvial_feature(&self, grid: &Grid2D<FluvialFeature>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
     fn hash_biome(&self, grid: &Grid2D<Biome>) -> String {
         let mut h = Hasher::new();
         for v in grid.as_slice() {
@@ -352,6 +1333,108 @@ impl WorldState {
         h.finalize().to_hex().to_string()
     }
 
+    fn hash_biome_blend(&self, grid: &Grid2D<BiomeBlend>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            for (b, w) in v.biomes.iter().zip(&v.weights) {
+                h.update(&[b.as_u8()]);
+                h.update(&w.to_bits().to_le_bytes());
+            }
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_whittaker(&self, grid: &Grid2D<WhittakerBiome>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_holdridge(&self, grid: &Grid2D<HoldridgeZone>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_regions(&self, regions: &[Region]) -> String {
+        let mut h = Hasher::new();
+        for r in regions {
+            h.update(&r.id.to_le_bytes());
+            h.update(&[r.kind.as_u8(), r.biome.as_u8()]);
+            h.update(&r.lake_id.to_le_bytes());
+            h.update(&r.area.to_le_bytes());
+            h.update(&r.centroid[0].to_le_bytes());
+            h.update(&r.centroid[1].to_le_bytes());
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_mountains(&self) -> String {
+        let mut h = Hasher::new();
+        for p in &self.peaks {
+            h.update(&(p.x as u64).to_le_bytes());
+            h.update(&(p.y as u64).to_le_bytes());
+            h.update(&p.prominence_m.to_le_bytes());
+            h.update(&p.range.to_le_bytes());
+        }
+        for p in &self.mountain_passes {
+            h.update(&(p.x as u64).to_le_bytes());
+            h.update(&(p.y as u64).to_le_bytes());
+            h.update(&p.peaks[0].to_le_bytes());
+            h.update(&p.peaks[1].to_le_bytes());
+        }
+        for r in &self.mountain_ranges {
+            h.update(&r.id.to_le_bytes());
+            h.update(&r.highest_peak.to_le_bytes());
+            for p in r.peaks.iter().chain(&r.passes) {
+                h.update(&p.to_le_bytes());
+            }
+            for ridge in &r.ridges {
+                for [x, y] in ridge {
+                    h.update(&(*x as u64).to_le_bytes());
+                    h.update(&(*y as u64).to_le_bytes());
+                }
+            }
+            h.update(&r.area.to_le_bytes());
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_place_names(&self, places: &[PlaceName]) -> String {
+        let mut h = Hasher::new();
+        for p in places {
+            h.update(&[p.kind.as_u8()]);
+            h.update(&p.feature_id.to_le_bytes());
+            h.update(p.name.as_bytes());
+            h.update(&[0]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_creature_sites(&self, sites: &[CreatureSite]) -> String {
+        let mut h = Hasher::new();
+        for site in sites {
+            h.update(&(site.species as u64).to_le_bytes());
+            h.update(&[site.kind.as_u8()]);
+            h.update(&(site.x as u64).to_le_bytes());
+            h.update(&(site.y as u64).to_le_bytes());
+            h.update(&site.population.to_le_bytes());
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_soil(&self, grid: &Grid2D<SoilOrder>) -> String {
+        let mut h = Hasher::new();
+        for v in grid.as_slice() {
+            h.update(&[v.as_u8()]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
     fn hash_province(&self, grid: &Grid2D<GeologicProvince>) -> String {
         let mut h = Hasher::new();
         for v in grid.as_slice() {
@@ -360,6 +1443,89 @@ impl WorldState {
         h.finalize().to_hex().to_string()
     }
 
+    fn hash_ore_deposits(&self, deposits: &[OreDeposit]) -> String {
+        let mut h = Hasher::new();
+        for d in deposits {
+            h.update(&d.id.to_le_bytes());
+            h.update(&[d.mineral.as_u8(), d.kind.as_u8()]);
+            h.update(&d.grade.to_bits().to_le_bytes());
+            h.update(&d.tonnage_t.to_bits().to_le_bytes());
+            h.update(&d.tiles.to_le_bytes());
+            h.update(&(d.anchor[0] as u32).to_le_bytes());
+            h.update(&(d.anchor[1] as u32).to_le_bytes());
+            h.update(&d.top_m.to_bits().to_le_bytes());
+            h.update(&d.bottom_m.to_bits().to_le_bytes());
+            h.update(&[d.host.map_or(255, RockType::as_u8)]);
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_faults(&self, faults: &[Fault]) -> String {
+        let mut h = Hasher::new();
+        for f in faults {
+            h.update(&f.id.to_le_bytes());
+            h.update(&[f.kind.as_u8(), u8::from(f.upthrown_left)]);
+            h.update(&f.throw_m.to_bits().to_le_bytes());
+            h.update(&(f.points.len() as u32).to_le_bytes());
+            for [x, y] in &f.points {
+                h.update(&x.to_bits().to_le_bytes());
+                h.update(&y.to_bits().to_le_bytes());
+            }
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_volcanoes(&self, volcanoes: &[Volcano]) -> String {
+        let mut h = Hasher::new();
+        for v in volcanoes {
+            h.update(&v.id.to_le_bytes());
+            h.update(&[v.kind.as_u8(), v.activity.as_u8(), u8::from(v.oceanic)]);
+            h.update(&(v.x as u32).to_le_bytes());
+            h.update(&(v.y as u32).to_le_bytes());
+            h.update(&v.cone_m.to_bits().to_le_bytes());
+            h.update(&v.radius_tiles.to_bits().to_le_bytes());
+            h.update(&v.hotspot.to_le_bytes());
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_cave_layers(&self) -> String {
+        let mut h = Hasher::new();
+        for layer in &self.cave_layers {
+            h.update(&layer.top_m.to_bits().to_le_bytes());
+            h.update(&layer.bottom_m.to_bits().to_le_bytes());
+            for v in layer.cave.as_slice() {
+                h.update(&[v.as_u8()]);
+            }
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_cave_openings(&self, openings: &[CaveOpening]) -> String {
+        let mut h = Hasher::new();
+        for o in openings {
+            h.update(&o.id.to_le_bytes());
+            h.update(&[o.kind.as_u8()]);
+            h.update(&(o.x as u32).to_le_bytes());
+            h.update(&(o.y as u32).to_le_bytes());
+            h.update(&o.linked.to_le_bytes());
+            h.update(&o.discharge.to_bits().to_le_bytes());
+        }
+        h.finalize().to_hex().to_string()
+    }
+
+    fn hash_strata(&self) -> String {
+        let mut h = Hasher::new();
+        for column in self.strata.as_slice() {
+            h.update(&(column.len() as u32).to_le_bytes());
+            for layer in column {
+                h.update(&[layer.rock.as_u8()]);
+                h.update(&layer.thickness.to_bits().to_le_bytes());
+            }
+        }
+        h.finalize().to_hex().to_string()
+    }
+
     fn hash_rock(&self, grid: &Grid2D<RockType>) -> String {
         let mut h = Hasher::new();
         for v in grid.as_slice() {
@@ -0,0 +1,364 @@
+use serde::{Deserialize, Serialize};
+
+use crate::state::{CaveKind, DepositKind, Mineral, RockType, WorldState};
+
+/// A layer placed in its column: depths are metres below the ground surface,
+/// `top_m` inclusive and `bottom_m` exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StrataInterval {
+    pub rock: RockType,
+    pub top_m: f32,
+    pub bottom_m: f32,
+}
+
+impl StrataInterval {
+    pub fn thickness_m(&self) -> f32 {
+        self.bottom_m - self.top_m
+    }
+
+    pub fn contains(&self, depth_m: f32) -> bool {
+        depth_m >= self.top_m && depth_m < self.bottom_m
+    }
+
+    /// The part of this interval between `top_m` and `bottom_m`, if any.
+    pub fn clip(&self, top_m: f32, bottom_m: f32) -> Option<Self> {
+        let top = self.top_m.max(top_m);
+        let bottom = self.bottom_m.min(bottom_m);
+        (top < bottom).then_some(Self {
+            rock: self.rock,
+            top_m: top,
+            bottom_m: bottom,
+        })
+    }
+}
+
+/// Cave passages placed in their column, depths as in `StrataInterval`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaveInterval {
+    pub kind: CaveKind,
+    pub top_m: f32,
+    pub bottom_m: f32,
+}
+
+/// Depth queries against the strata columns built by the geology step. All
+/// of them see an empty column before that step has run, and nothing below
+/// the deepest modelled layer.
+impl WorldState {
+    /// Layers of the column at `(x, y)`, top first, with absolute depths.
+    pub fn strata_column(&self, x: usize, y: usize) -> Vec<StrataInterval> {
+        let mut top = 0.0;
+        self.strata
+            .get(x, y)
+            .iter()
+            .map(|layer| {
+                let interval = StrataInterval {
+                    rock: layer.rock,
+                    top_m: top,
+                    bottom_m: top + layer.thickness,
+                };
+                top = interval.bottom_m;
+                interval
+            })
+            .collect()
+    }
+
+    /// Total modelled thickness of the column at `(x, y)`.
+    pub fn column_depth_m(&self, x: usize, y: usize) -> f32 {
+        self.strata.get(x, y).iter().map(|l| l.thickness).sum()
+    }
+
+    /// Rock at `depth_m` below `(x, y)`, or `None` above the surface or below
+    /// the column.
+    pub fn rock_at_depth(&self, x: usize, y: usize, depth_m: f32) -> Option<RockType> {
+        self.strata_column(x, y)
+            .into_iter()
+            .find(|i| i.contains(depth_m))
+            .map(|i| i.rock)
+    }
+
+    /// Overburden above the first layer of `rock` at `(x, y)`: how far one
+    /// digs to reach it.
+    pub fn depth_to_rock(&self, x: usize, y: usize, rock: RockType) -> Option<f32> {
+        self.strata_column(x, y)
+            .into_iter()
+            .find(|i| i.rock == rock)
+            .map(|i| i.top_m)
+    }
+
+    /// Combined thickness of all layers of `rock` at `(x, y)`.
+    pub fn rock_thickness_m(&self, x: usize, y: usize, rock: RockType) -> f32 {
+        self.strata_column(x, y)
+            .iter()
+            .filter(|i| i.rock == rock)
+            .map(StrataInterval::thickness_m)
+            .sum()
+    }
+
+    /// Layers cut by a borehole from `top_m` to `bottom_m`, clipped to it.
+    pub fn strata_between(
+        &self,
+        x: usize,
+        y: usize,
+        top_m: f32,
+        bottom_m: f32,
+    ) -> Vec<StrataInterval> {
+        self.strata_column(x, y)
+            .iter()
+            .filter_map(|i| i.clip(top_m, bottom_m))
+            .collect()
+    }
+
+    /// Water-bearing layers at `(x, y)`, with adjacent ones kept separate.
+    pub fn aquifers(&self, x: usize, y: usize) -> Vec<StrataInterval> {
+        self.strata_column(x, y)
+            .into_iter()
+            .filter(|i| self.geology_catalogue.rock(i.rock).aquifer)
+            .collect()
+    }
+
+    /// Bedrock layers at `(x, y)` holding ore: the host stratum of a bed, or
+    /// every layer a vein cuts within the mineral's depth range. Placers lie
+    /// in gravel above the column.
+    pub fn ore_intervals(&self, x: usize, y: usize) -> Vec<(Mineral, StrataInterval)> {
+        let column = self.strata_column(x, y);
+        let mut out = Vec::new();
+        for ids in self.ore_deposit_id.values() {
+            let Some(deposit) = (*ids.get(x, y) as usize)
+                .checked_sub(1)
+                .and_then(|i| self.ore_deposits.get(i))
+            else {
+                continue;
+            };
+            match deposit.kind {
+                DepositKind::Vein => {
+                    let (top, bottom) = vein_span(self, deposit.mineral, x, y);
+                    out.extend(
+                        column
+                            .iter()
+                            .filter_map(|i| i.clip(top, bottom))
+                            .map(|i| (deposit.mineral, i)),
+                    );
+                }
+                DepositKind::Stratiform => out.extend(
+                    column
+                        .iter()
+                        .find(|i| Some(i.rock) == deposit.host)
+                        .map(|&i| (deposit.mineral, i)),
+                ),
+                DepositKind::Placer => {}
+            }
+        }
+        out
+    }
+
+    /// Caves under `(x, y)`, top first. Karst lies in the soluble layers of
+    /// its band and lava tubes in the lava capping the column; caverns fill
+    /// their band.
+    pub fn caves(&self, x: usize, y: usize) -> Vec<CaveInterval> {
+        let column = self.strata_column(x, y);
+        let mut out = Vec::new();
+        for layer in &self.cave_layers {
+            let kind = *layer.cave.get(x, y);
+            let interval = |top_m: f32, bottom_m: f32| {
+                let (top_m, bottom_m) = (top_m.max(layer.top_m), bottom_m.min(layer.bottom_m));
+                (top_m < bottom_m).then_some(CaveInterval {
+                    kind,
+                    top_m,
+                    bottom_m,
+                })
+            };
+            match kind {
+                CaveKind::None => {}
+                CaveKind::Karst => out.extend(
+                    column
+                        .iter()
+                        .filter(|i| self.geology_catalogue.rock(i.rock).soluble)
+                        .filter_map(|i| interval(i.top_m, i.bottom_m)),
+                ),
+                CaveKind::LavaTube => {
+                    out.extend(interval(0.0, *self.lava_thickness_m.get(x, y)));
+                }
+                CaveKind::Cavern => out.extend(interval(layer.top_m, layer.bottom_m)),
+            }
+        }
+        out
+    }
+
+    /// Kind of cave open at `depth_m` below `(x, y)`.
+    pub fn cave_at_depth(&self, x: usize, y: usize, depth_m: f32) -> CaveKind {
+        self.caves(x, y)
+            .into_iter()
+            .find(|c| depth_m >= c.top_m && depth_m < c.bottom_m)
+            .map_or(CaveKind::None, |c| c.kind)
+    }
+}
+
+/// Depths between which a vein of `mineral` may run at `(x, y)`: the column,
+/// clipped to the mineral's depth range.
+pub(crate) fn vein_span(state: &WorldState, mineral: Mineral, x: usize, y: usize) -> (f32, f32) {
+    let column = state.column_depth_m(x, y);
+    match state.geology_catalogue.mineral(mineral).depth_m {
+        Some([min, max]) => (
+            min.unwrap_or(0.0).max(0.0),
+            max.map_or(column, |m| m.min(column)),
+        ),
+        None => (0.0, column),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid2D;
+    use crate::state::{CaveKind, CaveLayer, Step, StrataLayer};
+    use crate::{run_step, GenerationParams, MapSizePreset, WorldState};
+
+    #[test]
+    fn depth_queries_follow_layer_thicknesses() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params);
+        let rock = |name| {
+            state
+                .geology_catalogue
+                .find_rock(name)
+                .expect("built-in rock")
+        };
+        let (shale, sandstone, granite, basalt) = (
+            rock("Shale"),
+            rock("Sandstone"),
+            rock("Granite"),
+            rock("Basalt"),
+        );
+        *state.strata.get_mut(3, 4) = vec![
+            StrataLayer {
+                rock: shale,
+                thickness: 10.0,
+            },
+            StrataLayer {
+                rock: sandstone,
+                thickness: 20.0,
+            },
+            StrataLayer {
+                rock: granite,
+                thickness: 15.0,
+            },
+        ];
+
+        assert_eq!(state.rock_at_depth(3, 4, 0.0), Some(shale));
+        assert_eq!(state.rock_at_depth(3, 4, 10.0), Some(sandstone));
+        assert_eq!(state.rock_at_depth(3, 4, 44.9), Some(granite));
+        assert_eq!(state.rock_at_depth(3, 4, 45.0), None);
+        assert_eq!(state.depth_to_rock(3, 4, granite), Some(30.0));
+        assert_eq!(state.depth_to_rock(3, 4, basalt), None);
+
+        let aquifers = state.aquifers(3, 4);
+        assert_eq!(aquifers.len(), 1);
+        assert_eq!((aquifers[0].top_m, aquifers[0].bottom_m), (10.0, 30.0));
+
+        let hole = state.strata_between(3, 4, 5.0, 12.0);
+        assert_eq!(hole.len(), 2);
+        assert_eq!(hole[1].thickness_m(), 2.0);
+    }
+
+    #[test]
+    fn karst_follows_soluble_layers() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params);
+        let rock = |name| {
+            state
+                .geology_catalogue
+                .find_rock(name)
+                .expect("built-in rock")
+        };
+        let (shale, limestone) = (rock("Shale"), rock("Limestone"));
+        *state.strata.get_mut(1, 1) = vec![
+            StrataLayer {
+                rock: shale,
+                thickness: 20.0,
+            },
+            StrataLayer {
+                rock: limestone,
+                thickness: 200.0,
+            },
+        ];
+        let (width, height) = (state.width, state.height);
+        let mut shallow = Grid2D::new(width, height, CaveKind::None);
+        *shallow.get_mut(1, 1) = CaveKind::Karst;
+        let mut deep = Grid2D::new(width, height, CaveKind::None);
+        *deep.get_mut(1, 1) = CaveKind::Cavern;
+        state.cave_layers = vec![
+            CaveLayer {
+                top_m: 0.0,
+                bottom_m: 150.0,
+                cave: shallow,
+            },
+            CaveLayer {
+                top_m: 300.0,
+                bottom_m: 360.0,
+                cave: deep,
+            },
+        ];
+
+        let caves = state.caves(1, 1);
+        assert_eq!(caves.len(), 2);
+        assert_eq!((caves[0].top_m, caves[0].bottom_m), (20.0, 150.0));
+        assert_eq!(state.cave_at_depth(1, 1, 10.0), CaveKind::None);
+        assert_eq!(state.cave_at_depth(1, 1, 100.0), CaveKind::Karst);
+        assert_eq!(state.cave_at_depth(1, 1, 200.0), CaveKind::None);
+        assert_eq!(state.cave_at_depth(1, 1, 320.0), CaveKind::Cavern);
+        assert_eq!(state.cave_at_depth(2, 1, 320.0), CaveKind::None);
+    }
+
+    #[test]
+    fn neighbouring_columns_vary_smoothly() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        for step in Step::ALL.into_iter().take_while(|&s| s <= Step::Geology) {
+            run_step(&mut state, step, &params).expect("step");
+        }
+
+        // Pairs of land tiles within one province, off the faults and lava.
+        let plain = |x: usize, y: usize| {
+            !*state.ocean_mask.get(x, y)
+                && *state.fault_uplift_m.get(x, y) == 0.0
+                && *state.lava_thickness_m.get(x, y) == 0.0
+        };
+        let (mut pairs, mut shared, mut diff_m, mut layers) = (0, 0, 0.0, 0);
+        for y in 0..state.height {
+            for x in 0..state.width - 1 {
+                if !plain(x, y)
+                    || !plain(x + 1, y)
+                    || state.geologic_province.get(x, y) != state.geologic_province.get(x + 1, y)
+                {
+                    continue;
+                }
+                let (a, b) = (state.strata.get(x, y), state.strata.get(x + 1, y));
+                pairs += 1;
+                for (a, b) in a.iter().zip(b) {
+                    shared += usize::from(a.rock == b.rock);
+                    diff_m += (a.thickness - b.thickness).abs();
+                    layers += 1;
+                }
+            }
+        }
+        assert!(pairs > 1000);
+        let shared = shared as f32 / layers as f32;
+        let diff_m = diff_m / layers as f32;
+        assert!(
+            shared > 0.8,
+            "only {shared} of neighbours share their rocks"
+        );
+        assert!(
+            diff_m < 2.0,
+            "layers differ by {diff_m} m between neighbours"
+        );
+    }
+}
@@ -1,13 +1,27 @@
 use crate::params::GenerationParams;
 use crate::rng::hash_2d;
 use crate::state::WorldState;
+use crate::systems::{bathymetry, faults, hydro_finalize, volcanoes};
+
+/// Height in metres spanned by the normalized elevation range above sea
+/// level; every pass converting elevation to metres, including the lapse
+/// rate, uses this scale.
+pub(crate) const ELEVATION_SPAN_M: f32 = 7_500.0;
 
 pub fn run(state: &mut WorldState, params: &GenerationParams) {
     build_elevation_and_atmosphere(state, params);
     smooth_elevation(state, 2);
     rebalance_elevation_distribution(state, params.base.sea_level);
+    hydro_finalize::mark_ocean_component(state, params.base.sea_level);
+    bathymetry::shape_sea_floor(state, params);
+    // Structure goes in before climate and erosion, so scarps and cones shape
+    // both and rivers can follow the fault troughs.
+    faults::run(state, params);
+    volcanoes::run(state, params);
+    hydro_finalize::mark_ocean_component(state, params.base.sea_level);
     build_temperature(state, params);
     simulate_moisture_transport(state, params);
+    bathymetry::classify_ocean_zones(state, params);
 }
 
 fn build_elevation_and_atmosphere(state: &mut WorldState, params: &GenerationParams) {
@@ -96,7 +110,7 @@ fn build_elevation_and_atmosphere(state: &mut WorldState, params: &GenerationPar
     }
 }
 
-fn build_temperature(state: &mut WorldState, params: &GenerationParams) {
+pub(crate) fn build_temperature(state: &mut WorldState, params: &GenerationParams) {
     let height = state.height as f32;
     for y in 0..state.height {
         let lat = (y as f32 + 0.5) / height;
@@ -109,7 +123,7 @@ fn build_temperature(state: &mut WorldState, params: &GenerationParams) {
             } else {
                 0.0
             };
-            let elev_km = ((elev - params.base.sea_level).max(0.0)) * 7.5;
+            let elev_km = ((elev - params.base.sea_level).max(0.0)) * (ELEVATION_SPAN_M / 1000.0);
             let base_temp_c = 33.0 - 57.0 * lat_factor;
             let maritime = oceanic * (1.0 - lat_factor) * 2.5;
             let temp = base_temp_c + maritime - params.base.lapse_rate_c_per_km * elev_km;
@@ -253,7 +267,7 @@ fn rebalance_elevation_distribution(state: &mut WorldState, sea_level: f32) {
     }
 }
 
-fn seed_offset(seed: u64, offset: u64) -> u64 {
+pub(crate) fn seed_offset(seed: u64, offset: u64) -> u64 {
     seed ^ (offset.wrapping_mul(0x9E37_79B9_7F4A_7C15))
 }
 
@@ -276,7 +290,7 @@ fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
     lerp(a, b, sy)
 }
 
-fn fbm(seed: u64, x: f32, y: f32, octaves: u32, base_freq: f32) -> f32 {
+pub(crate) fn fbm(seed: u64, x: f32, y: f32, octaves: u32, base_freq: f32) -> f32 {
     let mut amp = 0.5;
     let mut freq = base_freq;
     let mut sum = 0.0;
@@ -297,7 +311,7 @@ fn fbm(seed: u64, x: f32, y: f32, octaves: u32, base_freq: f32) -> f32 {
     }
 }
 
-fn ridged_fbm(seed: u64, x: f32, y: f32, octaves: u32, base_freq: f32) -> f32 {
+pub(crate) fn ridged_fbm(seed: u64, x: f32, y: f32, octaves: u32, base_freq: f32) -> f32 {
     let mut amp = 0.5;
     let mut freq = base_freq;
     let mut sum = 0.0;
@@ -319,10 +333,10 @@ fn ridged_fbm(seed: u64, x: f32, y: f32, octaves: u32, base_freq: f32) -> f32 {
     }
 }
 
-fn smoothstep(t: f32) -> f32 {
+pub(crate) fn smoothstep(t: f32) -> f32 {
     t * t * (3.0 - 2.0 * t)
 }
 
-fn lerp(a: f32, b: f32, t: f32) -> f32 {
+pub(crate) fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
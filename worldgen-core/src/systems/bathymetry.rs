@@ -0,0 +1,214 @@
+use crate::params::GenerationParams;
+use crate::rng::SplitMix64;
+use crate::state::{OceanZone, WorldState};
+use crate::systems::base_fields::{fbm, lerp, seed_offset, smoothstep};
+use crate::systems::coast;
+
+/// Replaces the noise left under the open sea with a continental margin
+/// profile: shelf, slope and abyssal plain, with trenches along active margins
+/// and seeded seamounts rising from the deep floor. Only `ocean_mask` tiles are
+/// reshaped; inland basins below sea level keep their relief.
+pub fn shape_sea_floor(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let coast_dist = distance_from_land(state);
+    let width = state.width as f32;
+    let height = state.height as f32;
+    let base_shelf = (params.base.shelf_width * width).max(1.0);
+    let mut abyssal = vec![false; state.width * state.height];
+
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let idx = state.elevation.idx(x, y);
+            if !*state.ocean_mask.get(x, y) {
+                continue;
+            }
+            let orig = *state.elevation.get(x, y);
+            let nx = x as f32 / width;
+            let ny = y as f32 / height;
+            let d = coast_dist[idx] as f32;
+
+            // Active margins (high values) have narrow shelves and trenches offshore.
+            let activity = fbm(seed_offset(params.seed, 808), nx, ny, 3, 1.6);
+            let shelf_w = base_shelf * lerp(1.6, 0.3, activity);
+            let slope_w = (shelf_w * 1.2).max(2.0);
+
+            let mut depth = if d <= shelf_w {
+                params.base.shelf_depth * (d / shelf_w)
+            } else if d <= shelf_w + slope_w {
+                let t = smoothstep((d - shelf_w) / slope_w);
+                lerp(params.base.shelf_depth, params.base.abyssal_depth, t)
+            } else {
+                abyssal[idx] = true;
+                params.base.abyssal_depth
+            };
+
+            if activity > 0.58 {
+                let trench_w = 2.0 + shelf_w * 0.5;
+                let center = shelf_w + slope_w + trench_w;
+                let bump = (1.0 - (d - center).abs() / trench_w).max(0.0);
+                if bump > 0.0 {
+                    let strength = ((activity - 0.58) / 0.12).min(1.0);
+                    let trench = lerp(
+                        params.base.abyssal_depth,
+                        params.base.trench_depth,
+                        bump * strength,
+                    );
+                    depth = depth.max(trench);
+                }
+            }
+
+            let hills = fbm(seed_offset(params.seed, 909), nx, ny, 4, 6.0);
+            depth *= 0.92 + 0.16 * hills;
+
+            // Keep a trace of the original relief so basins and rises survive.
+            depth += (sea_level - orig) * 0.05;
+            *state.elevation.get_mut(x, y) = (sea_level - depth).clamp(0.0, sea_level);
+        }
+    }
+
+    raise_seamounts(state, params, &abyssal);
+}
+
+/// Tags `ocean_mask` tiles by depth; warm tiles on the shallow shelf can host
+/// reefs. Everything else, inland basins included, is `Land`.
+pub fn classify_ocean_zones(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let trench_cut = (params.base.abyssal_depth + params.base.trench_depth) * 0.5;
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let zone = if !*state.ocean_mask.get(x, y) {
+                OceanZone::Land
+            } else {
+                let depth = sea_level - *state.elevation.get(x, y);
+                if depth <= params.base.shelf_depth * 1.25 {
+                    if depth <= params.base.shelf_depth
+                        && *state.temperature.get(x, y) >= params.base.reef_min_temp_c
+                    {
+                        OceanZone::ReefShallows
+                    } else {
+                        OceanZone::Shelf
+                    }
+                } else if depth >= trench_cut {
+                    OceanZone::Trench
+                } else {
+                    OceanZone::DeepOcean
+                }
+            };
+            *state.ocean_zone.get_mut(x, y) = zone;
+        }
+    }
+}
+
+/// Steps from the nearest tile outside the open sea.
+fn distance_from_land(state: &WorldState) -> Vec<u32> {
+    let land: Vec<(usize, usize)> = state
+        .elevation
+        .iter_coords()
+        .filter(|&(x, y)| !*state.ocean_mask.get(x, y))
+        .collect();
+    let mut dist = coast::step_distance(state, &land, 0, |_, _| true);
+
+    // A world with no land at all gets a uniform far-offshore distance.
+    for d in &mut dist {
+        if *d == u32::MAX {
+            *d = state.width.max(state.height) as u32;
+        }
+    }
+    dist
+}
+
+fn raise_seamounts(state: &mut WorldState, params: &GenerationParams, abyssal: &[bool]) {
+    let sea_level = params.base.sea_level;
+    let scale = state.width as f32 / 256.0;
+    let count = (params.base.seamount_density * (state.width * state.height) as f32 / 4096.0)
+        .round() as usize;
+    let mut rng = SplitMix64::new(seed_offset(params.seed, 1010));
+
+    for _ in 0..count {
+        let cx = (rng.next_f32() * state.width as f32) as usize;
+        let cy = (rng.next_f32() * state.height as f32) as usize;
+        let radius = rng.gen_range_f32(2.0, 6.0) * scale;
+        let rise = rng.gen_range_f32(0.4, 0.9);
+        let cx = cx.min(state.width - 1);
+        let cy = cy.min(state.height - 1);
+        if !abyssal[state.elevation.idx(cx, cy)] {
+            continue;
+        }
+
+        let peak_depth = sea_level - *state.elevation.get(cx, cy);
+        let r = radius.ceil() as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let nx = cx as isize + dx;
+                let ny = cy as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let dist = ((dx * dx + dy * dy) as f32).sqrt();
+                if dist > radius {
+                    continue;
+                }
+                let cone = (1.0 - dist / radius).powf(1.5);
+                if !*state.ocean_mask.get(nx as usize, ny as usize) {
+                    continue;
+                }
+                let cell = state.elevation.get_mut(nx as usize, ny as usize);
+                // Seamounts stay submerged below shelf depth.
+                let lifted = *cell + peak_depth * rise * cone;
+                *cell = cell.max(lifted.min(sea_level - params.base.shelf_depth));
+            }
+        }
+    }
+}
+
+pub fn ocean_zone_color(zone: OceanZone) -> [u8; 3] {
+    match zone {
+        OceanZone::Land => [20, 20, 20],
+        OceanZone::Shelf => [70, 150, 200],
+        OceanZone::ReefShallows => [60, 210, 200],
+        OceanZone::DeepOcean => [14, 48, 120],
+        OceanZone::Trench => [4, 12, 50],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::hydro_finalize;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn inland_basins_stay_out_of_the_sea() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.base.seamount_density = 0.0;
+        let mut state = WorldState::new(params.clone());
+        let sea_level = params.base.sea_level;
+        for (x, y) in state.elevation.clone().iter_coords() {
+            let h = if x < 40 {
+                sea_level - 0.2
+            } else if (120..140).contains(&x) && (120..140).contains(&y) {
+                sea_level - 0.05
+            } else {
+                sea_level + 0.1
+            };
+            *state.elevation.get_mut(x, y) = h;
+        }
+        state.temperature.fill(10.0);
+
+        hydro_finalize::mark_ocean_component(&mut state, sea_level);
+        shape_sea_floor(&mut state, &params);
+        classify_ocean_zones(&mut state, &params);
+
+        assert!(*state.ocean_mask.get(0, 128));
+        assert!(!*state.ocean_mask.get(130, 130));
+        assert_eq!(*state.elevation.get(130, 130), sea_level - 0.05);
+        assert_eq!(*state.ocean_zone.get(130, 130), OceanZone::Land);
+        // The open sea takes the margin profile: shallow by the coast, deep
+        // offshore.
+        assert_eq!(*state.ocean_zone.get(39, 128), OceanZone::Shelf);
+        assert_eq!(*state.ocean_zone.get(0, 128), OceanZone::DeepOcean);
+    }
+}
@@ -1,5 +1,12 @@
+use std::cmp::Ordering;
+
+use anyhow::Result;
+
+use crate::biome_table::{BiomeSample, BiomeTable};
 use crate::params::GenerationParams;
-use crate::state::{Biome, WorldState};
+use crate::state::{Biome, BiomeBlend, WorldState, BIOME_BLEND_SLOTS};
+use crate::systems::base_fields::ELEVATION_SPAN_M;
+use crate::systems::{coast, fire, life_zones};
 
 const DIRS_8: [(isize, isize); 8] = [
     (1, 0),
@@ -12,33 +19,73 @@ const DIRS_8: [(isize, isize); 8] = [
     (1, -1),
 ];
 
-pub fn run(state: &mut WorldState, params: &GenerationParams) {
+pub fn run(state: &mut WorldState, params: &GenerationParams) -> Result<()> {
+    state.biome_table = match &params.biomes.definitions {
+        Some(path) => BiomeTable::load(path)?,
+        None => BiomeTable::builtin(),
+    };
+
+    coast::run(state, params);
+
+    let sea_level = params.base.sea_level;
     for y in 0..state.height {
         for x in 0..state.width {
             let elev = *state.elevation.get(x, y);
             let rain = *state.rainfall.get(x, y);
             let temp = *state.temperature.get(x, y);
             let wet = wetness(state, x, y, params.biomes.wetness_weight);
+            let sample = BiomeSample {
+                temperature: temp,
+                rainfall: rain,
+                elevation: elev - sea_level,
+                wetness: wet,
+                coast_distance: *state.coast_distance.get(x, y) as f32,
+                slope: slope_m(state, x, y, sea_level),
+                exposure: *state.wave_exposure.get(x, y),
+            };
 
-            let biome = classify(
-                temp,
-                rain,
-                elev,
-                wet,
-                state.params.base.sea_level,
-                *state.lake_id.get(x, y) > 0,
-            );
+            let biome = if elev <= sea_level {
+                state.biome_table.classify_marine(&sample)
+            } else if *state.lake_id.get(x, y) > 0 {
+                state.biome_table.lake()
+            } else {
+                state.biome_table.classify_land(&sample)
+            };
             *state.biome.get_mut(x, y) = biome;
 
+            let blend = if !params.biomes.ecotones || state.biome_table.is_water(biome) {
+                BiomeBlend::single(biome)
+            } else if elev <= sea_level {
+                let w = state
+                    .biome_table
+                    .blend_marine(&sample, params.biomes.ecotone_width);
+                top_weights(w, params.biomes.blend_slots, biome)
+            } else {
+                let w = state
+                    .biome_table
+                    .blend_land(&sample, params.biomes.ecotone_width);
+                top_weights(w, params.biomes.blend_slots, biome)
+            };
+            *state.biome_blend.get_mut(x, y) = blend;
+
             let fertility =
                 (rain * 0.7 + wet * 0.2 + (1.0 - (temp - 18.0).abs() / 40.0) * 0.1).clamp(0.0, 1.0);
             *state.fertility.get_mut(x, y) = fertility;
         }
     }
 
+    fire::run(state, params);
+
     for _ in 0..params.biomes.smoothing_passes {
         smooth_biomes(state);
+        if params.biomes.ecotones {
+            soften_blend(state, params.biomes.blend_slots);
+        }
     }
+    anchor_blends(state, params);
+
+    life_zones::run(state, params);
+    Ok(())
 }
 
 fn wetness(state: &WorldState, x: usize, y: usize, wetness_weight: f32) -> f32 {
@@ -47,92 +94,64 @@ fn wetness(state: &WorldState, x: usize, y: usize, wetness_weight: f32) -> f32 {
     (1.0 - wetness_weight) * *state.rainfall.get(x, y) + wetness_weight * qn
 }
 
-fn classify(temp_c: f32, rain: f32, elev: f32, wet: f32, sea_level: f32, is_lake: bool) -> Biome {
-    if elev <= sea_level {
-        return Biome::Ocean;
-    }
-    if is_lake {
-        return Biome::Lake;
-    }
-    if wet > 0.85 {
-        return Biome::Wetland;
-    }
-    if elev > sea_level + 0.28 {
-        return Biome::Alpine;
-    }
-
-    if temp_c < -8.0 {
-        if rain < 0.18 {
-            Biome::PolarDesert
-        } else {
-            Biome::Tundra
-        }
-    } else if temp_c < 4.0 {
-        if rain > 0.35 {
-            Biome::BorealForest
-        } else {
-            Biome::Tundra
+/// Largest drop to a neighbouring tile, in metres. Water neighbours count at
+/// the surface, so a shoreline tile's slope is its height above the sea.
+pub(crate) fn slope_m(state: &WorldState, x: usize, y: usize, sea_level: f32) -> f32 {
+    let h = *state.elevation.get(x, y);
+    let mut drop = 0.0f32;
+    for (dx, dy) in DIRS_8 {
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
+        if state.elevation.in_bounds(nx, ny) {
+            let n = state.elevation.get(nx as usize, ny as usize).max(sea_level);
+            drop = drop.max(h - n);
         }
-    } else if temp_c < 15.0 {
-        if rain < 0.2 {
-            Biome::TemperateGrassland
-        } else if rain < 0.45 {
-            Biome::Mediterranean
-        } else {
-            Biome::TemperateForest
-        }
-    } else if rain < 0.16 {
-        Biome::HotDesert
-    } else if rain < 0.38 {
-        Biome::Savanna
-    } else if rain < 0.62 {
-        Biome::TropicalSeasonalForest
-    } else {
-        Biome::TropicalRainforest
     }
+    drop * ELEVATION_SPAN_M
 }
 
+/// Majority filter over plain land biomes. Only smoothable neighbours vote:
+/// water, coastal and marine tiles neither change nor spread, so a shoreline
+/// tile keeps a land biome however much water surrounds it.
 fn smooth_biomes(state: &mut WorldState) {
     let mut out = state.biome.clone();
+    let mut votes = vec![0.0f32; state.biome_table.len()];
 
     for y in 1..state.height.saturating_sub(1) {
         for x in 1..state.width.saturating_sub(1) {
             let center = *state.biome.get(x, y);
-            if matches!(center, Biome::Ocean | Biome::Lake) {
+            if !state.biome_table.is_smoothed(center) {
                 continue;
             }
 
-            let mut counts = [0u8; 14];
-            counts[usize::from(center.as_u8())] += 1;
+            // Frequent fire keeps forest from spreading into this tile, though
+            // a tile that is already forest keeps its own vote in full.
+            let woody_f = fire::woody_vote_factor(*state.fire_return_interval.get(x, y));
+            votes.fill(0.0);
+            votes[usize::from(center.as_u8())] += 1.0;
 
             for (dx, dy) in DIRS_8 {
                 let nx = (x as isize + dx) as usize;
                 let ny = (y as isize + dy) as usize;
                 let b = *state.biome.get(nx, ny);
-                counts[usize::from(b.as_u8())] += 1;
+                if !state.biome_table.is_smoothed(b) {
+                    continue;
+                }
+                let vote = if b != center && state.biome_table.def(b).is_woody() {
+                    woody_f
+                } else {
+                    1.0
+                };
+                votes[usize::from(b.as_u8())] += vote;
             }
 
             let mut best = center;
-            let mut best_count = 0u8;
-            for (idx, c) in counts.iter().enumerate() {
-                if *c > best_count {
-                    best_count = *c;
-                    best = match idx as u8 {
-                        0 => Biome::Ocean,
-                        1 => Biome::Lake,
-                        2 => Biome::PolarDesert,
-                        3 => Biome::Tundra,
-                        4 => Biome::BorealForest,
-                        5 => Biome::TemperateGrassland,
-                        6 => Biome::TemperateForest,
-                        7 => Biome::Mediterranean,
-                        8 => Biome::Savanna,
-                        9 => Biome::TropicalSeasonalForest,
-                        10 => Biome::TropicalRainforest,
-                        11 => Biome::HotDesert,
-                        12 => Biome::Alpine,
-                        _ => Biome::Wetland,
-                    };
+            let mut best_votes = 0.0f32;
+            for (biome, _) in state.biome_table.iter() {
+                let v = votes[usize::from(biome.as_u8())];
+                if v > best_votes {
+                    best_votes = v;
+                    best = biome;
                 }
             }
             *out.get_mut(x, y) = best;
@@ -141,3 +160,172 @@ fn smooth_biomes(state: &mut WorldState) {
 
     state.biome = out;
 }
+
+/// Box-filters the blend weights of smoothable land tiles, the soft
+/// counterpart of `smooth_biomes`.
+fn soften_blend(state: &mut WorldState, slots: usize) {
+    let mut out = state.biome_blend.clone();
+    let mut acc = vec![0.0f32; state.biome_table.len()];
+
+    for y in 0..state.height {
+        for x in 0..state.width {
+            if !state.biome_table.is_smoothed(*state.biome.get(x, y)) {
+                continue;
+            }
+            acc.fill(0.0);
+            let mut add = |blend: &BiomeBlend| {
+                for (b, w) in blend.iter() {
+                    acc[usize::from(b.as_u8())] += w;
+                }
+            };
+            add(state.biome_blend.get(x, y));
+            for (dx, dy) in DIRS_8 {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if state.biome_table.is_smoothed(*state.biome.get(nx, ny)) {
+                    add(state.biome_blend.get(nx, ny));
+                }
+            }
+
+            let weights = state
+                .biome_table
+                .iter()
+                .map(|(b, _)| (b, acc[usize::from(b.as_u8())]))
+                .filter(|&(_, w)| w > 0.0)
+                .collect();
+            *out.get_mut(x, y) = top_weights(weights, slots, *state.biome.get(x, y));
+        }
+    }
+
+    state.biome_blend = out;
+}
+
+/// Makes the smoothed `biome` the dominant blend entry. Where smoothing
+/// overruled the blend, the tile's biome is raised to the heaviest weight
+/// before renormalizing; without ecotones the blend is the biome alone.
+fn anchor_blends(state: &mut WorldState, params: &GenerationParams) {
+    for (b, blend) in state
+        .biome
+        .as_slice()
+        .iter()
+        .zip(state.biome_blend.as_mut_slice())
+    {
+        if !params.biomes.ecotones {
+            *blend = BiomeBlend::single(*b);
+            continue;
+        }
+        if blend.biomes[0] == *b {
+            continue;
+        }
+        let mut weights: Vec<(Biome, f32)> = blend.iter().collect();
+        let top = weights.iter().map(|&(_, w)| w).fold(0.0, f32::max);
+        match weights.iter_mut().find(|(other, _)| other == b) {
+            Some(entry) => entry.1 = top,
+            None => weights.push((*b, top)),
+        }
+        *blend = top_weights(weights, params.biomes.blend_slots, *b);
+    }
+}
+
+/// Keeps the `slots` heaviest biomes and renormalizes them to sum to 1.
+/// `dominant` wins ties for the first slot and takes full weight when
+/// nothing else has any.
+fn top_weights(mut weights: Vec<(Biome, f32)>, slots: usize, dominant: Biome) -> BiomeBlend {
+    weights.retain(|&(_, w)| w > 0.0);
+    if weights.is_empty() {
+        return BiomeBlend::single(dominant);
+    }
+    weights.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| (b.0 == dominant).cmp(&(a.0 == dominant)))
+    });
+    weights.truncate(slots.clamp(1, BIOME_BLEND_SLOTS));
+    let total: f32 = weights.iter().map(|&(_, w)| w).sum();
+
+    let mut blend = BiomeBlend::single(weights[0].0);
+    blend.weights[0] = 0.0;
+    for (i, (b, w)) in weights.into_iter().enumerate() {
+        blend.biomes[i] = b;
+        blend.weights[i] = w / total;
+    }
+    blend
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn smoothing_never_turns_land_into_water() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params);
+        let biome = |name| state.biome_table.find(name).expect("built-in biome");
+        let (ocean, grassland, forest) = (
+            state.biome_table.ocean(),
+            biome("Temperate Grassland"),
+            biome("Temperate Forest"),
+        );
+        state.biome.fill(ocean);
+        // A grassland tile on a spit: five water neighbours, three forest.
+        *state.biome.get_mut(10, 10) = grassland;
+        for (x, y) in [(9, 11), (10, 11), (11, 11)] {
+            *state.biome.get_mut(x, y) = forest;
+        }
+        // Deep in the forest, a lone grassland tile is still outvoted.
+        for y in 20..25 {
+            for x in 20..25 {
+                *state.biome.get_mut(x, y) = forest;
+            }
+        }
+        *state.biome.get_mut(22, 22) = grassland;
+
+        smooth_biomes(&mut state);
+        assert_eq!(*state.biome.get(10, 10), forest);
+        assert_eq!(*state.biome.get(22, 22), forest);
+        assert_eq!(*state.biome.get(10, 9), ocean);
+    }
+
+    #[test]
+    fn blend_leads_with_the_smoothed_biome() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        let biome = |name| state.biome_table.find(name).expect("built-in biome");
+        let (grassland, forest, savanna) = (
+            biome("Temperate Grassland"),
+            biome("Temperate Forest"),
+            biome("Savanna"),
+        );
+        assert_eq!(
+            top_weights(Vec::new(), 4, forest),
+            BiomeBlend::single(forest)
+        );
+
+        state.biome.fill(forest);
+        state.biome_blend.fill(top_weights(
+            vec![(grassland, 0.7), (forest, 0.2)],
+            4,
+            grassland,
+        ));
+        *state.biome_blend.get_mut(1, 1) = top_weights(vec![(savanna, 1.0)], 4, savanna);
+        anchor_blends(&mut state, &params);
+
+        for (x, y) in [(0, 0), (1, 1)] {
+            let blend = state.biome_blend.get(x, y);
+            assert_eq!(blend.biomes[0], forest);
+            assert!(blend.iter().all(|(_, w)| w <= blend.weights[0]));
+            assert!((blend.weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        }
+    }
+}
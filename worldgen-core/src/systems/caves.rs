@@ -0,0 +1,372 @@
+use std::f32::consts::TAU;
+
+use crate::grid::Grid2D;
+use crate::params::GenerationParams;
+use crate::rng::{hash_2d, SplitMix64};
+use crate::state::{
+    CaveKind, CaveLayer, CaveOpening, CaveOpeningKind, RiverClass, VolcanoKind, WorldState,
+};
+use crate::systems::base_fields::{ridged_fbm, seed_offset};
+use crate::systems::hydro_finalize::{self, DIRS_8};
+
+/// Noise frequency of karst passages; ridges of the noise trace the conduits.
+const KARST_SCALE: f32 = 18.0;
+/// Noise frequency of deep caverns, coarser so chambers merge into systems.
+const CAVERN_SCALE: f32 = 7.0;
+/// Tubes start this many tiles from the vent, past the crater.
+const TUBE_START_TILES: f32 = 2.0;
+
+/// Carves the cave layers once the strata are in place. The near-surface
+/// band holds karst passages along ridged noise wherever soluble rock lies
+/// within it, and lava tubes running downhill from shield vents through
+/// their basalt flows. Deeper cavern layers open on a share of every tile
+/// regardless of rock. Streams crossing onto shallow karst sink, and the
+/// water follows the surface drainage underground until the karst ends,
+/// where it rises again as a spring; the surface reach between them loses
+/// the sunk discharge.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let cp = &params.caves;
+    let (width, height) = (state.width, state.height);
+    let shallow_m = cp.shallow_depth_m.max(1.0);
+
+    let mut shallow = Grid2D::new(width, height, CaveKind::None);
+    carve_karst(state, params, &mut shallow);
+    carve_lava_tubes(state, params, &mut shallow);
+
+    let mut layers = vec![CaveLayer {
+        top_m: 0.0,
+        bottom_m: shallow_m,
+        cave: shallow,
+    }];
+    for (i, &depth) in cp.cavern_depths_m.iter().enumerate() {
+        let top_m = depth.max(shallow_m);
+        layers.push(CaveLayer {
+            top_m,
+            bottom_m: top_m + cp.cavern_thickness_m.max(1.0),
+            cave: carve_caverns(state, params, i as u64),
+        });
+    }
+    state.cave_layers = layers;
+    state.cave_openings = openings(state, params);
+    sink_streams(state, params);
+}
+
+/// Soluble rock starting within `depth_m` of the surface at `(x, y)`.
+fn soluble_within(state: &WorldState, x: usize, y: usize, depth_m: f32) -> bool {
+    state
+        .strata_column(x, y)
+        .iter()
+        .any(|i| i.top_m < depth_m && state.geology_catalogue.rock(i.rock).soluble)
+}
+
+/// Marks the karst passages: the highest-ridged `karst_density` share of
+/// land tiles with soluble rock in the band.
+fn carve_karst(state: &WorldState, params: &GenerationParams, cave: &mut Grid2D<CaveKind>) {
+    let cp = &params.caves;
+    let seed = seed_offset(params.seed, 2500);
+    let (width, height) = (state.width as f32, state.height as f32);
+    let mut hosts: Vec<((usize, usize), f32)> = state
+        .elevation
+        .iter_coords()
+        .filter(|&(x, y)| {
+            !*state.ocean_mask.get(x, y) && soluble_within(state, x, y, cp.shallow_depth_m)
+        })
+        .map(|(x, y)| {
+            let n = ridged_fbm(seed, x as f32 / width, y as f32 / height, 4, KARST_SCALE);
+            ((x, y), n)
+        })
+        .collect();
+    hosts.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let keep = (hosts.len() as f32 * cp.karst_density.clamp(0.0, 1.0)).round() as usize;
+    for &((x, y), _) in hosts.iter().take(keep) {
+        *cave.get_mut(x, y) = CaveKind::Karst;
+    }
+}
+
+/// Tubes from each shield vent, following the steepest descent until the
+/// flow field or the volcanic rock ends.
+fn carve_lava_tubes(state: &WorldState, params: &GenerationParams, cave: &mut Grid2D<CaveKind>) {
+    let mut rng = SplitMix64::new(seed_offset(params.seed, 2501));
+    let catalogue = &state.geology_catalogue;
+    for v in state
+        .volcanoes
+        .iter()
+        .filter(|v| v.kind == VolcanoKind::Shield)
+    {
+        for _ in 0..params.caves.lava_tubes_per_volcano {
+            let angle = rng.gen_range_f32(0.0, TAU);
+            let sx = (v.x as f32 + angle.cos() * TUBE_START_TILES).round() as isize;
+            let sy = (v.y as f32 + angle.sin() * TUBE_START_TILES).round() as isize;
+            if !state.elevation.in_bounds(sx, sy) {
+                continue;
+            }
+            let (mut x, mut y) = (sx as usize, sy as usize);
+            loop {
+                if *state.volcano_id.get(x, y) != v.id
+                    || *state.ocean_mask.get(x, y)
+                    || *state.lava_thickness_m.get(x, y) <= 0.0
+                    || !catalogue.rock(*state.rock_type.get(x, y)).volcanic
+                {
+                    break;
+                }
+                *cave.get_mut(x, y) = CaveKind::LavaTube;
+                match steepest_descent(state, x, y) {
+                    Some(next) => (x, y) = next,
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+fn steepest_descent(state: &WorldState, x: usize, y: usize) -> Option<(usize, usize)> {
+    let here = *state.elevation.get(x, y);
+    let mut best = None;
+    let mut best_h = here;
+    for (dx, dy) in DIRS_8 {
+        let (nx, ny) = (x as isize + dx, y as isize + dy);
+        if !state.elevation.in_bounds(nx, ny) {
+            continue;
+        }
+        let h = *state.elevation.get(nx as usize, ny as usize);
+        if h < best_h {
+            best_h = h;
+            best = Some((nx as usize, ny as usize));
+        }
+    }
+    best
+}
+
+/// The highest-ridged `cavern_coverage` share of all tiles, on a noise field
+/// of its own per layer.
+fn carve_caverns(state: &WorldState, params: &GenerationParams, layer: u64) -> Grid2D<CaveKind> {
+    let seed = seed_offset(params.seed, 2510 + layer);
+    let (width, height) = (state.width as f32, state.height as f32);
+    let raw: Vec<f32> = state
+        .elevation
+        .iter_coords()
+        .map(|(x, y)| ridged_fbm(seed, x as f32 / width, y as f32 / height, 4, CAVERN_SCALE))
+        .collect();
+    let mut sorted = raw.clone();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let keep = (sorted.len() as f32 * params.caves.cavern_coverage.clamp(0.0, 1.0)) as usize;
+    let mut cave = Grid2D::new(state.width, state.height, CaveKind::None);
+    if keep == 0 {
+        return cave;
+    }
+    let cutoff = sorted[keep - 1];
+    for (c, &n) in cave.as_mut_slice().iter_mut().zip(&raw) {
+        if n >= cutoff {
+            *c = CaveKind::Cavern;
+        }
+    }
+    cave
+}
+
+/// Sinkholes where streams cross onto karst within `sinkhole_depth_m` of the
+/// surface, each linked to the spring at the last karst tile downstream, plus
+/// isolated collapse sinkholes scattered over the rest of the shallow karst.
+fn openings(state: &WorldState, params: &GenerationParams) -> Vec<CaveOpening> {
+    let cp = &params.caves;
+    let Some(shallow) = state.cave_layers.first() else {
+        return Vec::new();
+    };
+    let karst = |x: usize, y: usize| {
+        *shallow.cave.get(x, y) == CaveKind::Karst
+            && *state.lake_id.get(x, y) == 0
+            && !*state.ocean_mask.get(x, y)
+    };
+    let near_surface =
+        |x: usize, y: usize| karst(x, y) && soluble_within(state, x, y, cp.sinkhole_depth_m);
+    let stream = |x: usize, y: usize| *state.river_class.get(x, y) != RiverClass::None;
+
+    let mut openings: Vec<CaveOpening> = Vec::new();
+    let mut spring_at = Grid2D::new(state.width, state.height, 0u32);
+    for (x, y) in state.elevation.iter_coords() {
+        if !stream(x, y) || !near_surface(x, y) {
+            continue;
+        }
+        let fed_from_outside = DIRS_8.iter().any(|&(dx, dy)| {
+            let (ux, uy) = (x as isize - dx, y as isize - dy);
+            state.elevation.in_bounds(ux, uy) && {
+                let (ux, uy) = (ux as usize, uy as usize);
+                hydro_finalize::downstream(state, ux, uy) == Some((x, y))
+                    && stream(ux, uy)
+                    && !near_surface(ux, uy)
+            }
+        });
+        if !fed_from_outside {
+            continue;
+        }
+
+        let sink_id = openings.len() as u32 + 1;
+        openings.push(CaveOpening {
+            id: sink_id,
+            kind: CaveOpeningKind::Sinkhole,
+            x,
+            y,
+            linked: 0,
+            discharge: *state.discharge.get(x, y),
+        });
+        let (mut sx, mut sy) = (x, y);
+        let mut steps = 0;
+        while let Some((nx, ny)) = hydro_finalize::downstream(state, sx, sy) {
+            if !karst(nx, ny) || steps > state.width + state.height {
+                break;
+            }
+            (sx, sy) = (nx, ny);
+            steps += 1;
+        }
+        if (sx, sy) == (x, y) {
+            continue;
+        }
+        let spring_id = match *spring_at.get(sx, sy) {
+            0 => {
+                let id = openings.len() as u32 + 1;
+                openings.push(CaveOpening {
+                    id,
+                    kind: CaveOpeningKind::Spring,
+                    x: sx,
+                    y: sy,
+                    linked: sink_id,
+                    discharge: *state.discharge.get(sx, sy),
+                });
+                *spring_at.get_mut(sx, sy) = id;
+                id
+            }
+            id => id,
+        };
+        openings[sink_id as usize - 1].linked = spring_id;
+    }
+
+    let seed = seed_offset(params.seed, 2502);
+    for (x, y) in state.elevation.iter_coords() {
+        if stream(x, y)
+            || !near_surface(x, y)
+            || *spring_at.get(x, y) != 0
+            || hash_2d(seed, x as i32, y as i32) >= cp.doline_density
+        {
+            continue;
+        }
+        openings.push(CaveOpening {
+            id: openings.len() as u32 + 1,
+            kind: CaveOpeningKind::Sinkhole,
+            x,
+            y,
+            linked: 0,
+            discharge: *state.discharge.get(x, y),
+        });
+    }
+    openings
+}
+
+/// Takes each linked sinkhole's discharge off the surface tiles between it
+/// and its spring, lowering their river class and navigation to match. The
+/// spring keeps its full discharge, so the flow returns to the surface there.
+fn sink_streams(state: &mut WorldState, params: &GenerationParams) {
+    let openings = state.cave_openings.clone();
+    for sink in openings
+        .iter()
+        .filter(|o| o.kind == CaveOpeningKind::Sinkhole && o.linked > 0)
+    {
+        let spring = &openings[sink.linked as usize - 1];
+        let (mut x, mut y) = (sink.x, sink.y);
+        let mut steps = 0;
+        while let Some(next) = hydro_finalize::downstream(state, x, y) {
+            if next == (spring.x, spring.y) || steps > state.width + state.height {
+                break;
+            }
+            (x, y) = next;
+            steps += 1;
+            let q = state.discharge.get_mut(x, y);
+            *q = (*q - sink.discharge).max(0.0);
+            let class = hydro_finalize::river_class(*q, params);
+            *state.river_class.get_mut(x, y) = class;
+        }
+    }
+    hydro_finalize::classify_navigation(state, params);
+}
+
+pub fn cave_color(kind: CaveKind) -> [u8; 3] {
+    match kind {
+        CaveKind::None => [0, 0, 0],
+        CaveKind::Karst => [120, 190, 230],
+        CaveKind::LavaTube => [230, 90, 40],
+        CaveKind::Cavern => [170, 120, 220],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Step;
+    use crate::{run_step, MapSizePreset};
+
+    #[test]
+    fn stream_sinks_pair_with_a_spring_downstream() {
+        let mut params = GenerationParams {
+            seed: 7,
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.caves.karst_density = 1.0;
+        let mut before = Grid2D::new(0, 0, 0.0);
+        let mut state = WorldState::new(params.clone());
+        for step in Step::ALL.into_iter().take_while(|&s| s <= Step::Geology) {
+            if step == Step::Geology {
+                before = state.discharge.clone();
+            }
+            run_step(&mut state, step, &params).expect("step");
+        }
+
+        let shallow = &state.cave_layers[0].cave;
+        let mut pairs = 0;
+        let mut dried = 0;
+        for sink in state
+            .cave_openings
+            .iter()
+            .filter(|o| o.kind == CaveOpeningKind::Sinkhole && o.linked > 0)
+        {
+            assert_eq!(*shallow.get(sink.x, sink.y), CaveKind::Karst);
+            assert!(soluble_within(
+                &state,
+                sink.x,
+                sink.y,
+                params.caves.sinkhole_depth_m
+            ));
+            assert_ne!(*state.river_class.get(sink.x, sink.y), RiverClass::None);
+
+            let spring = &state.cave_openings[sink.linked as usize - 1];
+            assert_eq!(spring.kind, CaveOpeningKind::Spring);
+            assert_eq!(*shallow.get(spring.x, spring.y), CaveKind::Karst);
+            let (mut x, mut y) = (sink.x, sink.y);
+            let mut above_spring = None;
+            loop {
+                (x, y) = hydro_finalize::downstream(&state, x, y)
+                    .expect("spring lies downstream of its sink");
+                assert_eq!(*shallow.get(x, y), CaveKind::Karst);
+                if (x, y) == (spring.x, spring.y) {
+                    break;
+                }
+                // The sunk water leaves the surface reach below the sink.
+                let q = *state.discharge.get(x, y);
+                assert!(q <= (*before.get(x, y) - sink.discharge).max(0.0) + 1e-3);
+                assert_eq!(
+                    *state.river_class.get(x, y),
+                    hydro_finalize::river_class(q, &params)
+                );
+                above_spring = Some(q);
+            }
+            // ...and comes back up at the spring.
+            let q_spring = *state.discharge.get(spring.x, spring.y);
+            assert_eq!(q_spring, *before.get(spring.x, spring.y));
+            if let Some(q) = above_spring {
+                assert!(q_spring > q, "spring {q_spring} vs reach {q}");
+                dried += 1;
+            }
+            pairs += 1;
+        }
+        assert!(pairs > 0, "no stream sinks formed");
+        assert!(dried > 0, "no surface reach between a sink and its spring");
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+
+use crate::params::GenerationParams;
+use crate::state::WorldState;
+
+const DIRS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Exposure is only resolved this many steps either side of the shoreline;
+/// farther inland it is 0 and farther offshore 1.
+const EXPOSURE_BAND: u32 = 6;
+/// A land tile looks past at most this many land tiles for open water.
+const SHORE_LOOKAHEAD: usize = 3;
+
+/// Fills `coast_distance` and `wave_exposure`, the coastal inputs to biome
+/// classification.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    compute_coast_distance(state, params.base.sea_level);
+    compute_wave_exposure(state, params);
+}
+
+/// Steps to the nearest tile on the other side of the shoreline: land tiles
+/// measure to the sea and ocean tiles to land, so both sides of the coast
+/// read 1.
+fn compute_coast_distance(state: &mut WorldState, sea_level: f32) {
+    let cap = state.width.max(state.height) as u32;
+    let land = |x: usize, y: usize| *state.elevation.get(x, y) > sea_level;
+    let shore: Vec<(usize, usize)> = state
+        .elevation
+        .iter_coords()
+        .filter(|&(x, y)| {
+            DIRS_8.iter().any(|&(dx, dy)| {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                state.elevation.in_bounds(nx, ny) && land(nx as usize, ny as usize) != land(x, y)
+            })
+        })
+        .collect();
+    let dist = step_distance(state, &shore, 1, |from, to| {
+        land(from.0, from.1) == land(to.0, to.1)
+    });
+
+    for (d, out) in dist.iter().zip(state.coast_distance.as_mut_slice()) {
+        *out = (*d).min(cap);
+    }
+}
+
+/// Multi-source BFS over 8-connected tiles: each of `seeds` reads `start`,
+/// and steps are only taken between neighbours `connected` allows. Tiles
+/// never reached stay at `u32::MAX`.
+pub(crate) fn step_distance(
+    state: &WorldState,
+    seeds: &[(usize, usize)],
+    start: u32,
+    connected: impl Fn((usize, usize), (usize, usize)) -> bool,
+) -> Vec<u32> {
+    let mut dist = vec![u32::MAX; state.width * state.height];
+    let mut q = VecDeque::new();
+    for &(x, y) in seeds {
+        dist[state.elevation.idx(x, y)] = start;
+        q.push_back((x, y));
+    }
+
+    while let Some((x, y)) = q.pop_front() {
+        let next = dist[state.elevation.idx(x, y)] + 1;
+        for (dx, dy) in DIRS_8 {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !state.elevation.in_bounds(nx, ny) {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            if !connected((x, y), (nx, ny)) {
+                continue;
+            }
+            let nidx = state.elevation.idx(nx, ny);
+            if dist[nidx] > next {
+                dist[nidx] = next;
+                q.push_back((nx, ny));
+            }
+        }
+    }
+    dist
+}
+
+/// Open-water fetch in eight directions, weighted towards the direction the
+/// prevailing wind blows from, normalized to 0 (sheltered) ..1 (open ocean).
+fn compute_wave_exposure(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let max_fetch = (params.biomes.wave_fetch * state.width as f32).max(2.0) as usize;
+
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let land = *state.elevation.get(x, y) > sea_level;
+            let d = *state.coast_distance.get(x, y);
+            if d > EXPOSURE_BAND {
+                *state.wave_exposure.get_mut(x, y) = if land { 0.0 } else { 1.0 };
+                continue;
+            }
+
+            let (wu, wv) = (*state.wind_u.get(x, y), *state.wind_v.get(x, y));
+            let wind_len = (wu * wu + wv * wv).sqrt().max(1e-6);
+            let mut total = 0.0;
+            let mut weight_sum = 0.0;
+            for (dx, dy) in DIRS_8 {
+                let len = ((dx * dx + dy * dy) as f32).sqrt();
+                // Waves arrive from upwind, i.e. looking against the wind vector.
+                let upwind = -(dx as f32 * wu + dy as f32 * wv) / (len * wind_len);
+                let weight = 0.5 + upwind.max(0.0);
+                let fetch = fetch_along(state, sea_level, x, y, dx, dy, max_fetch);
+                total += weight * fetch as f32 / max_fetch as f32;
+                weight_sum += weight;
+            }
+            *state.wave_exposure.get_mut(x, y) = total / weight_sum;
+        }
+    }
+}
+
+/// Counts ocean tiles along a ray until it hits land again; a ray that starts
+/// on land may first cross a few shore tiles.
+fn fetch_along(
+    state: &WorldState,
+    sea_level: f32,
+    x: usize,
+    y: usize,
+    dx: isize,
+    dy: isize,
+    max_fetch: usize,
+) -> usize {
+    let from_land = *state.elevation.get(x, y) > sea_level;
+    let mut fetch = 0;
+    let mut shore = if from_land { 0 } else { SHORE_LOOKAHEAD };
+    let (mut cx, mut cy) = (x as isize, y as isize);
+    loop {
+        cx += dx;
+        cy += dy;
+        if !state.elevation.in_bounds(cx, cy) {
+            // The map edge is open sea if the ray is already over water.
+            return if fetch > 0 || !from_land {
+                max_fetch
+            } else {
+                0
+            };
+        }
+        if *state.elevation.get(cx as usize, cy as usize) > sea_level {
+            if fetch > 0 || shore >= SHORE_LOOKAHEAD {
+                return fetch;
+            }
+            shore += 1;
+            continue;
+        }
+        fetch += 1;
+        if fetch >= max_fetch {
+            return max_fetch;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::base_fields::ELEVATION_SPAN_M;
+    use crate::systems::biomes;
+    use crate::MapSizePreset;
+
+    /// Open sea west of column 128 and warm, low land east of it, with a
+    /// cliffed stretch of coast, a headland reaching west into the sea and a
+    /// lagoon behind a one-tile channel.
+    fn coastline(params: &GenerationParams) -> WorldState {
+        let sea_level = params.base.sea_level;
+        let mut state = WorldState::new(params.clone());
+        state.temperature.fill(24.0);
+        state.rainfall.fill(0.5);
+        for y in 0..state.height {
+            for x in 0..state.width {
+                let land = (x >= 128 || ((60..66).contains(&y) && x >= 80))
+                    && !((150..160).contains(&x) && (170..200).contains(&y))
+                    && !((128..150).contains(&x) && y == 185);
+                let h = if !land {
+                    sea_level - 0.1
+                } else if x >= 128 && (20..40).contains(&y) {
+                    sea_level + 375.0 / ELEVATION_SPAN_M
+                } else {
+                    sea_level + 15.0 / ELEVATION_SPAN_M
+                };
+                *state.elevation.get_mut(x, y) = h;
+            }
+        }
+        state
+    }
+
+    fn params() -> GenerationParams {
+        GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        }
+    }
+
+    #[test]
+    fn shore_tiles_are_one_step_from_the_coast() {
+        let params = params();
+        let mut state = coastline(&params);
+
+        run(&mut state, &params);
+
+        assert_eq!(*state.coast_distance.get(127, 120), 1);
+        assert_eq!(*state.coast_distance.get(128, 120), 1);
+        assert_eq!(*state.coast_distance.get(131, 120), 4);
+        assert_eq!(*state.coast_distance.get(120, 120), 8);
+        assert_eq!(*state.coast_distance.get(160, 172), 1);
+    }
+
+    #[test]
+    fn headland_is_more_exposed_than_lagoon() {
+        let params = params();
+        let mut state = coastline(&params);
+
+        run(&mut state, &params);
+
+        let headland = *state.wave_exposure.get(80, 63);
+        let lagoon = *state.wave_exposure.get(160, 172);
+        assert!(
+            headland > lagoon + 0.3,
+            "headland {headland} vs lagoon {lagoon}"
+        );
+    }
+
+    #[test]
+    fn onshore_wind_raises_exposure() {
+        let params = params();
+        let exposure = |wind_u: f32| {
+            let mut state = coastline(&params);
+            state.wind_u.fill(wind_u);
+            run(&mut state, &params);
+            *state.wave_exposure.get(128, 120)
+        };
+
+        // Wind blowing east comes off the open sea; blowing west, off the land.
+        assert!(exposure(1.0) > exposure(-1.0));
+    }
+
+    #[test]
+    fn coastal_biomes_are_classified() {
+        let params = params();
+        let mut state = coastline(&params);
+
+        biomes::run(&mut state, &params).expect("biomes");
+
+        let name = |x, y| state.biome_table.name(*state.biome.get(x, y)).to_string();
+        assert_eq!(name(128, 120), "Beach");
+        assert_eq!(name(128, 30), "Rocky Cliff");
+        assert_eq!(name(160, 172), "Mangrove");
+    }
+}
@@ -0,0 +1,223 @@
+use std::cmp::Ordering;
+
+use crate::params::GenerationParams;
+use crate::state::{IceCover, WorldState};
+use crate::systems::hydro_finalize::{self, DIRS_8};
+use crate::systems::{base_fields, erosion_hydrology};
+
+const GLACIER_MIN_FLUX: f32 = 0.5;
+/// Summer melt reaches this far below the mean-annual snowline temperature,
+/// giving glaciers an ablation zone that feeds meltwater to rivers.
+const SUMMER_MELT_OFFSET_C: f32 = 6.0;
+
+/// Snowpack above the snowline, glaciers grown from a simple mass balance and
+/// routed down `flow_dir`, glacial valley carving, and sea ice on cold ocean.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    state.ice_cover.fill(IceCover::None);
+    state.glacier_thickness.fill(0.0);
+    state.sea_ice.fill(0.0);
+    state.meltwater.fill(0.0);
+
+    flow_glaciers(state, params);
+    carve_glacial_valleys(state, params);
+    // Carved troughs sit lower and so warmer than the ground they replaced.
+    base_fields::build_temperature(state, params);
+    freeze_sea_ice(state, params);
+}
+
+/// Share of precipitation falling as snow at `temp_c`; the rest falls as rain
+/// and runs off directly.
+pub(crate) fn snow_share(temp_c: f32, params: &GenerationParams) -> f32 {
+    ((params.cryosphere.snowline_temp_c - temp_c) / 10.0).clamp(0.0, 1.0)
+}
+
+/// Routes ice flux downhill: tiles colder than the snowline gain their
+/// `snow_share` of precipitation as snow, warmer tiles ablate it into
+/// meltwater, and any surviving flux is glacier ice. Snow only reaches the
+/// rivers as meltwater; the liquid share is left to `seed_runoff`.
+fn flow_glaciers(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let cryo = &params.cryosphere;
+
+    let mean_rain = erosion_hydrology::mean_land_rainfall(state, sea_level);
+    let inv_mean = if mean_rain > 0.0 {
+        1.0 / mean_rain
+    } else {
+        0.0
+    };
+
+    let mut order: Vec<(usize, usize, f32)> = Vec::with_capacity(state.width * state.height);
+    for (x, y) in state.elevation.iter_coords() {
+        let h = *state.elevation.get(x, y);
+        if h > sea_level {
+            order.push((x, y, h));
+        }
+    }
+    order.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+
+    let mut flux = vec![0.0f32; state.width * state.height];
+    for (x, y, _) in order {
+        let idx = state.elevation.idx(x, y);
+        let temp = *state.temperature.get(x, y);
+        let snowfall = *state.rainfall.get(x, y) * inv_mean * snow_share(temp, params);
+        let ablation =
+            cryo.melt_rate * (temp - cryo.snowline_temp_c + SUMMER_MELT_OFFSET_C).max(0.0);
+
+        let available = flux[idx] + snowfall;
+        let melt = ablation.min(available);
+        let remaining = available - melt;
+        *state.meltwater.get_mut(x, y) = melt;
+
+        // Thin, slow-accumulating snow stays put as snowpack; only enough
+        // accumulated flux compacts into flowing ice.
+        if remaining >= GLACIER_MIN_FLUX {
+            *state.glacier_thickness.get_mut(x, y) = cryo.ice_thickness_m * remaining.sqrt();
+            *state.ice_cover.get_mut(x, y) = IceCover::Glacier;
+            if let Some((nx, ny)) = hydro_finalize::downstream(state, x, y) {
+                flux[state.elevation.idx(nx, ny)] += remaining;
+            }
+        } else if temp <= cryo.snowline_temp_c && remaining > 0.0 {
+            *state.ice_cover.get_mut(x, y) = IceCover::Snowpack;
+        }
+    }
+}
+
+/// Glaciers deepen their own bed and, more gently, the flanks beside it,
+/// turning V-shaped fluvial valleys into broad U-shaped troughs.
+fn carve_glacial_valleys(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let mut delta = vec![0.0f32; state.width * state.height];
+
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let thickness = *state.glacier_thickness.get(x, y);
+            if thickness <= 0.0 {
+                continue;
+            }
+            let depth = params.cryosphere.carve_depth * thickness / (thickness + 100.0);
+            let idx = state.elevation.idx(x, y);
+            delta[idx] = delta[idx].max(depth);
+
+            for (dx, dy) in DIRS_8 {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let nidx = state.elevation.idx(nx as usize, ny as usize);
+                delta[nidx] = delta[nidx].max(depth * 0.5);
+            }
+        }
+    }
+
+    for (h, d) in state.elevation.as_mut_slice().iter_mut().zip(&delta) {
+        if *h > sea_level && *d > 0.0 {
+            *h = (*h - d).max(sea_level + 1e-4);
+        }
+    }
+}
+
+fn freeze_sea_ice(state: &mut WorldState, params: &GenerationParams) {
+    for y in 0..state.height {
+        for x in 0..state.width {
+            if !*state.ocean_mask.get(x, y) {
+                continue;
+            }
+            let temp = *state.temperature.get(x, y);
+            let cover = ((params.cryosphere.sea_ice_temp_c - temp) / 10.0).clamp(0.0, 1.0);
+            if cover > 0.0 {
+                *state.sea_ice.get_mut(x, y) = cover;
+                *state.ice_cover.get_mut(x, y) = IceCover::SeaIce;
+            }
+        }
+    }
+}
+
+pub fn ice_color(cover: IceCover) -> [u8; 3] {
+    match cover {
+        IceCover::None => [20, 20, 20],
+        IceCover::Snowpack => [200, 210, 220],
+        IceCover::Glacier => [150, 210, 250],
+        IceCover::SeaIce => [236, 244, 250],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    fn params() -> GenerationParams {
+        GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        }
+    }
+
+    #[test]
+    fn sea_ice_covers_only_cold_ocean() {
+        let params = params();
+        let mut state = WorldState::new(params.clone());
+        state.ocean_mask.fill(true);
+        for (x, y) in state.elevation.iter_coords() {
+            *state.temperature.get_mut(x, y) = if y < 100 { -20.0 } else { 10.0 };
+        }
+        *state.ocean_mask.get_mut(5, 5) = false;
+
+        freeze_sea_ice(&mut state, &params);
+
+        assert_eq!(*state.sea_ice.get(50, 50), 1.0);
+        assert_eq!(*state.ice_cover.get(50, 50), IceCover::SeaIce);
+        assert_eq!(*state.sea_ice.get(50, 200), 0.0);
+        assert_eq!(*state.ice_cover.get(50, 200), IceCover::None);
+        assert_eq!(*state.ice_cover.get(5, 5), IceCover::None);
+    }
+
+    #[test]
+    fn glaciers_thicken_downhill_and_melt_below_the_snowline() {
+        let params = params();
+        let sea_level = params.base.sea_level;
+        let mut state = WorldState::new(params.clone());
+        // A slope falling east, frozen above column 100, with every tile
+        // draining to its eastern neighbour.
+        for y in 0..state.height {
+            for x in 0..state.width {
+                *state.elevation.get_mut(x, y) = sea_level + 0.4 * (1.0 - x as f32 / 256.0);
+                *state.temperature.get_mut(x, y) = if x < 100 { -20.0 } else { 10.0 };
+            }
+        }
+        state.rainfall.fill(1.0);
+        state.flow_dir.fill(0);
+
+        flow_glaciers(&mut state, &params);
+
+        let thickness = |x| *state.glacier_thickness.get(x, 128);
+        assert_eq!(*state.ice_cover.get(0, 128), IceCover::Glacier);
+        assert!(thickness(0) > 0.0);
+        assert!(thickness(90) > thickness(10));
+        assert!(*state.meltwater.get(120, 128) > 0.0);
+        assert_eq!(thickness(250), 0.0);
+        assert_eq!(*state.ice_cover.get(250, 128), IceCover::None);
+    }
+
+    #[test]
+    fn glaciers_carve_troughs() {
+        let params = params();
+        let sea_level = params.base.sea_level;
+        let mut state = WorldState::new(params.clone());
+        let ground = sea_level + 0.3;
+        state.elevation.fill(ground);
+        for x in 50..150 {
+            *state.glacier_thickness.get_mut(x, 128) = 300.0;
+        }
+
+        carve_glacial_valleys(&mut state, &params);
+
+        let depth = params.cryosphere.carve_depth * 300.0 / 400.0;
+        let h = |y| *state.elevation.get(100, y);
+        assert!((ground - h(128) - depth).abs() < 1e-6);
+        assert!((ground - h(127) - 0.5 * depth).abs() < 1e-6);
+        assert!((ground - h(129) - 0.5 * depth).abs() < 1e-6);
+        assert_eq!(h(126), ground);
+    }
+}
@@ -0,0 +1,15 @@
+use anyhow::Result;
+
+use crate::params::GenerationParams;
+use crate::state::WorldState;
+use crate::systems::{fauna, soils, vegetation, wilderness};
+
+/// Step 6: soils first, since vegetation is limited by rooting depth and
+/// wilderness by distance from fertile land; fauna last, as habitat follows
+/// everything else.
+pub fn run(state: &mut WorldState, params: &GenerationParams) -> Result<()> {
+    soils::run(state, params);
+    vegetation::run(state, params);
+    wilderness::run(state, params);
+    fauna::run(state, params)
+}
@@ -3,8 +3,9 @@ use std::cmp::Ordering;
 use crate::params::GenerationParams;
 use crate::rng::hash_2d;
 use crate::state::WorldState;
+use crate::systems::{cryosphere, hydro_finalize};
 
-const DIRS: [(isize, isize); 8] = [
+const DIRS_8: [(isize, isize); 8] = [
     (1, 0),
     (1, 1),
     (0, 1),
@@ -20,13 +21,17 @@ pub fn run(state: &mut WorldState, params: &GenerationParams) {
 
     for _ in 0..params.erosion.iterations {
         compute_flow_d8(state);
-        compute_accumulation(state);
+        compute_accumulation(state, params);
         apply_hydraulic_erosion(state, params);
         apply_thermal_relaxation(state, params);
     }
 
+    // Glaciers carve the terrain, so routing is rebuilt after the ice pass.
     compute_flow_d8(state);
-    compute_accumulation(state);
+    hydro_finalize::mark_ocean_component(state, params.base.sea_level);
+    cryosphere::run(state, params);
+    compute_flow_d8(state);
+    compute_accumulation(state, params);
 }
 
 fn fill_depressions(state: &mut WorldState, sea_level: f32) {
@@ -41,7 +46,7 @@ fn fill_depressions(state: &mut WorldState, sea_level: f32) {
                 }
 
                 let mut min_nb = f32::MAX;
-                for (dx, dy) in DIRS {
+                for (dx, dy) in DIRS_8 {
                     let nx = (x as isize + dx) as usize;
                     let ny = (y as isize + dy) as usize;
                     min_nb = min_nb.min(*state.elevation.get(nx, ny));
@@ -68,7 +73,7 @@ fn compute_flow_d8(state: &mut WorldState) {
             let mut best_dir = 255u8;
             let mut best_tie = 0.0f32;
 
-            for (i, (dx, dy)) in DIRS.iter().enumerate() {
+            for (i, (dx, dy)) in DIRS_8.iter().enumerate() {
                 let nx = x as isize + dx;
                 let ny = y as isize + dy;
                 if !state.elevation.in_bounds(nx, ny) {
@@ -103,8 +108,16 @@ fn compute_flow_d8(state: &mut WorldState) {
     }
 }
 
-fn compute_accumulation(state: &mut WorldState) {
+/// Routes contributing area into `accumulation` and climate-driven runoff into
+/// `discharge` with the same multiple-flow-direction weights.
+///
+/// Discharge is seeded from a Budyko-style water balance (rainfall minus
+/// evapotranspiration) and loses water to evaporation and infiltration on the
+/// way downstream, so rivers can shrink or vanish crossing dry country. Lake
+/// tiles additionally lose surface evaporation once lakes have been identified.
+pub(crate) fn compute_accumulation(state: &mut WorldState, params: &GenerationParams) {
     state.accumulation.fill(1.0);
+    seed_runoff(state, params);
 
     let mut order: Vec<(usize, usize, f32)> = Vec::with_capacity(state.width * state.height);
     for (x, y) in state.elevation.iter_coords() {
@@ -116,11 +129,20 @@ fn compute_accumulation(state: &mut WorldState) {
         let h = *state.elevation.get(x, y);
         let q = *state.accumulation.get(x, y);
 
+        let pet = potential_evapotranspiration(*state.temperature.get(x, y), params);
+        let mut loss = params.erosion.channel_loss_rate * (pet + 0.1);
+        if *state.lake_id.get(x, y) > 0 {
+            loss += params.hydro.lake_evaporation * pet;
+        }
+        let inflow = *state.discharge.get(x, y);
+        let d = (inflow - loss * inflow.sqrt()).max(0.0);
+        *state.discharge.get_mut(x, y) = d;
+
         let mut targets: [(usize, usize, f32); 8] = [(0, 0, 0.0); 8];
         let mut n_targets = 0usize;
         let mut wsum = 0.0f32;
 
-        for (dx, dy) in DIRS {
+        for (dx, dy) in DIRS_8 {
             let nx = x as isize + dx;
             let ny = y as isize + dy;
             if !state.elevation.in_bounds(nx, ny) {
@@ -149,12 +171,67 @@ fn compute_accumulation(state: &mut WorldState) {
 
         for &(nx, ny, w) in &targets[..n_targets] {
             let frac = w / wsum;
-            let dst = state.accumulation.get_mut(nx, ny);
-            *dst += q * frac;
+            *state.accumulation.get_mut(nx, ny) += q * frac;
+            *state.discharge.get_mut(nx, ny) += d * frac;
         }
     }
+}
+
+/// Fills `discharge` with local runoff in tile-equivalents: a land tile with
+/// average rainfall and no evaporative demand yields `runoff_coefficient`.
+/// Only the liquid share of precipitation runs off here; the snow share
+/// reaches the rivers as glacial meltwater, on the same scale.
+fn seed_runoff(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let coefficient = params.erosion.runoff_coefficient;
+    let mean_rain = mean_land_rainfall(state, sea_level);
+    let inv_mean = if mean_rain > 0.0 {
+        1.0 / mean_rain
+    } else {
+        0.0
+    };
 
-    state.discharge.clone_from(&state.accumulation);
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let runoff = if *state.elevation.get(x, y) <= sea_level {
+                0.0
+            } else {
+                let temp = *state.temperature.get(x, y);
+                let rain = 1.0 - cryosphere::snow_share(temp, params);
+                let p = *state.rainfall.get(x, y) * inv_mean * rain * coefficient;
+                let pet = potential_evapotranspiration(temp, params);
+                // Schreiber curve: runoff fraction decays with the aridity index PET/P.
+                let rain_runoff = if p > 0.0 { p * (-pet / p).exp() } else { 0.0 };
+                rain_runoff + *state.meltwater.get(x, y) * coefficient
+            };
+            *state.discharge.get_mut(x, y) = runoff;
+        }
+    }
+}
+
+pub(crate) fn mean_land_rainfall(state: &WorldState, sea_level: f32) -> f32 {
+    let mut land_rain = 0.0f64;
+    let mut land_tiles = 0usize;
+    for (h, r) in state
+        .elevation
+        .as_slice()
+        .iter()
+        .zip(state.rainfall.as_slice())
+    {
+        if *h > sea_level {
+            land_rain += f64::from(*r);
+            land_tiles += 1;
+        }
+    }
+    if land_tiles > 0 {
+        (land_rain / land_tiles as f64) as f32
+    } else {
+        0.0
+    }
+}
+
+fn potential_evapotranspiration(temp_c: f32, params: &GenerationParams) -> f32 {
+    params.erosion.evapotranspiration_rate * ((temp_c + 10.0) / 40.0).max(0.0)
 }
 
 fn apply_hydraulic_erosion(state: &mut WorldState, params: &GenerationParams) {
@@ -170,7 +247,7 @@ fn apply_hydraulic_erosion(state: &mut WorldState, params: &GenerationParams) {
                 continue;
             }
             let h = *state.elevation.get(x, y);
-            let (dx, dy) = DIRS[usize::from(dir)];
+            let (dx, dy) = DIRS_8[usize::from(dir)];
             let nx = (x as isize + dx) as usize;
             let ny = (y as isize + dy) as usize;
             let nh = *state.elevation.get(nx, ny);
@@ -215,7 +292,7 @@ fn apply_thermal_relaxation(state: &mut WorldState, params: &GenerationParams) {
             let h = *state.elevation.get(x, y);
             let mut sum = 0.0;
             let mut count = 0.0;
-            for (dx, dy) in DIRS {
+            for (dx, dy) in DIRS_8 {
                 let nx = (x as isize + dx) as usize;
                 let ny = (y as isize + dy) as usize;
                 sum += *state.elevation.get(nx, ny);
@@ -229,3 +306,94 @@ fn apply_thermal_relaxation(state: &mut WorldState, params: &GenerationParams) {
 
     state.elevation = out;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    /// A plane tilted towards the bottom edge, with lossless routing.
+    fn tilted_plane() -> (WorldState, GenerationParams) {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.erosion.evapotranspiration_rate = 0.0;
+        params.erosion.channel_loss_rate = 0.0;
+        let mut state = WorldState::new(params.clone());
+        let height = state.height as f32;
+        for (x, y) in state.elevation.clone().iter_coords() {
+            *state.elevation.get_mut(x, y) = 0.9 - 0.3 * y as f32 / height;
+        }
+        state.rainfall.fill(0.002);
+        state.temperature.fill(15.0);
+        (state, params)
+    }
+
+    #[test]
+    fn routing_conserves_runoff_without_losses() {
+        let (mut state, params) = tilted_plane();
+        compute_accumulation(&mut state, &params);
+
+        let seeded = (state.width * state.height) as f32 * params.erosion.runoff_coefficient;
+        let outlet = state.height - 1;
+        let delivered: f32 = (0..state.width)
+            .map(|x| *state.discharge.get(x, outlet))
+            .sum();
+        assert!(
+            (delivered - seeded).abs() <= seeded * 1e-3,
+            "delivered {delivered}, seeded {seeded}"
+        );
+    }
+
+    /// Discharge leaving the map or collecting in pits: every tile with no
+    /// lower neighbour.
+    fn delivered(state: &WorldState) -> f32 {
+        state
+            .elevation
+            .iter_coords()
+            .filter(|&(x, y)| {
+                let h = *state.elevation.get(x, y);
+                DIRS_8.iter().all(|&(dx, dy)| {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    !state.elevation.in_bounds(nx, ny)
+                        || *state.elevation.get(nx as usize, ny as usize) >= h
+                })
+            })
+            .map(|(x, y)| *state.discharge.get(x, y))
+            .sum()
+    }
+
+    #[test]
+    fn snow_reaches_rivers_only_as_meltwater() {
+        let (mut state, params) = tilted_plane();
+        crate::systems::base_fields::build_temperature(&mut state, &params);
+        compute_flow_d8(&mut state);
+        cryosphere::run(&mut state, &params);
+        compute_flow_d8(&mut state);
+        compute_accumulation(&mut state, &params);
+
+        let coefficient = params.erosion.runoff_coefficient;
+        let glaciers = state
+            .glacier_thickness
+            .as_slice()
+            .iter()
+            .filter(|t| **t > 0.0)
+            .count();
+        assert!(glaciers > 0);
+
+        let mut expected = 0.0f32;
+        for (x, y) in state.elevation.iter_coords() {
+            let rain = 1.0 - cryosphere::snow_share(*state.temperature.get(x, y), &params);
+            expected += coefficient * (rain + *state.meltwater.get(x, y));
+        }
+        let precipitation = (state.width * state.height) as f32 * coefficient;
+        let delivered = delivered(&state);
+        assert!(
+            (delivered - expected).abs() <= expected * 1e-3,
+            "delivered {delivered}, expected {expected}"
+        );
+        // Snow still held as ice or snowpack never reaches the rivers.
+        assert!(delivered < precipitation);
+    }
+}
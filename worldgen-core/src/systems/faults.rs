@@ -0,0 +1,443 @@
+use std::f32::consts::{FRAC_PI_3, PI, TAU};
+
+use crate::params::GenerationParams;
+use crate::rng::SplitMix64;
+use crate::state::{Fault, FaultKind, WorldState};
+use crate::systems::base_fields::{seed_offset, ELEVATION_SPAN_M};
+
+/// Tiles walked between recorded trace vertices.
+const VERTEX_SPACING: usize = 3;
+/// Tiles either side over which elevation gives the strike of a belt.
+const STRIKE_REACH: isize = 3;
+/// Share of each step's heading pulled toward the belt's strike or the
+/// lineament set's trend.
+const STEER: f32 = 0.25;
+/// Largest random turn per step, radians.
+const JITTER: f32 = 0.12;
+/// Range of a trace's half-length as a share of the longer map side.
+const HALF_LENGTH: (f32, f32) = (0.06, 0.16);
+/// Share of land tiles, by local relief, where boundary faults may start.
+const BELT_SHARE: usize = 20;
+/// Tries at finding a start away from earlier faults before giving up.
+const START_TRIES: usize = 32;
+/// Raised blocks keep full uplift this far from the trace and fade to none
+/// at twice the distance.
+const BLOCK_WIDTH_TILES: f32 = 10.0;
+/// Normalized depth, at fault strength 1, of the trough that crushed rock
+/// erodes into along a trace; rivers are drawn into it.
+const ZONE_NOTCH: f32 = 0.04;
+const NOTCH_WIDTH_TILES: f32 = 2.0;
+
+/// Greatest throw at fault strength 1, metres.
+fn max_throw_m(kind: FaultKind) -> f32 {
+    match kind {
+        FaultKind::Reverse => 240.0,
+        FaultKind::Normal => 160.0,
+        FaultKind::StrikeSlip => 30.0,
+    }
+}
+
+/// A tile crossed by a trace: which fault, how far along it (0..1), the
+/// local direction and the exact point.
+#[derive(Clone, Copy)]
+struct TraceTile {
+    fault: usize,
+    along: f32,
+    dir: [f32; 2],
+    at: [f32; 2],
+}
+
+/// Fault traces as polylines: thrusts following the strike of mountain
+/// belts and normal or strike-slip faults along two conjugate sets of seeded
+/// lineaments. Each raises one block, recorded in `fault_uplift_m` for the
+/// geology step to offset strata, and lifts the land surface to match; a
+/// trough along the trace draws rivers before erosion runs.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let gp = &params.geology;
+    let sea_level = params.base.sea_level;
+    let mut rng = SplitMix64::new(seed_offset(params.seed, 2222));
+    let span = state.width.max(state.height) as f32;
+    let spacing = span / 12.0;
+    let strength = gp.fault_strength.max(0.0);
+
+    let land: Vec<usize> = (0..state.width * state.height)
+        .filter(|&i| state.elevation.as_slice()[i] > sea_level)
+        .collect();
+    let mut belt: Vec<(f32, usize)> = land.iter().map(|&i| (relief(state, i), i)).collect();
+    belt.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    belt.truncate(belt.len().div_ceil(BELT_SHARE));
+    let belt: Vec<usize> = belt.into_iter().map(|(_, i)| i).collect();
+
+    let mut faults: Vec<Fault> = Vec::new();
+    let mut starts: Vec<[f32; 2]> = Vec::new();
+    for _ in 0..gp.boundary_faults {
+        let Some(start) = pick_start(state, &mut rng, &belt, &starts, spacing) else {
+            continue;
+        };
+        let heading =
+            strike(state, start).map_or(rng.gen_range_f32(0.0, TAU), |s| s[1].atan2(s[0]));
+        let steps = (span * rng.gen_range_f32(HALF_LENGTH.0, HALF_LENGTH.1)) as usize;
+        let Some(points) = trace(state, &mut rng, start, heading, steps, true) else {
+            continue;
+        };
+        // The hanging wall rides up toward the high ground.
+        let normal = [-heading.sin(), heading.cos()];
+        let left = sample(state, start, normal, -4.0);
+        let right = sample(state, start, normal, 4.0);
+        let kind = FaultKind::Reverse;
+        faults.push(Fault {
+            id: faults.len() as u32 + 1,
+            kind,
+            points,
+            throw_m: max_throw_m(kind) * strength * rng.gen_range_f32(0.5, 1.0),
+            upthrown_left: left >= right,
+        });
+        starts.push(start);
+    }
+
+    let trend = rng.gen_range_f32(0.0, PI);
+    for i in 0..gp.lineament_faults {
+        let Some(start) = pick_start(state, &mut rng, &land, &starts, spacing) else {
+            continue;
+        };
+        let set = if i % 2 == 0 { 0.0 } else { FRAC_PI_3 };
+        let heading = trend + set + rng.gen_range_f32(-0.2, 0.2);
+        let steps = (span * rng.gen_range_f32(HALF_LENGTH.0, HALF_LENGTH.1)) as usize;
+        let Some(points) = trace(state, &mut rng, start, heading, steps, false) else {
+            continue;
+        };
+        let kind = if rng.next_f32() < 0.6 {
+            FaultKind::Normal
+        } else {
+            FaultKind::StrikeSlip
+        };
+        faults.push(Fault {
+            id: faults.len() as u32 + 1,
+            kind,
+            points,
+            throw_m: max_throw_m(kind) * strength * rng.gen_range_f32(0.5, 1.0),
+            upthrown_left: rng.next_f32() < 0.5,
+        });
+        starts.push(start);
+    }
+
+    let nearest = nearest_trace(state, &faults);
+    for (idx, nearest) in nearest.into_iter().enumerate() {
+        let (x, y) = (idx % state.width, idx / state.width);
+        let Some(tile) = nearest else {
+            *state.fault_distance.get_mut(x, y) = span;
+            *state.fault_id.get_mut(x, y) = 0;
+            *state.fault_uplift_m.get_mut(x, y) = 0.0;
+            continue;
+        };
+        let fault = &faults[tile.fault];
+        let v = [x as f32 - tile.at[0], y as f32 - tile.at[1]];
+        let dist = v[0].hypot(v[1]);
+        // With y pointing down, points left of the trace give a negative
+        // cross product.
+        let cross = tile.dir[0] * v[1] - tile.dir[1] * v[0];
+        let raised = if fault.upthrown_left {
+            cross < 0.0
+        } else {
+            cross > 0.0
+        };
+        let taper = 1.0 - (2.0 * tile.along - 1.0).powi(2);
+        let block = (2.0 - dist / BLOCK_WIDTH_TILES).clamp(0.0, 1.0);
+        let uplift = if raised {
+            fault.throw_m * taper * block
+        } else {
+            0.0
+        };
+
+        *state.fault_distance.get_mut(x, y) = dist.min(span);
+        *state.fault_id.get_mut(x, y) = fault.id;
+        *state.fault_uplift_m.get_mut(x, y) = uplift;
+
+        let h = state.elevation.get_mut(x, y);
+        if *h > sea_level {
+            let notch = ZONE_NOTCH * strength * (-dist / NOTCH_WIDTH_TILES).exp();
+            *h = (*h + uplift / ELEVATION_SPAN_M - notch).clamp(sea_level + 1e-4, 1.0);
+        }
+    }
+    state.faults = faults;
+}
+
+/// Elevation range within `STRIKE_REACH` of tile `idx`.
+fn relief(state: &WorldState, idx: usize) -> f32 {
+    let (x, y) = ((idx % state.width) as isize, (idx / state.width) as isize);
+    let mut lo = f32::MAX;
+    let mut hi = f32::MIN;
+    for dy in -STRIKE_REACH..=STRIKE_REACH {
+        for dx in -STRIKE_REACH..=STRIKE_REACH {
+            if state.elevation.in_bounds(x + dx, y + dy) {
+                let h = *state.elevation.get((x + dx) as usize, (y + dy) as usize);
+                lo = lo.min(h);
+                hi = hi.max(h);
+            }
+        }
+    }
+    hi - lo
+}
+
+/// A tile from `pool` at least `spacing` tiles from every earlier start.
+fn pick_start(
+    state: &WorldState,
+    rng: &mut SplitMix64,
+    pool: &[usize],
+    starts: &[[f32; 2]],
+    spacing: f32,
+) -> Option<[f32; 2]> {
+    if pool.is_empty() {
+        return None;
+    }
+    (0..START_TRIES).find_map(|_| {
+        let idx = pool[(rng.next_u64() % pool.len() as u64) as usize];
+        let p = [(idx % state.width) as f32, (idx / state.width) as f32];
+        starts
+            .iter()
+            .all(|s| (s[0] - p[0]).hypot(s[1] - p[1]) >= spacing)
+            .then_some(p)
+    })
+}
+
+/// Unit vector along the contours at `p`, or `None` on flat ground.
+fn strike(state: &WorldState, p: [f32; 2]) -> Option<[f32; 2]> {
+    let h = |dx: isize, dy: isize| {
+        let x = (p[0].round() as isize + dx).clamp(0, state.width as isize - 1);
+        let y = (p[1].round() as isize + dy).clamp(0, state.height as isize - 1);
+        *state.elevation.get(x as usize, y as usize)
+    };
+    let gx = h(STRIKE_REACH, 0) - h(-STRIKE_REACH, 0);
+    let gy = h(0, STRIKE_REACH) - h(0, -STRIKE_REACH);
+    let mag = gx.hypot(gy);
+    (mag > 1e-4).then(|| [-gy / mag, gx / mag])
+}
+
+/// Elevation `offset` tiles from `p` along `dir`, clamped to the map.
+fn sample(state: &WorldState, p: [f32; 2], dir: [f32; 2], offset: f32) -> f32 {
+    let x = (p[0] + dir[0] * offset)
+        .round()
+        .clamp(0.0, state.width as f32 - 1.0);
+    let y = (p[1] + dir[1] * offset)
+        .round()
+        .clamp(0.0, state.height as f32 - 1.0);
+    *state.elevation.get(x as usize, y as usize)
+}
+
+/// A trace walked `steps` tiles both ways from `start`, or `None` where both
+/// walks leave the map at once and no line remains.
+fn trace(
+    state: &WorldState,
+    rng: &mut SplitMix64,
+    start: [f32; 2],
+    heading: f32,
+    steps: usize,
+    follow_strike: bool,
+) -> Option<Vec<[f32; 2]>> {
+    let back = walk(state, rng, start, heading + PI, steps, follow_strike);
+    let forward = walk(state, rng, start, heading, steps, follow_strike);
+    let mut points: Vec<[f32; 2]> = back.into_iter().rev().collect();
+    points.extend(forward.into_iter().skip(1));
+    (points.len() >= 2).then_some(points)
+}
+
+/// Vertices from `start` along `heading`, steered toward the local strike or
+/// back toward the heading, until `steps` tiles or the map edge.
+fn walk(
+    state: &WorldState,
+    rng: &mut SplitMix64,
+    start: [f32; 2],
+    heading: f32,
+    steps: usize,
+    follow_strike: bool,
+) -> Vec<[f32; 2]> {
+    let trend = [heading.cos(), heading.sin()];
+    let mut dir = trend;
+    let mut p = start;
+    let mut points = vec![start];
+    for step in 1..=steps {
+        let target = if follow_strike {
+            strike(state, p).map_or(dir, |s| {
+                if s[0] * dir[0] + s[1] * dir[1] < 0.0 {
+                    [-s[0], -s[1]]
+                } else {
+                    s
+                }
+            })
+        } else {
+            trend
+        };
+        let turn = rng.gen_range_f32(-JITTER, JITTER);
+        let steered = [
+            dir[0] + (target[0] - dir[0]) * STEER,
+            dir[1] + (target[1] - dir[1]) * STEER,
+        ];
+        let (sin, cos) = turn.sin_cos();
+        let turned = [
+            steered[0] * cos - steered[1] * sin,
+            steered[0] * sin + steered[1] * cos,
+        ];
+        let len = turned[0].hypot(turned[1]);
+        if len > 1e-6 {
+            dir = [turned[0] / len, turned[1] / len];
+        }
+        let next = [p[0] + dir[0], p[1] + dir[1]];
+        if next[0] < 0.0
+            || next[1] < 0.0
+            || next[0] > (state.width - 1) as f32
+            || next[1] > (state.height - 1) as f32
+        {
+            break;
+        }
+        p = next;
+        if step % VERTEX_SPACING == 0 {
+            points.push(p);
+        }
+    }
+    if points.last() != Some(&p) {
+        points.push(p);
+    }
+    points
+}
+
+/// The trace tile nearest each tile, by a two-pass sweep that hands each
+/// tile's nearest trace point on to its neighbours.
+fn nearest_trace(state: &WorldState, faults: &[Fault]) -> Vec<Option<TraceTile>> {
+    let (width, height) = (state.width, state.height);
+    let n = width * height;
+    let mut nearest: Vec<Option<TraceTile>> = vec![None; n];
+    let mut dist = vec![f32::INFINITY; n];
+    let dist_to = |idx: usize, t: &TraceTile| {
+        let (x, y) = ((idx % width) as f32, (idx / width) as f32);
+        (x - t.at[0]).hypot(y - t.at[1])
+    };
+
+    for (fi, fault) in faults.iter().enumerate() {
+        let lengths: Vec<f32> = fault
+            .points
+            .windows(2)
+            .map(|w| (w[1][0] - w[0][0]).hypot(w[1][1] - w[0][1]))
+            .collect();
+        let total: f32 = lengths.iter().sum::<f32>().max(1e-6);
+        let mut walked = 0.0;
+        for (w, &len) in fault.points.windows(2).zip(&lengths) {
+            let (a, b) = (w[0], w[1]);
+            let dir = if len > 1e-6 {
+                [(b[0] - a[0]) / len, (b[1] - a[1]) / len]
+            } else {
+                [1.0, 0.0]
+            };
+            let samples = (len * 2.0).ceil().max(1.0) as usize;
+            for j in 0..=samples {
+                let t = j as f32 / samples as f32;
+                let at = [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+                let x = (at[0].round() as usize).min(width - 1);
+                let y = (at[1].round() as usize).min(height - 1);
+                let idx = y * width + x;
+                let tile = TraceTile {
+                    fault: fi,
+                    along: (walked + len * t) / total,
+                    dir,
+                    at,
+                };
+                let d = dist_to(idx, &tile);
+                if d < dist[idx] {
+                    dist[idx] = d;
+                    nearest[idx] = Some(tile);
+                }
+            }
+            walked += len;
+        }
+    }
+
+    const FORWARD: [(isize, isize); 4] = [(-1, -1), (0, -1), (1, -1), (-1, 0)];
+    const BACKWARD: [(isize, isize); 4] = [(1, 1), (0, 1), (-1, 1), (1, 0)];
+    for _ in 0..2 {
+        for pass in [FORWARD, BACKWARD] {
+            let forward = pass == FORWARD;
+            for k in 0..n {
+                let idx = if forward { k } else { n - 1 - k };
+                let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+                for (dx, dy) in pass {
+                    if !state.elevation.in_bounds(x + dx, y + dy) {
+                        continue;
+                    }
+                    let nidx = state.elevation.idx((x + dx) as usize, (y + dy) as usize);
+                    let Some(tile) = nearest[nidx] else {
+                        continue;
+                    };
+                    let d = dist_to(idx, &tile);
+                    if d < dist[idx] {
+                        dist[idx] = d;
+                        nearest[idx] = Some(tile);
+                    }
+                }
+            }
+        }
+    }
+    nearest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn upthrown_block_is_raised() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.geology.fault_strength = 1.0;
+        params.geology.boundary_faults = 0;
+        params.geology.lineament_faults = 1;
+        let mut state = WorldState::new(params.clone());
+        state.elevation.fill(0.6);
+
+        run(&mut state, &params);
+
+        assert_eq!(state.faults.len(), 1);
+        let fault = &state.faults[0];
+        let mid = fault.points.len() / 2;
+        let (a, b) = (fault.points[mid - 1], fault.points[mid + 1]);
+        let len = (b[0] - a[0]).hypot(b[1] - a[1]);
+        let dir = [(b[0] - a[0]) / len, (b[1] - a[1]) / len];
+        // With y pointing down, left of the trace is along (dir.y, -dir.x).
+        let at = |side: f32| {
+            let p = fault.points[mid];
+            let x = (p[0] + side * 5.0 * dir[1]).round() as usize;
+            let y = (p[1] - side * 5.0 * dir[0]).round() as usize;
+            (x, y)
+        };
+        let (up, down) = if fault.upthrown_left {
+            (at(1.0), at(-1.0))
+        } else {
+            (at(-1.0), at(1.0))
+        };
+
+        assert!(*state.fault_uplift_m.get(up.0, up.1) > 0.5 * fault.throw_m);
+        assert_eq!(*state.fault_uplift_m.get(down.0, down.1), 0.0);
+        assert!(*state.elevation.get(up.0, up.1) > *state.elevation.get(down.0, down.1));
+        assert_eq!(*state.fault_id.get(up.0, up.1), fault.id);
+    }
+
+    #[test]
+    fn traces_leaving_the_map_at_once_are_dropped() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let state = WorldState::new(params.clone());
+        let mut rng = SplitMix64::new(params.seed);
+
+        // From the corner, both ways along this heading are off the map.
+        let heading = -std::f32::consts::FRAC_PI_4;
+        assert!(trace(&state, &mut rng, [0.0, 0.0], heading, 20, false).is_none());
+
+        // Along the border the trace still runs inward.
+        let points = trace(&state, &mut rng, [0.0, 100.0], heading, 20, false)
+            .expect("trace along the border");
+        assert!(points.len() >= 2);
+    }
+}
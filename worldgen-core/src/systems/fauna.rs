@@ -0,0 +1,219 @@
+use anyhow::Result;
+
+use crate::creature_catalogue::{CreatureCatalogue, CreatureDef, CreatureHabitat};
+use crate::grid::Grid2D;
+use crate::params::GenerationParams;
+use crate::rng::hash_2d;
+use crate::state::{CreatureSite, CreatureSiteKind, RiverClass, WorldState};
+use crate::systems::base_fields::seed_offset;
+
+/// Temperature suitability fades to zero this many °C outside a species' range.
+const TEMPERATURE_FADE_C: f32 = 5.0;
+/// Tiles below this suitability never host a site.
+const MIN_SITE_SUITABILITY: f32 = 0.2;
+
+/// Habitat suitability grids for every catalogue species, then seeded
+/// population and lair sites drawn from them.
+pub fn run(state: &mut WorldState, params: &GenerationParams) -> Result<()> {
+    state.creature_catalogue = match &params.fauna.catalogue {
+        Some(path) => CreatureCatalogue::load(path)?,
+        None => CreatureCatalogue::builtin(),
+    };
+
+    let catalogue = state.creature_catalogue.clone();
+    state.habitat_suitability = catalogue
+        .iter()
+        .map(|(_, def)| suitability_grid(state, def))
+        .collect();
+
+    state.creature_sites.clear();
+    for (species, def) in catalogue.iter() {
+        let sites = sample_sites(state, params, species, def);
+        state.creature_sites.extend(sites);
+    }
+    Ok(())
+}
+
+fn suitability_grid(state: &WorldState, def: &CreatureDef) -> Grid2D<f32> {
+    // Biome names are resolved against this world's table; names it does not
+    // define simply contribute nothing.
+    let affinity: Vec<f32> = state
+        .biome_table
+        .iter()
+        .map(|(_, b)| {
+            def.biomes
+                .get(&b.name)
+                .copied()
+                .unwrap_or(0.0)
+                .clamp(0.0, 1.0)
+        })
+        .collect();
+
+    let mut grid = Grid2D::new(state.width, state.height, 0.0f32);
+    for (x, y) in state.elevation.iter_coords() {
+        let ocean = *state.ocean_mask.get(x, y);
+        let lake = *state.lake_id.get(x, y) > 0;
+        let habitat = match def.habitat {
+            CreatureHabitat::Land if !ocean && !lake => 1.0,
+            CreatureHabitat::Marine if ocean => 1.0,
+            CreatureHabitat::Freshwater if lake => 1.0,
+            CreatureHabitat::Freshwater if !ocean => match *state.river_class.get(x, y) {
+                RiverClass::Major => 1.0,
+                RiverClass::Perennial => 0.6,
+                RiverClass::Ephemeral | RiverClass::None => 0.0,
+            },
+            _ => 0.0,
+        };
+        if habitat <= 0.0 {
+            continue;
+        }
+
+        let biome_fit: f32 = state
+            .biome_blend
+            .get(x, y)
+            .iter()
+            .map(|(b, w)| affinity[usize::from(b.as_u8())] * w)
+            .sum();
+        let temp_fit = temperature_fit(def, *state.temperature.get(x, y));
+        *grid.get_mut(x, y) = habitat * biome_fit * temp_fit;
+    }
+    grid
+}
+
+fn temperature_fit(def: &CreatureDef, temp: f32) -> f32 {
+    let Some([min, max]) = def.temperature else {
+        return 1.0;
+    };
+    let below = min.map_or(0.0, |m| m - temp).max(0.0);
+    let above = max.map_or(0.0, |m| temp - m).max(0.0);
+    (1.0 - (below + above) / TEMPERATURE_FADE_C).clamp(0.0, 1.0)
+}
+
+/// Weighted sampling without replacement (each tile keyed by `u^(1/w)`),
+/// thinned so sites keep `min_site_spacing` apart. Rarer species get fewer
+/// sites, but any species with habitat gets at least one.
+fn sample_sites(
+    state: &WorldState,
+    params: &GenerationParams,
+    species: usize,
+    def: &CreatureDef,
+) -> Vec<CreatureSite> {
+    let grid = &state.habitat_suitability[species];
+    let total: f32 = grid.as_slice().iter().sum();
+    if total < 1.0 {
+        return Vec::new();
+    }
+    let commonness = (1.0 - def.rarity).powi(2);
+    let target = ((total * params.fauna.site_density * commonness).round() as usize).max(1);
+
+    let seed = seed_offset(params.seed, 1414 + species as u64);
+    let mut candidates: Vec<(f32, usize, usize, f32)> = Vec::new();
+    for (x, y) in grid.iter_coords() {
+        let s = *grid.get(x, y);
+        if s < MIN_SITE_SUITABILITY {
+            continue;
+        }
+        let u = hash_2d(seed, x as i32, y as i32).max(1e-6);
+        candidates.push((u.powf(1.0 / s), x, y, s));
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let spacing_sq = params.fauna.min_site_spacing * params.fauna.min_site_spacing;
+    let kind = if def.lairs {
+        CreatureSiteKind::Lair
+    } else {
+        CreatureSiteKind::Population
+    };
+    let mut sites: Vec<CreatureSite> = Vec::with_capacity(target);
+    for (key, x, y, s) in candidates {
+        if sites.len() >= target {
+            break;
+        }
+        let crowded = sites.iter().any(|site| {
+            let dx = site.x as f32 - x as f32;
+            let dy = site.y as f32 - y as f32;
+            dx * dx + dy * dy < spacing_sq
+        });
+        if crowded {
+            continue;
+        }
+        // Reuse the sampling key as a second draw for group size.
+        let population = (def.group_size as f32 * (0.5 + s) * (0.75 + 0.5 * key)).round();
+        sites.push(CreatureSite {
+            species,
+            kind,
+            x,
+            y,
+            population: (population as u32).max(1),
+            suitability: s,
+        });
+    }
+    sites
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::MapSizePreset;
+
+    fn creature(rarity: f32, temperature: Option<[Option<f32>; 2]>) -> CreatureDef {
+        CreatureDef {
+            name: "Test".to_string(),
+            habitat: CreatureHabitat::Land,
+            biomes: BTreeMap::new(),
+            temperature,
+            rarity,
+            group_size: 10,
+            lairs: false,
+        }
+    }
+
+    #[test]
+    fn temperature_fit_fades_at_range_edges() {
+        let def = creature(0.0, Some([Some(0.0), Some(20.0)]));
+        assert_eq!(temperature_fit(&def, 10.0), 1.0);
+        assert_eq!(temperature_fit(&def, 20.0), 1.0);
+        assert!((temperature_fit(&def, 22.5) - 0.5).abs() < 1e-6);
+        assert!((temperature_fit(&def, -2.5) - 0.5).abs() < 1e-6);
+        assert_eq!(temperature_fit(&def, 25.0), 0.0);
+        assert_eq!(temperature_fit(&def, -30.0), 0.0);
+
+        let open_above = creature(0.0, Some([Some(0.0), None]));
+        assert_eq!(temperature_fit(&open_above, 45.0), 1.0);
+        assert_eq!(temperature_fit(&creature(0.0, None), -40.0), 1.0);
+    }
+
+    #[test]
+    fn sites_keep_their_spacing_and_rare_species_get_one() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.fauna.site_density = 0.05;
+        params.fauna.min_site_spacing = 6.0;
+        let mut state = WorldState::new(params.clone());
+        state.habitat_suitability = vec![
+            Grid2D::new(state.width, state.height, 0.8),
+            Grid2D::new(state.width, state.height, 0.0),
+        ];
+        *state.habitat_suitability[1].get_mut(40, 40) = 0.6;
+        *state.habitat_suitability[1].get_mut(41, 40) = 0.6;
+
+        let common = sample_sites(&state, &params, 0, &creature(0.0, None));
+        assert!(common.len() > 100, "{} sites", common.len());
+        let spacing_sq = params.fauna.min_site_spacing.powi(2);
+        for (i, a) in common.iter().enumerate() {
+            for b in &common[i + 1..] {
+                let dx = a.x as f32 - b.x as f32;
+                let dy = a.y as f32 - b.y as f32;
+                assert!(dx * dx + dy * dy >= spacing_sq);
+            }
+        }
+
+        let legendary = sample_sites(&state, &params, 1, &creature(1.0, None));
+        assert_eq!(legendary.len(), 1);
+        assert_eq!(legendary[0].y, 40);
+    }
+}
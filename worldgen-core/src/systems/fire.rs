@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+
+use crate::params::GenerationParams;
+use crate::rng::SplitMix64;
+use crate::state::{IceCover, WorldState};
+use crate::systems::base_fields::seed_offset;
+use crate::systems::erosion_hydrology;
+
+const DIRS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Leaf area index at which fuel load saturates.
+const FULL_FUEL_LAI: f32 = 4.0;
+/// Return interval assigned to burnable tiles too rarely ignited to burn in
+/// the simulated span and with no analytic estimate either.
+const MAX_RETURN_INTERVAL_YEARS: f32 = 10_000.0;
+/// Ceiling on per-neighbour spread chance so every fire eventually dies out.
+const MAX_SPREAD_CHANCE: f32 = 0.9;
+
+/// Fire weather from fuel, dryness and wind, then a seeded history of fires
+/// whose scars give `burn_scar_age` and `fire_return_interval`.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let n = state.width * state.height;
+    let (fuel, dryness) = fire_weather(state, params);
+
+    for i in 0..n {
+        let freq = params.fire.ignition_rate * fuel[i].sqrt() * dryness[i];
+        state.fire_frequency.as_mut_slice()[i] = freq;
+    }
+
+    let span = params.fire.simulated_years;
+    let (burns, last_burn) = simulate_fires(state, params, &fuel, &dryness);
+
+    for i in 0..n {
+        let (age, interval) = if fuel[i] <= 0.0 {
+            (0.0, 0.0)
+        } else if burns[i] > 0 {
+            let age = (span - last_burn[i]) as f32;
+            (age, span as f32 / burns[i] as f32)
+        } else {
+            // Never burned in the record: at least `span` years since the last
+            // fire, with the return interval estimated from the fire weather.
+            let freq = state.fire_frequency.as_slice()[i];
+            let interval = if freq > 0.0 {
+                (1.0 / freq).min(MAX_RETURN_INTERVAL_YEARS)
+            } else {
+                MAX_RETURN_INTERVAL_YEARS
+            };
+            (span as f32, interval.max(span as f32))
+        };
+        state.burn_scar_age.as_mut_slice()[i] = age;
+        state.fire_return_interval.as_mut_slice()[i] = interval;
+    }
+}
+
+/// Per-tile fuel (0..1, from the blended biome leaf area) and dryness
+/// (0..1, warm and low relative rainfall). Water and glaciers carry no fuel.
+fn fire_weather(state: &WorldState, params: &GenerationParams) -> (Vec<f32>, Vec<f32>) {
+    let sea_level = params.base.sea_level;
+    let mean_rain = erosion_hydrology::mean_land_rainfall(state, sea_level);
+    let inv_mean = if mean_rain > 0.0 {
+        1.0 / mean_rain
+    } else {
+        0.0
+    };
+
+    let n = state.width * state.height;
+    let mut fuel = vec![0.0f32; n];
+    let mut dryness = vec![0.0f32; n];
+    for (x, y) in state.elevation.iter_coords() {
+        let idx = state.elevation.idx(x, y);
+        let land = *state.elevation.get(x, y) > sea_level && *state.lake_id.get(x, y) == 0;
+        if !land || *state.ice_cover.get(x, y) != IceCover::None {
+            continue;
+        }
+        let lai: f32 = state
+            .biome_blend
+            .get(x, y)
+            .iter()
+            .map(|(b, w)| state.biome_table.def(b).max_lai * w)
+            .sum();
+        fuel[idx] = (lai / FULL_FUEL_LAI).min(1.0);
+
+        let warm = ((*state.temperature.get(x, y) + 5.0) / 30.0).clamp(0.0, 1.0);
+        let rel_rain = *state.rainfall.get(x, y) * inv_mean;
+        dryness[idx] = warm * (-rel_rain).exp();
+    }
+    (fuel, dryness)
+}
+
+/// Year-by-year ignitions and cellular spread. Returns burn counts and the
+/// year of the most recent burn per tile. Only the sampled ignitions cost
+/// random draws, not every fuel tile every year.
+fn simulate_fires(
+    state: &WorldState,
+    params: &GenerationParams,
+    fuel: &[f32],
+    dryness: &[f32],
+) -> (Vec<u32>, Vec<u32>) {
+    let n = state.width * state.height;
+    let mut burns = vec![0u32; n];
+    let mut last_burn = vec![0u32; n];
+    let mut fire_stamp = vec![u32::MAX; n];
+    let mut rng = SplitMix64::new(seed_offset(params.seed, 1313));
+    let mut queue = VecDeque::new();
+    let mut fire_id = 0u32;
+
+    let max_wind = state
+        .wind_u
+        .as_slice()
+        .iter()
+        .zip(state.wind_v.as_slice())
+        .map(|(u, v)| (u * u + v * v).sqrt())
+        .fold(0.0f32, f32::max)
+        .max(1e-6);
+
+    // Each year's ignition count is Poisson with the summed tile chances, and
+    // each ignition lands on a tile in proportion to its own chance.
+    let mut cumulative = Vec::with_capacity(n);
+    let mut total = 0.0f64;
+    for &freq in state.fire_frequency.as_slice() {
+        total += f64::from(freq.max(0.0));
+        cumulative.push(total);
+    }
+
+    for year in 1..=params.fire.simulated_years {
+        let ignitions = rng.poisson(total as f32);
+        for _ in 0..ignitions {
+            let r = f64::from(rng.next_f32()) * total;
+            let start = cumulative.partition_point(|&c| c <= r).min(n - 1);
+            if last_burn[start] == year {
+                continue;
+            }
+
+            fire_id += 1;
+            fire_stamp[start] = fire_id;
+            queue.push_back(start);
+            while let Some(idx) = queue.pop_front() {
+                burns[idx] += 1;
+                last_burn[idx] = year;
+
+                let x = idx % state.width;
+                let y = idx / state.width;
+                let wu = *state.wind_u.get(x, y) / max_wind;
+                let wv = *state.wind_v.get(x, y) / max_wind;
+                let h = *state.elevation.get(x, y);
+                for (dx, dy) in DIRS_8 {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if !state.elevation.in_bounds(nx, ny) {
+                        continue;
+                    }
+                    let nidx = state.elevation.idx(nx as usize, ny as usize);
+                    if fire_stamp[nidx] == fire_id || fuel[nidx] <= 0.0 {
+                        continue;
+                    }
+
+                    let len = ((dx * dx + dy * dy) as f32).sqrt();
+                    let downwind = (dx as f32 * wu + dy as f32 * wv) / len;
+                    let wind_f = (1.0 + params.fire.wind_influence * downwind).max(0.1);
+                    // Flames climb: uphill spread is faster than downhill.
+                    let rise = (*state.elevation.get(nx as usize, ny as usize) - h) * 100.0;
+                    let slope_f = (1.0 + rise).clamp(0.5, 2.0);
+                    let chance = params.fire.spread_rate
+                        * fuel[nidx]
+                        * dryness[nidx].sqrt()
+                        * wind_f
+                        * slope_f;
+                    if rng.next_f32() < chance.min(MAX_SPREAD_CHANCE) {
+                        fire_stamp[nidx] = fire_id;
+                        queue.push_back(nidx);
+                    }
+                }
+            }
+        }
+    }
+
+    (burns, last_burn)
+}
+
+/// Share of a woody biome's smoothing vote kept on a tile with the given fire
+/// return interval: frequent fire holds forest back from open land.
+pub fn woody_vote_factor(return_interval: f32) -> f32 {
+    const FIRE_MAINTAINED_YEARS: f32 = 40.0;
+    if return_interval <= 0.0 {
+        1.0
+    } else {
+        (return_interval / FIRE_MAINTAINED_YEARS).clamp(0.25, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::BiomeBlend;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn ignitions_match_the_summed_fire_frequency() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.fire.spread_rate = 0.0;
+        let mut state = WorldState::new(params.clone());
+        let forest = state
+            .biome_table
+            .find("Temperate Forest")
+            .expect("built-in biome");
+        state.elevation.fill(0.6);
+        for y in 0..state.height {
+            for x in 0..32 {
+                *state.elevation.get_mut(x, y) = 0.4;
+            }
+        }
+        state.biome_blend.fill(BiomeBlend::single(forest));
+        state.temperature.fill(25.0);
+        state.rainfall.fill(1.0);
+
+        run(&mut state, &params);
+        let (fuel, dryness) = fire_weather(&state, &params);
+        let (burns, _) = simulate_fires(&state, &params, &fuel, &dryness);
+
+        // Without spread every fire burns only the tile it started on.
+        let expected: f32 = state.fire_frequency.as_slice().iter().sum::<f32>()
+            * params.fire.simulated_years as f32;
+        let burned = burns.iter().sum::<u32>() as f32;
+        assert!(
+            (burned - expected).abs() < 0.05 * expected,
+            "burned {burned}, expected {expected}"
+        );
+        for y in 0..state.height {
+            assert_eq!(burns[state.elevation.idx(0, y)], 0);
+        }
+    }
+}
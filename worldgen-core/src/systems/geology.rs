@@ -1,28 +1,78 @@
+use anyhow::Result;
+
+use crate::geology_catalogue::GeologyCatalogue;
+use crate::grid::Grid2D;
 use crate::params::GenerationParams;
 use crate::rng::hash_2d;
-use crate::state::{GeologicProvince, Mineral, RockType, StrataLayer, WorldState};
+use crate::state::{GeologicProvince, StrataLayer, VolcanoKind, WorldState};
+use crate::systems::base_fields::{fbm, seed_offset};
+use crate::systems::{caves, hazards, ore};
+
+/// Noise frequency of layer thickness and of rock choice within a layer.
+const LAYER_PATCH_SCALE: f32 = 5.0;
+
+pub fn run(state: &mut WorldState, params: &GenerationParams) -> Result<()> {
+    state.geology_catalogue = match &params.geology.catalogue {
+        Some(path) => GeologyCatalogue::load(path)?,
+        None => GeologyCatalogue::builtin(),
+    };
+    reset_mineral_grids(state);
 
-pub fn run(state: &mut WorldState, params: &GenerationParams) {
     assign_provinces(state, params);
     assign_strata_and_rock(state, params);
-    assign_minerals(state, params);
+    ore::run(state, params);
+    caves::run(state, params);
+    hazards::run(state, params);
+    Ok(())
+}
+
+/// One empty grid per catalogue mineral in each of the per-mineral maps.
+fn reset_mineral_grids(state: &mut WorldState) {
+    let (width, height) = (state.width, state.height);
+    state.mineral_masks.clear();
+    state.mineral_grade.clear();
+    state.mineral_reserve_t.clear();
+    state.ore_deposit_id.clear();
+    state.ore_deposits.clear();
+    for (mineral, _) in state.geology_catalogue.minerals() {
+        let key = state.geology_catalogue.mineral_key(mineral).to_string();
+        state
+            .mineral_masks
+            .insert(key.clone(), Grid2D::new(width, height, false));
+        state
+            .ore_deposit_id
+            .insert(key.clone(), Grid2D::new(width, height, 0));
+        state
+            .mineral_grade
+            .insert(key.clone(), Grid2D::new(width, height, 0.0));
+        state
+            .mineral_reserve_t
+            .insert(key, Grid2D::new(width, height, 0.0));
+    }
 }
 
+/// Land inside a lava field belongs to its volcano: oceanic for islands
+/// raised by hotspots from the sea floor, the volcanic arc otherwise.
 fn assign_provinces(state: &mut WorldState, params: &GenerationParams) {
     for y in 0..state.height {
         for x in 0..state.width {
             let elev = *state.elevation.get(x, y);
             let slope = local_slope(state, x, y);
-            let noise = hash_2d(params.seed ^ 0xABCDEF01, x as i32, y as i32);
+            let volcano = match *state.volcano_id.get(x, y) {
+                0 => None,
+                id => state.volcanoes.get(id as usize - 1),
+            };
 
             let province = if *state.ocean_mask.get(x, y) {
                 GeologicProvince::Oceanic
-            } else if slope > 0.03 && elev > params.base.sea_level + 0.2 {
-                if noise > 0.72 {
-                    GeologicProvince::VolcanicArc
+            } else if let Some(v) = volcano {
+                if v.kind == VolcanoKind::Shield && v.oceanic {
+                    GeologicProvince::Oceanic
                 } else {
-                    GeologicProvince::Orogen
+                    GeologicProvince::VolcanicArc
                 }
+            } else if slope > 0.03 && elev > params.base.sea_level + 0.2 {
+                GeologicProvince::Orogen
             } else if elev < params.base.sea_level + 0.07 {
                 GeologicProvince::Basin
             } else {
@@ -34,150 +84,102 @@ fn assign_provinces(state: &mut WorldState, params: &GenerationParams) {
     }
 }
 
+/// Layer `l` of every column draws its thickness and rock from smooth noise
+/// fields shared by all tiles, so layer boundaries and rock bodies continue
+/// across neighbours and only break at province boundaries and faults.
+/// Rocks are picked by the catalogue's province weights among those allowed
+/// at the layer's depth. Lava fields cap the stack with a flow of the
+/// volcano's lava rock, laid after faulting. The surface rock is the top of
+/// the resulting stack.
 fn assign_strata_and_rock(state: &mut WorldState, params: &GenerationParams) {
     let layers = params.geology.strata_layers.max(3);
+    let fields: Vec<(Vec<f32>, Vec<f32>)> = (0..layers)
+        .map(|l| {
+            let l = u64::from(l);
+            (
+                smooth_uniform(state, seed_offset(params.seed, 2020 + l), LAYER_PATCH_SCALE),
+                smooth_uniform(state, seed_offset(params.seed, 2120 + l), LAYER_PATCH_SCALE),
+            )
+        })
+        .collect();
+
     for y in 0..state.height {
         for x in 0..state.width {
+            let idx = state.elevation.idx(x, y);
             let province = *state.geologic_province.get(x, y);
-            let base_rock = match province {
-                GeologicProvince::Oceanic => RockType::Basalt,
-                GeologicProvince::Craton => RockType::Granite,
-                GeologicProvince::Orogen => RockType::Schist,
-                GeologicProvince::Basin => RockType::Shale,
-                GeologicProvince::VolcanicArc => RockType::Rhyolite,
-            };
-            *state.rock_type.get_mut(x, y) = base_rock;
 
             let mut stack = Vec::with_capacity(usize::from(layers));
-            for l in 0..layers {
-                let n = hash_2d(params.seed ^ 0x55115511 ^ u64::from(l), x as i32, y as i32);
-                let thickness = 8.0 + n * 28.0;
-                let rock = layer_rock(province, l, n);
+            let mut top_m = 0.0;
+            for (thick, pick) in &fields {
+                let thickness = 8.0 + thick[idx] * 28.0;
+                let rock = state
+                    .geology_catalogue
+                    .pick_rock(province, top_m, pick[idx]);
                 stack.push(StrataLayer { rock, thickness });
+                top_m += thickness;
             }
 
-            if params.geology.fault_strength > 0.0 {
-                let fault = hash_2d(params.seed ^ 0xDEADBEEF, x as i32, y as i32);
-                if fault > 1.0 - params.geology.fault_strength * 0.12 && stack.len() > 1 {
-                    stack.swap(0, 1);
+            offset_column(&mut stack, *state.fault_uplift_m.get(x, y));
+            let lava_m = *state.lava_thickness_m.get(x, y);
+            let id = *state.volcano_id.get(x, y);
+            let volcano = (id as usize)
+                .checked_sub(1)
+                .and_then(|i| state.volcanoes.get(i));
+            if let (true, Some(v)) = (lava_m > 0.0, volcano) {
+                let n = hash_2d(seed_offset(params.seed, 2410), id as i32, 0);
+                if let Some(rock) = state.geology_catalogue.lava_rock(v.kind, n) {
+                    stack.insert(
+                        0,
+                        StrataLayer {
+                            rock,
+                            thickness: lava_m,
+                        },
+                    );
                 }
             }
-
+            *state.rock_type.get_mut(x, y) = stack[0].rock;
             *state.strata.get_mut(x, y) = stack;
         }
     }
 }
 
-fn assign_minerals(state: &mut WorldState, params: &GenerationParams) {
-    for mask in state.mineral_masks.values_mut() {
-        mask.fill(false);
-    }
-
-    for y in 0..state.height {
-        for x in 0..state.width {
-            let province = *state.geologic_province.get(x, y);
-            let rock = *state.rock_type.get(x, y);
-
-            for mineral in Mineral::ALL {
-                let key = format!("{:?}", mineral).to_lowercase();
-                let score = mineral_score(params.seed, x, y, mineral, rock, province);
-                let threshold = match mineral {
-                    Mineral::Iron => 0.72,
-                    Mineral::Copper => 0.78,
-                    Mineral::Gold => 0.9,
-                    Mineral::Tin => 0.82,
-                    Mineral::Coal => 0.74,
-                    Mineral::Gem => 0.94,
-                } - params.geology.ore_richness * 0.25;
-
-                if score > threshold {
-                    if let Some(mask) = state.mineral_masks.get_mut(&key) {
-                        *mask.get_mut(x, y) = true;
-                    }
-                }
-            }
+/// Strips a raised block's uplift from the top of the column and extends the
+/// bottom layer by as much, so a boundary at depth d on the dropped side of a
+/// fault lies at d minus the throw on the raised side. The bottom layer is
+/// never stripped away.
+fn offset_column(stack: &mut Vec<StrataLayer>, uplift_m: f32) {
+    let mut left = uplift_m;
+    while left > 0.0 && stack.len() > 1 {
+        if stack[0].thickness <= left {
+            left -= stack.remove(0).thickness;
+        } else {
+            stack[0].thickness -= left;
+            left = 0.0;
         }
     }
-}
-
-fn layer_rock(province: GeologicProvince, layer: u8, n: f32) -> RockType {
-    match province {
-        GeologicProvince::Oceanic => {
-            if layer == 0 || n > 0.6 {
-                RockType::Basalt
-            } else {
-                RockType::Gabbro
-            }
-        }
-        GeologicProvince::Craton => {
-            if layer.is_multiple_of(3) {
-                RockType::Granite
-            } else if n > 0.66 {
-                RockType::Gneiss
-            } else {
-                RockType::Sandstone
-            }
-        }
-        GeologicProvince::Orogen => {
-            if n > 0.52 {
-                RockType::Schist
-            } else {
-                RockType::Gneiss
-            }
-        }
-        GeologicProvince::Basin => {
-            if n > 0.6 {
-                RockType::Limestone
-            } else if n > 0.25 {
-                RockType::Shale
-            } else {
-                RockType::Sandstone
-            }
-        }
-        GeologicProvince::VolcanicArc => {
-            if n > 0.55 {
-                RockType::Rhyolite
-            } else {
-                RockType::Basalt
-            }
-        }
+    if let Some(bottom) = stack.last_mut() {
+        bottom.thickness += uplift_m - left;
     }
 }
 
-fn mineral_score(
-    seed: u64,
-    x: usize,
-    y: usize,
-    mineral: Mineral,
-    rock: RockType,
-    province: GeologicProvince,
-) -> f32 {
-    let n = hash_2d(
-        seed ^ (u64::from(mineral.as_u8()) << 32),
-        x as i32,
-        y as i32,
-    );
-    let host_bonus = match (mineral, rock) {
-        (Mineral::Iron, RockType::Basalt | RockType::Gabbro) => 0.16,
-        (Mineral::Copper, RockType::Rhyolite | RockType::Basalt) => 0.18,
-        (Mineral::Gold, RockType::Schist | RockType::Gneiss) => 0.17,
-        (Mineral::Tin, RockType::Granite) => 0.14,
-        (Mineral::Coal, RockType::Shale | RockType::Sandstone) => 0.2,
-        (Mineral::Gem, RockType::Schist | RockType::Gneiss | RockType::Rhyolite) => 0.12,
-        _ => 0.0,
-    };
-
-    let province_bonus = match (mineral, province) {
-        (Mineral::Iron, GeologicProvince::Oceanic | GeologicProvince::VolcanicArc) => 0.12,
-        (Mineral::Copper, GeologicProvince::VolcanicArc) => 0.13,
-        (Mineral::Gold, GeologicProvince::Orogen) => 0.14,
-        (Mineral::Tin, GeologicProvince::Craton) => 0.1,
-        (Mineral::Coal, GeologicProvince::Basin) => 0.16,
-        (Mineral::Gem, GeologicProvince::Orogen) => 0.1,
-        _ => 0.0,
-    };
-
-    (n + host_bonus + province_bonus).clamp(0.0, 1.0)
+/// fBm over the map, rank-normalized so values are uniform on 0..1 and
+/// weighted picks keep their proportions.
+pub(crate) fn smooth_uniform(state: &WorldState, seed: u64, scale: f32) -> Vec<f32> {
+    let width = state.width as f32;
+    let height = state.height as f32;
+    let raw: Vec<f32> = state
+        .elevation
+        .iter_coords()
+        .map(|(x, y)| fbm(seed, x as f32 / width, y as f32 / height, 4, scale))
+        .collect();
+    let mut order: Vec<usize> = (0..raw.len()).collect();
+    order.sort_by(|&a, &b| raw[a].total_cmp(&raw[b]).then(a.cmp(&b)));
+    let mut out = vec![0.0; raw.len()];
+    let denom = (raw.len().max(2) - 1) as f32;
+    for (rank, &i) in order.iter().enumerate() {
+        out[i] = rank as f32 / denom;
+    }
+    out
 }
 
 fn local_slope(state: &WorldState, x: usize, y: usize) -> f32 {
@@ -200,3 +202,27 @@ fn local_slope(state: &WorldState, x: usize, y: usize) -> f32 {
 
     max_diff
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::RockType;
+
+    #[test]
+    fn uplift_raises_strata_boundaries_by_the_throw() {
+        let layer = |thickness| StrataLayer {
+            rock: RockType(0),
+            thickness,
+        };
+        let mut stack = vec![layer(20.0), layer(30.0), layer(40.0)];
+        offset_column(&mut stack, 25.0);
+        let thickness: Vec<f32> = stack.iter().map(|l| l.thickness).collect();
+        // The boundary at 50 m on the dropped side sits at 25 m.
+        assert_eq!(thickness, vec![25.0, 65.0]);
+
+        let mut stack = vec![layer(20.0), layer(30.0)];
+        offset_column(&mut stack, 500.0);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack[0].thickness, 50.0);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::grid::Grid2D;
+use crate::params::GenerationParams;
+use crate::state::{
+    FaultKind, FluvialFeature, GeologicProvince, Hazard, RiverClass, VolcanoActivity, WorldState,
+};
+use crate::systems::base_fields::{smoothstep, ELEVATION_SPAN_M};
+use crate::systems::biomes::slope_m;
+use crate::systems::erosion_hydrology;
+
+/// Drops gentler than this share of `landslide_drop_m` never fail.
+const LANDSLIDE_MIN_SHARE: f32 = 0.1;
+/// Rainfall, as a multiple of the land mean, that saturates slopes.
+const SATURATING_RAIN: f32 = 2.0;
+
+/// Background seismicity of each province, before any fault is counted.
+fn province_quake(province: GeologicProvince) -> f32 {
+    match province {
+        GeologicProvince::Craton => 0.03,
+        GeologicProvince::Basin => 0.08,
+        GeologicProvince::Oceanic => 0.15,
+        GeologicProvince::Orogen => 0.35,
+        GeologicProvince::VolcanicArc => 0.45,
+    }
+}
+
+/// Shaking on the trace of a fault, relative to a boundary thrust.
+fn fault_quake(kind: FaultKind) -> f32 {
+    match kind {
+        FaultKind::Reverse => 1.0,
+        FaultKind::StrikeSlip => 0.85,
+        FaultKind::Normal => 0.6,
+    }
+}
+
+fn eruption_weight(activity: VolcanoActivity) -> f32 {
+    match activity {
+        VolcanoActivity::Active => 1.0,
+        VolcanoActivity::Dormant => 0.5,
+        VolcanoActivity::Extinct => 0.05,
+    }
+}
+
+/// Flood-prone share of each fluvial landform before discharge is counted.
+fn landform_flood(feature: FluvialFeature) -> f32 {
+    match feature {
+        FluvialFeature::None => 0.0,
+        FluvialFeature::Floodplain => 0.6,
+        FluvialFeature::Estuary => 0.7,
+        FluvialFeature::MeanderBelt => 0.8,
+        FluvialFeature::Delta => 0.9,
+    }
+}
+
+/// Fills the hazard layers from the terrain, drainage and structure built by
+/// steps 1 to 5. Every layer is 0..1 on land and 0 over the sea and lakes.
+///
+/// Earthquake risk combines the background of the province with shaking
+/// that decays away from the nearest fault. Volcanic risk falls off over
+/// `volcanic_reach_radii` cone radii from each vent, weighted by activity.
+/// Landslide risk is steepness times wetness. Flood risk follows floodplains,
+/// meander belts, deltas, estuaries and channels, scaled up with discharge.
+/// Tsunami exposure covers land that is both low and near an open coast.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let hp = &params.hazards;
+    let sea_level = params.base.sea_level;
+    let (width, height) = (state.width, state.height);
+    let mut layers: Vec<Grid2D<f32>> = Hazard::ALL
+        .iter()
+        .map(|_| Grid2D::new(width, height, 0.0))
+        .collect();
+    let volcanic = volcanic_risk(state, params);
+    let flood_scale = (1.0 + params.hydro.major_threshold).ln();
+    let mean_rain = erosion_hydrology::mean_land_rainfall(state, sea_level);
+    let inv_mean = if mean_rain > 0.0 {
+        1.0 / mean_rain
+    } else {
+        0.0
+    };
+
+    for (x, y) in state.elevation.iter_coords() {
+        if *state.ocean_mask.get(x, y) || *state.lake_id.get(x, y) != 0 {
+            continue;
+        }
+        let idx = state.elevation.idx(x, y);
+
+        let fault = (*state.fault_id.get(x, y) as usize)
+            .checked_sub(1)
+            .and_then(|i| state.faults.get(i))
+            .map_or(0.0, |f| {
+                let d = *state.fault_distance.get(x, y);
+                fault_quake(f.kind) * (-d / hp.quake_reach_tiles.max(0.1)).exp()
+            });
+        let background = province_quake(*state.geologic_province.get(x, y));
+        let quake = 1.0 - (1.0 - fault) * (1.0 - background);
+
+        let drop = slope_m(state, x, y, sea_level) / hp.landslide_drop_m.max(1.0);
+        let steep = smoothstep(
+            ((drop - LANDSLIDE_MIN_SHARE) / (1.0 - LANDSLIDE_MIN_SHARE)).clamp(0.0, 1.0),
+        );
+        let wet = (*state.rainfall.get(x, y) * inv_mean / SATURATING_RAIN).min(1.0);
+        let landslide = steep * (0.3 + 0.7 * wet);
+
+        let mut landform = landform_flood(*state.fluvial_feature.get(x, y));
+        if *state.river_class.get(x, y) != RiverClass::None {
+            landform = landform.max(0.5);
+        }
+        let q = (1.0 + state.discharge.get(x, y).max(0.0)).ln() / flood_scale;
+        let flood = landform * (0.4 + 0.6 * q.clamp(0.0, 1.0));
+
+        let coast = *state.coast_distance.get(x, y);
+        let height_m = (*state.elevation.get(x, y) - sea_level) * ELEVATION_SPAN_M;
+        let tsunami = if coast <= hp.tsunami_reach_tiles && height_m < hp.tsunami_height_m {
+            let low = 1.0 - height_m.max(0.0) / hp.tsunami_height_m.max(1.0);
+            let near = 1.0 - (coast.saturating_sub(1)) as f32 / hp.tsunami_reach_tiles as f32;
+            low * near * (0.3 + 0.7 * *state.wave_exposure.get(x, y))
+        } else {
+            0.0
+        };
+
+        for (hazard, v) in [
+            (Hazard::Earthquake, quake),
+            (Hazard::Volcanic, volcanic[idx]),
+            (Hazard::Landslide, landslide),
+            (Hazard::Flood, flood),
+            (Hazard::Tsunami, tsunami),
+        ] {
+            layers[hazard.index()].as_mut_slice()[idx] = v.clamp(0.0, 1.0);
+        }
+    }
+    state.hazards = layers;
+}
+
+/// Highest threat from any volcano, tapering to 0 at the edge of its reach.
+fn volcanic_risk(state: &WorldState, params: &GenerationParams) -> Vec<f32> {
+    let mut risk = vec![0.0f32; state.width * state.height];
+    for v in &state.volcanoes {
+        let reach = (v.radius_tiles * params.hazards.volcanic_reach_radii).max(1.0);
+        let weight = eruption_weight(v.activity);
+        let r = reach.ceil() as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (nx, ny) = (v.x as isize + dx, v.y as isize + dy);
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let d = (dx as f32).hypot(dy as f32);
+                if d >= reach {
+                    continue;
+                }
+                let idx = state.elevation.idx(nx as usize, ny as usize);
+                risk[idx] = risk[idx].max(weight * smoothstep(1.0 - d / reach));
+            }
+        }
+    }
+    risk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Step;
+    use crate::{run_step, MapSizePreset};
+
+    #[test]
+    fn every_layer_is_zero_over_water() {
+        let params = GenerationParams {
+            seed: 12,
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        for step in Step::ALL.into_iter().take_while(|&s| s <= Step::Geology) {
+            run_step(&mut state, step, &params).expect("step");
+        }
+
+        let water: Vec<(usize, usize)> = state
+            .elevation
+            .iter_coords()
+            .filter(|&(x, y)| *state.ocean_mask.get(x, y) || *state.lake_id.get(x, y) != 0)
+            .collect();
+        assert!(!water.is_empty());
+        for hazard in Hazard::ALL {
+            let layer = state.hazard(hazard);
+            for &(x, y) in &water {
+                assert_eq!(*layer.get(x, y), 0.0, "{} over water", hazard.key());
+            }
+        }
+    }
+
+    #[test]
+    fn tsunami_exposure_falls_off_inland() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        // Low, even ground whose coast lies west of column 0.
+        state
+            .elevation
+            .fill(params.base.sea_level + 5.0 / ELEVATION_SPAN_M);
+        state.wave_exposure.fill(1.0);
+        for (x, y) in state.elevation.iter_coords() {
+            *state.coast_distance.get_mut(x, y) = x as u32 + 1;
+        }
+
+        run(&mut state, &params);
+
+        let tsunami = state.hazard(Hazard::Tsunami);
+        let reach = params.hazards.tsunami_reach_tiles as usize;
+        let y = state.height / 2;
+        assert!(*tsunami.get(0, y) > 0.0);
+        for x in 1..reach {
+            assert!(
+                *tsunami.get(x, y) < *tsunami.get(x - 1, y),
+                "no falloff at {x}"
+            );
+        }
+        for x in reach..state.width {
+            assert_eq!(*tsunami.get(x, y), 0.0);
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use std::cmp::Ordering;
 use std::collections::VecDeque;
 
 use crate::params::GenerationParams;
-use crate::state::{RiverClass, WorldState};
+use crate::state::{FluvialFeature, RiverClass, RiverNavigation, WorldState};
+use crate::systems::base_fields::ELEVATION_SPAN_M;
+use crate::systems::{bathymetry, erosion_hydrology};
 
-const DIRS_8: [(isize, isize); 8] = [
+/// Neighbour offsets in `flow_dir` order.
+pub(crate) const DIRS_8: [(isize, isize); 8] = [
     (1, 0),
     (1, 1),
     (0, 1),
@@ -16,32 +20,46 @@ const DIRS_8: [(isize, isize); 8] = [
 
 pub fn run(state: &mut WorldState, params: &GenerationParams) {
     mark_ocean_component(state, params.base.sea_level);
+    bathymetry::classify_ocean_zones(state, params);
     state.river_class.fill(RiverClass::None);
     state.lake_id.fill(0);
+    identify_lakes(state, params.base.sea_level);
+
+    // Re-route discharge now that lake surfaces are known so they evaporate.
+    erosion_hydrology::compute_accumulation(state, params);
 
     for y in 0..state.height {
         for x in 0..state.width {
             if *state.ocean_mask.get(x, y) {
                 continue;
             }
-            let q = *state.discharge.get(x, y);
-            let class = if q >= params.hydro.major_threshold {
-                RiverClass::Major
-            } else if q >= params.hydro.perennial_threshold {
-                RiverClass::Perennial
-            } else if q >= params.hydro.ephemeral_threshold {
-                RiverClass::Ephemeral
-            } else {
-                RiverClass::None
-            };
-            *state.river_class.get_mut(x, y) = class;
+            *state.river_class.get_mut(x, y) = river_class(*state.discharge.get(x, y), params);
         }
     }
 
-    identify_lakes(state, params.base.sea_level);
+    let sediment = route_sediment(state);
+    state.fluvial_feature.fill(FluvialFeature::None);
+    mark_floodplains(state, params);
+    mark_river_mouths(state, params, &sediment);
+    classify_navigation(state, params);
+}
+
+/// River class carried by `discharge` on a land tile.
+pub(crate) fn river_class(discharge: f32, params: &GenerationParams) -> RiverClass {
+    if discharge >= params.hydro.major_threshold {
+        RiverClass::Major
+    } else if discharge >= params.hydro.perennial_threshold {
+        RiverClass::Perennial
+    } else if discharge >= params.hydro.ephemeral_threshold {
+        RiverClass::Ephemeral
+    } else {
+        RiverClass::None
+    }
 }
 
-fn mark_ocean_component(state: &mut WorldState, sea_level: f32) {
+/// Flags the tiles at or below `sea_level` connected to the map edge. Lower
+/// ground enclosed by land is an inland basin, not sea.
+pub(crate) fn mark_ocean_component(state: &mut WorldState, sea_level: f32) {
     state.ocean_mask.fill(false);
     let mut q = VecDeque::new();
 
@@ -191,3 +209,437 @@ fn local_relief(state: &WorldState, x: usize, y: usize) -> f32 {
     }
     max_h - min_h
 }
+
+/// The tile `flow_dir` drains into, or `None` at a sink or the map edge.
+pub(crate) fn downstream(state: &WorldState, x: usize, y: usize) -> Option<(usize, usize)> {
+    let dir = *state.flow_dir.get(x, y);
+    if dir == 255 {
+        return None;
+    }
+    let (dx, dy) = DIRS_8[usize::from(dir)];
+    let nx = x as isize + dx;
+    let ny = y as isize + dy;
+    if state.elevation.in_bounds(nx, ny) {
+        Some((nx as usize, ny as usize))
+    } else {
+        None
+    }
+}
+
+/// Sediment supply routed down `flow_dir`: every land tile yields material in
+/// proportion to its channel slope, so steep catchments deliver heavy loads.
+fn route_sediment(state: &WorldState) -> Vec<f32> {
+    let mut sediment = vec![0.0f32; state.width * state.height];
+    let mut order: Vec<(usize, usize, f32)> = Vec::with_capacity(state.width * state.height);
+    for (x, y) in state.elevation.iter_coords() {
+        order.push((x, y, *state.elevation.get(x, y)));
+    }
+    order.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+
+    for (x, y, h) in order {
+        if *state.ocean_mask.get(x, y) {
+            continue;
+        }
+        let Some((nx, ny)) = downstream(state, x, y) else {
+            continue;
+        };
+        let idx = state.elevation.idx(x, y);
+        let slope = (h - *state.elevation.get(nx, ny)).max(0.0);
+        sediment[idx] += slope * 100.0;
+        let nidx = state.elevation.idx(nx, ny);
+        sediment[nidx] += sediment[idx];
+    }
+    sediment
+}
+
+/// Mean drop per tile over the next few steps downstream.
+fn reach_gradient(state: &WorldState, x: usize, y: usize, steps: usize) -> f32 {
+    let start = *state.elevation.get(x, y);
+    let (mut cx, mut cy) = (x, y);
+    let mut walked = 0usize;
+    while walked < steps {
+        match downstream(state, cx, cy) {
+            Some((nx, ny)) => {
+                cx = nx;
+                cy = ny;
+                walked += 1;
+            }
+            None => break,
+        }
+    }
+    if walked == 0 {
+        return 0.0;
+    }
+    (start - *state.elevation.get(cx, cy)).max(0.0) / walked as f32
+}
+
+/// Low-gradient reaches of perennial rivers get a meander belt along the
+/// channel and a wider floodplain over the flat ground either side of it.
+fn mark_floodplains(state: &mut WorldState, params: &GenerationParams) {
+    for y in 0..state.height {
+        for x in 0..state.width {
+            if !matches!(
+                *state.river_class.get(x, y),
+                RiverClass::Perennial | RiverClass::Major
+            ) {
+                continue;
+            }
+            if reach_gradient(state, x, y, 4) > params.hydro.floodplain_gradient {
+                continue;
+            }
+
+            let channel_h = *state.elevation.get(x, y);
+            let q = *state.discharge.get(x, y);
+            let plain_r = (1.0 + q.sqrt() * 0.08).min(6.0) as isize;
+            let belt_r = (plain_r / 2).max(1);
+            for dy in -plain_r..=plain_r {
+                for dx in -plain_r..=plain_r {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if !state.elevation.in_bounds(nx, ny) {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    if *state.ocean_mask.get(nx, ny) || *state.lake_id.get(nx, ny) > 0 {
+                        continue;
+                    }
+                    if *state.elevation.get(nx, ny) - channel_h > 0.006 {
+                        continue;
+                    }
+                    let d2 = dx * dx + dy * dy;
+                    let feature = if d2 <= belt_r * belt_r {
+                        FluvialFeature::MeanderBelt
+                    } else if d2 <= plain_r * plain_r {
+                        FluvialFeature::Floodplain
+                    } else {
+                        continue;
+                    };
+                    let cell = state.fluvial_feature.get_mut(nx, ny);
+                    if feature.as_u8() > cell.as_u8() {
+                        *cell = feature;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rivers reaching the ocean build a delta when their sediment load outpaces
+/// the depth of the receiving shelf; otherwise the mouth drowns into an estuary.
+fn mark_river_mouths(state: &mut WorldState, params: &GenerationParams, sediment: &[f32]) {
+    let sea_level = params.base.sea_level;
+    let mut mouths = Vec::new();
+    for y in 0..state.height {
+        for x in 0..state.width {
+            if !matches!(
+                *state.river_class.get(x, y),
+                RiverClass::Perennial | RiverClass::Major
+            ) {
+                continue;
+            }
+            if let Some((nx, ny)) = downstream(state, x, y) {
+                if *state.ocean_mask.get(nx, ny) {
+                    mouths.push((x, y));
+                }
+            }
+        }
+    }
+
+    for (x, y) in mouths {
+        let q = *state.discharge.get(x, y);
+        let load = sediment[state.elevation.idx(x, y)];
+        let shelf_depth = mean_ocean_depth(state, x, y, 5, sea_level);
+        let score = load / (1.0 + q.sqrt()) / (shelf_depth * 100.0 + 0.1);
+        let feature = if score >= params.hydro.delta_threshold {
+            FluvialFeature::Delta
+        } else {
+            FluvialFeature::Estuary
+        };
+
+        let r = (2.0 + q.sqrt() * 0.06).min(8.0) as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy > r * r {
+                    continue;
+                }
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                let h = *state.elevation.get(nx, ny);
+                let coastal = if *state.ocean_mask.get(nx, ny) {
+                    sea_level - h < 0.02
+                } else {
+                    h - sea_level < 0.02 && *state.lake_id.get(nx, ny) == 0
+                };
+                if coastal {
+                    *state.fluvial_feature.get_mut(nx, ny) = feature;
+                }
+            }
+        }
+    }
+}
+
+/// Derives travel attributes for every river tile from the drop along
+/// `flow_dir` and the discharge carried through it. Outside waterfalls and
+/// rapids the discharge thresholds are contiguous: every river is fordable,
+/// boatable or open to ships, and `None` marks only tiles without a river.
+pub(crate) fn classify_navigation(state: &mut WorldState, params: &GenerationParams) {
+    state.river_navigation.fill(RiverNavigation::None);
+    for y in 0..state.height {
+        for x in 0..state.width {
+            if *state.river_class.get(x, y) == RiverClass::None {
+                continue;
+            }
+            let drop_m = match downstream(state, x, y) {
+                Some((nx, ny)) => {
+                    (*state.elevation.get(x, y) - *state.elevation.get(nx, ny)).max(0.0)
+                        * ELEVATION_SPAN_M
+                }
+                None => 0.0,
+            };
+            let gradient_m = reach_gradient(state, x, y, 4) * ELEVATION_SPAN_M;
+            let q = *state.discharge.get(x, y);
+
+            // A waterfall is a knickpoint: a single step far steeper than its reach.
+            let nav = if drop_m >= params.hydro.waterfall_drop_m && drop_m >= gradient_m * 2.0 {
+                RiverNavigation::Waterfall
+            } else if gradient_m >= params.hydro.rapids_gradient_m {
+                RiverNavigation::Rapids
+            } else if q >= params.hydro.ship_min_discharge {
+                RiverNavigation::Ships
+            } else if q >= params.hydro.small_boat_min_discharge {
+                RiverNavigation::SmallBoats
+            } else {
+                RiverNavigation::Ford
+            };
+            *state.river_navigation.get_mut(x, y) = nav;
+        }
+    }
+}
+
+/// A run of river tiles along `flow_dir` sharing one class and navigation
+/// attribute. Reaches break where either changes and at confluences.
+#[derive(Clone, Debug)]
+pub struct RiverReach {
+    pub class: RiverClass,
+    pub navigation: RiverNavigation,
+    /// Tile coordinates from upstream to downstream. The last point is the
+    /// first tile of the next reach, or the tile the river drains into.
+    pub points: Vec<[f32; 2]>,
+    /// Discharge at the downstream end of the reach.
+    pub discharge: f32,
+}
+
+/// Splits the river network into reaches for vector export.
+pub fn river_reaches(state: &WorldState) -> Vec<RiverReach> {
+    let is_river = |x: usize, y: usize| {
+        *state.river_class.get(x, y) != RiverClass::None && !*state.ocean_mask.get(x, y)
+    };
+    let attrs = |x: usize, y: usize| {
+        (
+            *state.river_class.get(x, y),
+            *state.river_navigation.get(x, y),
+        )
+    };
+
+    let mut inflows = vec![0u8; state.width * state.height];
+    let mut upstream = vec![None; state.width * state.height];
+    for (x, y) in state.elevation.iter_coords() {
+        if !is_river(x, y) {
+            continue;
+        }
+        if let Some((nx, ny)) = downstream(state, x, y).filter(|&(nx, ny)| is_river(nx, ny)) {
+            let nidx = state.elevation.idx(nx, ny);
+            inflows[nidx] = inflows[nidx].saturating_add(1);
+            upstream[nidx] = Some((x, y));
+        }
+    }
+    let is_head = |x: usize, y: usize| {
+        let idx = state.elevation.idx(x, y);
+        inflows[idx] != 1 || upstream[idx].is_some_and(|(ux, uy)| attrs(ux, uy) != attrs(x, y))
+    };
+
+    let mut reaches = Vec::new();
+    for (x, y) in state.elevation.iter_coords() {
+        if !is_river(x, y) || !is_head(x, y) {
+            continue;
+        }
+        let (class, navigation) = attrs(x, y);
+        let mut points = vec![[x as f32, y as f32]];
+        let (mut cx, mut cy) = (x, y);
+        while let Some((nx, ny)) = downstream(state, cx, cy) {
+            points.push([nx as f32, ny as f32]);
+            if !is_river(nx, ny) || is_head(nx, ny) {
+                break;
+            }
+            (cx, cy) = (nx, ny);
+        }
+        // A lone tile draining off the map edge is no line.
+        if points.len() < 2 {
+            continue;
+        }
+        reaches.push(RiverReach {
+            class,
+            navigation,
+            points,
+            discharge: *state.discharge.get(cx, cy),
+        });
+    }
+    reaches
+}
+
+fn mean_ocean_depth(state: &WorldState, x: usize, y: usize, r: isize, sea_level: f32) -> f32 {
+    let mut sum = 0.0f32;
+    let mut count = 0usize;
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !state.elevation.in_bounds(nx, ny) {
+                continue;
+            }
+            let (nx, ny) = (nx as usize, ny as usize);
+            if *state.ocean_mask.get(nx, ny) {
+                sum += (sea_level - *state.elevation.get(nx, ny)).max(0.0);
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+pub fn fluvial_color(feature: FluvialFeature) -> [u8; 3] {
+    match feature {
+        FluvialFeature::None => [0, 0, 0],
+        FluvialFeature::Floodplain => [120, 160, 90],
+        FluvialFeature::MeanderBelt => [80, 140, 120],
+        FluvialFeature::Delta => [176, 156, 96],
+        FluvialFeature::Estuary => [70, 130, 170],
+    }
+}
+
+pub fn navigation_color(nav: RiverNavigation) -> [u8; 3] {
+    match nav {
+        RiverNavigation::None => [0, 0, 0],
+        RiverNavigation::Ford => [200, 180, 120],
+        RiverNavigation::SmallBoats => [60, 170, 200],
+        RiverNavigation::Ships => [20, 80, 230],
+        RiverNavigation::Rapids => [240, 140, 40],
+        RiverNavigation::Waterfall => [240, 40, 60],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    /// Ground falling eastwards by `drop` per tile, drained east, with a
+    /// perennial river along the middle row.
+    fn river_on_slope(drop: f32) -> (WorldState, GenerationParams) {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        for (x, y) in state.elevation.clone().iter_coords() {
+            *state.elevation.get_mut(x, y) = 0.9 - drop * x as f32;
+        }
+        state.flow_dir.fill(0);
+        let row = state.height / 2;
+        for x in 0..state.width {
+            *state.river_class.get_mut(x, row) = RiverClass::Perennial;
+            *state.discharge.get_mut(x, row) = 400.0;
+        }
+        (state, params)
+    }
+
+    #[test]
+    fn floodplains_only_line_gentle_reaches() {
+        let (mut state, params) = river_on_slope(0.0001);
+        mark_floodplains(&mut state, &params);
+        let row = state.height / 2;
+        assert_eq!(
+            *state.fluvial_feature.get(100, row),
+            FluvialFeature::MeanderBelt
+        );
+        assert_eq!(
+            *state.fluvial_feature.get(100, row + 1),
+            FluvialFeature::MeanderBelt
+        );
+        assert_eq!(
+            *state.fluvial_feature.get(100, row + 2),
+            FluvialFeature::Floodplain
+        );
+        assert_eq!(
+            *state.fluvial_feature.get(100, row + 4),
+            FluvialFeature::None
+        );
+
+        let (mut steep, params) = river_on_slope(0.002);
+        mark_floodplains(&mut steep, &params);
+        for dy in 0..4 {
+            assert_eq!(
+                *steep.fluvial_feature.get(100, row + dy),
+                FluvialFeature::None
+            );
+        }
+    }
+
+    #[test]
+    fn navigation_classes_cover_all_discharges() {
+        let (mut state, params) = river_on_slope(0.0001);
+        let row = state.height / 2;
+        for x in 0..state.width {
+            *state.discharge.get_mut(x, row) = x as f32 * 5.0;
+        }
+        classify_navigation(&mut state, &params);
+
+        let hydro = &params.hydro;
+        for x in 0..state.width {
+            let q = *state.discharge.get(x, row);
+            let expected = if q >= hydro.ship_min_discharge {
+                RiverNavigation::Ships
+            } else if q >= hydro.small_boat_min_discharge {
+                RiverNavigation::SmallBoats
+            } else {
+                RiverNavigation::Ford
+            };
+            assert_eq!(*state.river_navigation.get(x, row), expected, "q = {q}");
+        }
+    }
+
+    #[test]
+    fn reaches_split_where_navigation_changes() {
+        let (mut state, params) = river_on_slope(0.0001);
+        let row = state.height / 2;
+        for x in 0..state.width {
+            *state.discharge.get_mut(x, row) = x as f32 * 5.0;
+        }
+        classify_navigation(&mut state, &params);
+
+        let reaches = river_reaches(&state);
+        let navigation: Vec<RiverNavigation> = reaches.iter().map(|r| r.navigation).collect();
+        assert_eq!(
+            navigation,
+            [
+                RiverNavigation::Ford,
+                RiverNavigation::SmallBoats,
+                RiverNavigation::Ships
+            ]
+        );
+        // Consecutive reaches share their junction tile.
+        for pair in reaches.windows(2) {
+            assert_eq!(pair[0].points.last(), pair[1].points.first());
+        }
+        let tiles: usize = reaches.iter().map(|r| r.points.len()).sum();
+        assert_eq!(tiles, state.width + reaches.len() - 1);
+    }
+}
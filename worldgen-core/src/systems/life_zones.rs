@@ -0,0 +1,195 @@
+use crate::params::GenerationParams;
+use crate::state::{HoldridgeBelt, HoldridgeZone, HumidityProvince, WhittakerBiome, WorldState};
+use crate::systems::erosion_hydrology;
+
+/// Holdridge's PET constant: mm of potential evapotranspiration per degree of
+/// biotemperature.
+const PET_MM_PER_BIOTEMP: f32 = 58.93;
+
+/// Fills the Whittaker and Holdridge comparison layers that are enabled in
+/// `BiomeParams`; disabled layers are cleared to `None`.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    state.whittaker_biome.fill(WhittakerBiome::None);
+    state.holdridge_zone.fill(HoldridgeZone::None);
+    if !params.biomes.whittaker && !params.biomes.holdridge {
+        return;
+    }
+
+    let sea_level = params.base.sea_level;
+    let mean_rain = erosion_hydrology::mean_land_rainfall(state, sea_level);
+    let mm_per_rain = if mean_rain > 0.0 {
+        params.biomes.mean_precip_mm / mean_rain
+    } else {
+        0.0
+    };
+    let height = state.height as f32;
+
+    for y in 0..state.height {
+        let lat_factor = ((y as f32 + 0.5) / height - 0.5).abs() * 2.0;
+        for x in 0..state.width {
+            if *state.elevation.get(x, y) <= sea_level || *state.lake_id.get(x, y) > 0 {
+                continue;
+            }
+            let temp = *state.temperature.get(x, y);
+            let precip_mm = (*state.rainfall.get(x, y) * mm_per_rain).min(8_000.0);
+
+            if params.biomes.whittaker {
+                *state.whittaker_biome.get_mut(x, y) = whittaker(temp, precip_mm);
+            }
+            if params.biomes.holdridge {
+                *state.holdridge_zone.get_mut(x, y) = holdridge(temp, precip_mm, lat_factor);
+            }
+        }
+    }
+}
+
+/// Lookup over the classic Whittaker temperature/precipitation diagram.
+pub fn whittaker(temp_c: f32, precip_mm: f32) -> WhittakerBiome {
+    if temp_c < -5.0 {
+        WhittakerBiome::Tundra
+    } else if temp_c < 3.0 {
+        if precip_mm < 250.0 {
+            WhittakerBiome::Tundra
+        } else {
+            WhittakerBiome::BorealForest
+        }
+    } else if temp_c < 20.0 {
+        // The desert margin rises with temperature as evaporation increases.
+        let dry = 200.0 + temp_c * 10.0;
+        if precip_mm < dry {
+            WhittakerBiome::TemperateGrasslandDesert
+        } else if precip_mm < dry + 500.0 {
+            WhittakerBiome::WoodlandShrubland
+        } else if precip_mm < 2_000.0 {
+            WhittakerBiome::TemperateSeasonalForest
+        } else {
+            WhittakerBiome::TemperateRainforest
+        }
+    } else if precip_mm < 500.0 {
+        WhittakerBiome::SubtropicalDesert
+    } else if precip_mm < 2_400.0 {
+        WhittakerBiome::TropicalSeasonalForestSavanna
+    } else {
+        WhittakerBiome::TropicalRainforest
+    }
+}
+
+/// Holdridge life zone from biotemperature and the PET ratio. Monthly
+/// temperatures are reconstructed from the annual mean with a seasonal swing
+/// that grows towards the poles.
+pub fn holdridge(temp_c: f32, precip_mm: f32, lat_factor: f32) -> HoldridgeZone {
+    let amplitude = 2.0 + 16.0 * lat_factor;
+    let mut sum = 0.0f32;
+    for month in 0..12 {
+        let phase = month as f32 / 12.0 * std::f32::consts::TAU;
+        sum += (temp_c + amplitude * phase.cos()).clamp(0.0, 30.0);
+    }
+    let biotemp = sum / 12.0;
+
+    let belt = if biotemp < 1.5 {
+        HoldridgeBelt::Polar
+    } else if biotemp < 3.0 {
+        HoldridgeBelt::Subpolar
+    } else if biotemp < 6.0 {
+        HoldridgeBelt::Boreal
+    } else if biotemp < 12.0 {
+        HoldridgeBelt::CoolTemperate
+    } else if biotemp < 18.0 {
+        HoldridgeBelt::WarmTemperate
+    } else if biotemp < 24.0 {
+        HoldridgeBelt::Subtropical
+    } else {
+        HoldridgeBelt::Tropical
+    };
+
+    let pet = PET_MM_PER_BIOTEMP * biotemp;
+    let ratio = pet / precip_mm.max(1.0);
+    let humidity = if ratio < 0.25 {
+        HumidityProvince::Superhumid
+    } else if ratio < 0.5 {
+        HumidityProvince::Perhumid
+    } else if ratio < 1.0 {
+        HumidityProvince::Humid
+    } else if ratio < 2.0 {
+        HumidityProvince::Subhumid
+    } else if ratio < 4.0 {
+        HumidityProvince::Semiarid
+    } else if ratio < 8.0 {
+        HumidityProvince::Arid
+    } else if ratio < 16.0 {
+        HumidityProvince::Perarid
+    } else {
+        HumidityProvince::Superarid
+    };
+
+    HoldridgeZone::Zone { belt, humidity }
+}
+
+pub fn whittaker_color(biome: WhittakerBiome) -> [u8; 3] {
+    match biome {
+        WhittakerBiome::None => [20, 20, 20],
+        WhittakerBiome::Tundra => [176, 190, 156],
+        WhittakerBiome::BorealForest => [66, 102, 63],
+        WhittakerBiome::TemperateGrasslandDesert => [196, 190, 120],
+        WhittakerBiome::WoodlandShrubland => [150, 150, 80],
+        WhittakerBiome::TemperateSeasonalForest => [74, 136, 80],
+        WhittakerBiome::TemperateRainforest => [40, 110, 90],
+        WhittakerBiome::SubtropicalDesert => [214, 188, 126],
+        WhittakerBiome::TropicalSeasonalForestSavanna => [170, 168, 70],
+        WhittakerBiome::TropicalRainforest => [42, 116, 52],
+    }
+}
+
+/// Colder belts are bluer and drier humidity provinces redder, so neighbouring
+/// zones stay distinguishable without a 56-entry palette.
+pub fn holdridge_color(zone: HoldridgeZone) -> [u8; 3] {
+    match zone {
+        HoldridgeZone::None => [20, 20, 20],
+        HoldridgeZone::Zone { belt, humidity } => {
+            let warm = f32::from(belt.as_u8()) / 6.0;
+            let dry = f32::from(humidity.as_u8()) / 7.0;
+            let r = 60.0 + 180.0 * dry * (0.6 + 0.4 * warm) + 60.0 * (1.0 - warm);
+            let g = 180.0 - 40.0 * warm + 20.0 * dry;
+            let b = 200.0 * (1.0 - warm) * (1.0 - dry) + 40.0;
+            [
+                r.clamp(0.0, 255.0) as u8,
+                g.clamp(0.0, 255.0) as u8,
+                b.clamp(0.0, 255.0) as u8,
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_climates_land_in_their_textbook_zones() {
+        assert_eq!(whittaker(-12.0, 200.0), WhittakerBiome::Tundra);
+        assert_eq!(whittaker(0.0, 600.0), WhittakerBiome::BorealForest);
+        assert_eq!(
+            whittaker(12.0, 1_000.0),
+            WhittakerBiome::TemperateSeasonalForest
+        );
+        assert_eq!(whittaker(25.0, 150.0), WhittakerBiome::SubtropicalDesert);
+        assert_eq!(whittaker(26.0, 3_000.0), WhittakerBiome::TropicalRainforest);
+
+        let zone = |belt, humidity| HoldridgeZone::Zone { belt, humidity };
+        assert_eq!(
+            holdridge(27.0, 4_000.0, 0.0),
+            zone(HoldridgeBelt::Tropical, HumidityProvince::Perhumid)
+        );
+        assert_eq!(
+            holdridge(25.0, 100.0, 0.2),
+            zone(HoldridgeBelt::Tropical, HumidityProvince::Perarid)
+        );
+        assert!(matches!(
+            holdridge(-15.0, 200.0, 1.0),
+            HoldridgeZone::Zone {
+                belt: HoldridgeBelt::Polar,
+                ..
+            }
+        ));
+    }
+}
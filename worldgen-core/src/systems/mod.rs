@@ -1,5 +1,23 @@
 pub mod base_fields;
+pub mod bathymetry;
 pub mod biomes;
+pub mod caves;
+pub mod coast;
+pub mod cryosphere;
+pub mod ecology;
 pub mod erosion_hydrology;
+pub mod faults;
+pub mod fauna;
+pub mod fire;
 pub mod geology;
+pub mod hazards;
 pub mod hydro_finalize;
+pub mod life_zones;
+pub mod mountains;
+pub mod naming;
+pub mod ore;
+pub mod regions;
+pub mod soils;
+pub mod vegetation;
+pub mod volcanoes;
+pub mod wilderness;
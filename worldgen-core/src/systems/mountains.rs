@@ -0,0 +1,329 @@
+use crate::params::GenerationParams;
+use crate::state::{MountainPass, MountainRange, Peak, WorldState};
+use crate::systems::base_fields::ELEVATION_SPAN_M;
+
+const DIRS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// A local maximum found by the sweep, before the significance cut.
+struct Summit {
+    tile: usize,
+    /// Saddle to higher ground and the summit beyond it; `None` for the top
+    /// of a landmass.
+    key_col: Option<(usize, usize)>,
+    prominence: f32,
+}
+
+/// Peaks by topographic prominence, the passes (key cols) that separate them
+/// from higher ground, and mountain ranges grouping peaks along ridges.
+///
+/// Land tiles are flooded from the top down; every local maximum starts an
+/// island, and when two islands meet at a tile that tile is the key col of
+/// the lower island's summit. A summit's prominence is its height above that
+/// col, or above sea level for the top of a landmass.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let mp = &params.mountains;
+    let n = state.width * state.height;
+
+    let mut order: Vec<usize> = (0..n)
+        .filter(|&i| state.elevation.as_slice()[i] > sea_level)
+        .collect();
+    let elev = state.elevation.as_slice();
+    order.sort_by(|&a, &b| elev[b].total_cmp(&elev[a]).then(a.cmp(&b)));
+
+    let mut parent = vec![usize::MAX; n];
+    // Summit index of each island root.
+    let mut island_summit = vec![usize::MAX; n];
+    // Summit whose island a tile joined when it was flooded.
+    let mut tile_summit = vec![usize::MAX; n];
+    let mut summits: Vec<Summit> = Vec::new();
+    let mut roots = Vec::with_capacity(8);
+
+    for &t in &order {
+        let (x, y) = (t % state.width, t / state.width);
+        roots.clear();
+        for (dx, dy) in DIRS_8 {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !state.elevation.in_bounds(nx, ny) {
+                continue;
+            }
+            let nidx = state.elevation.idx(nx as usize, ny as usize);
+            if parent[nidx] == usize::MAX {
+                continue;
+            }
+            let r = find(&mut parent, nidx);
+            if !roots.contains(&r) {
+                roots.push(r);
+            }
+        }
+
+        parent[t] = t;
+        if roots.is_empty() {
+            island_summit[t] = summits.len();
+            tile_summit[t] = summits.len();
+            summits.push(Summit {
+                tile: t,
+                key_col: None,
+                prominence: 0.0,
+            });
+            continue;
+        }
+
+        // Highest summit wins; ties go to the earlier-found summit.
+        roots.sort_by(|&a, &b| {
+            let sa = summits[island_summit[a]].tile;
+            let sb = summits[island_summit[b]].tile;
+            elev[sb]
+                .total_cmp(&elev[sa])
+                .then(island_summit[a].cmp(&island_summit[b]))
+        });
+        let winner = roots[0];
+        for &r in &roots[1..] {
+            let s = island_summit[r];
+            summits[s].key_col = Some((t, island_summit[winner]));
+            summits[s].prominence = elev[summits[s].tile] - elev[t];
+            parent[r] = winner;
+        }
+        parent[t] = winner;
+        tile_summit[t] = island_summit[winner];
+    }
+    for s in &mut summits {
+        if s.key_col.is_none() {
+            s.prominence = elev[s.tile] - sea_level;
+        }
+    }
+
+    let min_prominence = mp.min_peak_prominence_m / ELEVATION_SPAN_M;
+    let range_prominence = mp.range_prominence_m / ELEVATION_SPAN_M;
+    let significant: Vec<bool> = summits
+        .iter()
+        .map(|s| s.prominence >= min_prominence)
+        .collect();
+
+    // Every summit answers to its nearest significant ancestor, and every
+    // significant peak to the range head above it: the first peak up the key
+    // col chain prominent enough to stand as its own range.
+    let mut owner = vec![usize::MAX; summits.len()];
+    let mut head = vec![usize::MAX; summits.len()];
+    for s in 0..summits.len() {
+        owner[s] = ancestor(&summits, s, |a| significant[a]);
+        head[s] = ancestor(&summits, s, |a| {
+            significant[a]
+                && (summits[a].prominence >= range_prominence || summits[a].key_col.is_none())
+        });
+    }
+
+    let to_m = |h: f32| (h - sea_level) * ELEVATION_SPAN_M;
+    let mut peak_id = vec![u32::MAX; summits.len()];
+    let mut peaks = Vec::new();
+    for (s, summit) in summits.iter().enumerate() {
+        if !significant[s] {
+            continue;
+        }
+        peak_id[s] = peaks.len() as u32;
+        peaks.push(Peak {
+            id: peaks.len() as u32,
+            x: summit.tile % state.width,
+            y: summit.tile / state.width,
+            height_m: to_m(elev[summit.tile]),
+            prominence_m: summit.prominence * ELEVATION_SPAN_M,
+            range: 0,
+            pass: None,
+            name: None,
+        });
+    }
+
+    // Heads that do not rise above the range base are only hills.
+    let base = sea_level + mp.range_base_m / ELEVATION_SPAN_M;
+    let mut range_id = vec![0u32; summits.len()];
+    let mut ranges: Vec<MountainRange> = Vec::new();
+    for s in 0..summits.len() {
+        if !significant[s] || head[s] != s || elev[summits[s].tile] < base {
+            continue;
+        }
+        range_id[s] = ranges.len() as u32 + 1;
+        ranges.push(MountainRange {
+            id: ranges.len() as u32 + 1,
+            highest_peak: peak_id[s],
+            peaks: Vec::new(),
+            passes: Vec::new(),
+            ridges: Vec::new(),
+            area: 0,
+            name: None,
+        });
+    }
+    for s in 0..summits.len() {
+        let r = if significant[s] { range_id[head[s]] } else { 0 };
+        if r > 0 {
+            peaks[peak_id[s] as usize].range = r;
+            ranges[r as usize - 1].peaks.push(peak_id[s]);
+        }
+    }
+
+    // Key cols of significant peaks are the passes from one peak's ground to
+    // higher ground; the ridge runs peak, col, higher peak.
+    let mut passes = Vec::new();
+    for (s, summit) in summits.iter().enumerate() {
+        let (Some((col, higher)), true) = (summit.key_col, significant[s]) else {
+            continue;
+        };
+        let higher = owner[higher];
+        let lower = peak_id[s];
+        let pass = MountainPass {
+            id: passes.len() as u32,
+            x: col % state.width,
+            y: col / state.width,
+            height_m: to_m(elev[col]),
+            prominence_m: summit.prominence * ELEVATION_SPAN_M,
+            peaks: [lower, peak_id[higher]],
+            range: peaks[lower as usize].range,
+            name: None,
+        };
+        peaks[lower as usize].pass = Some(pass.id);
+        if pass.range > 0 {
+            let range = &mut ranges[pass.range as usize - 1];
+            range.passes.push(pass.id);
+            // Passes out of a range's head lead to another range; only ridges
+            // inside the range belong to its skeleton.
+            if peaks[peak_id[higher] as usize].range == pass.range {
+                let p = &peaks[lower as usize];
+                let q = &peaks[peak_id[higher] as usize];
+                range
+                    .ridges
+                    .push([[p.x, p.y], [pass.x, pass.y], [q.x, q.y]]);
+            }
+        }
+        passes.push(pass);
+    }
+
+    // Mountain ground: tiles above the range base belong to the range of the
+    // island they were flooded into.
+    state.mountain_range.fill(0);
+    for &t in &order {
+        if elev[t] < base {
+            break;
+        }
+        let s = owner[tile_summit[t]];
+        if s == usize::MAX {
+            continue;
+        }
+        let r = range_id[head[s]];
+        if r == 0 {
+            continue;
+        }
+        state.mountain_range.as_mut_slice()[t] = r;
+        ranges[r as usize - 1].area += 1;
+    }
+
+    state.peaks = peaks;
+    state.mountain_passes = passes;
+    state.mountain_ranges = ranges;
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// First summit up the key col chain from `s` (itself included) satisfying
+/// `pred`, or `usize::MAX`.
+fn ancestor(summits: &[Summit], mut s: usize, pred: impl Fn(usize) -> bool) -> usize {
+    loop {
+        if pred(s) {
+            return s;
+        }
+        match summits[s].key_col {
+            Some((_, higher)) => s = higher,
+            None => return usize::MAX,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    /// Two conical summits on one east-west ridge, the western one higher,
+    /// rising from open sea.
+    fn two_summit_ridge(params: &GenerationParams) -> WorldState {
+        let mut state = WorldState::new(params.clone());
+        let cone = |x: usize, y: usize, cx: f32, top: f32| {
+            let d = ((x as f32 - cx).powi(2) + (y as f32 - 128.0).powi(2)).sqrt();
+            top - 0.006 * d
+        };
+        for (x, y) in state.elevation.clone().iter_coords() {
+            let h = cone(x, y, 80.0, 0.9).max(cone(x, y, 176.0, 0.8));
+            *state.elevation.get_mut(x, y) = h.max(0.4);
+        }
+        state
+    }
+
+    fn params_with_range_cut(range_prominence_m: f32) -> GenerationParams {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.mountains.range_prominence_m = range_prominence_m;
+        params
+    }
+
+    #[test]
+    fn lower_summit_key_col_sits_on_the_saddle() {
+        let params = params_with_range_cut(400.0);
+        let mut state = two_summit_ridge(&params);
+        run(&mut state, &params);
+
+        assert_eq!(state.peaks.len(), 2);
+        let high = &state.peaks[0];
+        let low = &state.peaks[1];
+        assert_eq!([high.x, high.y], [80, 128]);
+        assert_eq!([low.x, low.y], [176, 128]);
+        // The top of the landmass measures from sea level.
+        assert!((high.prominence_m - 0.4 * ELEVATION_SPAN_M).abs() < 1.0);
+        assert_eq!(high.pass, None);
+
+        // The cones meet a third of the way from x = 136 to 137.
+        let pass = &state.mountain_passes[low.pass.expect("key col") as usize];
+        assert_eq!(pass.y, 128);
+        assert!((135..=138).contains(&pass.x), "pass at x = {}", pass.x);
+        assert_eq!(pass.peaks, [low.id, high.id]);
+        assert!((low.prominence_m - (low.height_m - pass.height_m)).abs() < 1.0);
+        assert!((low.prominence_m - 1_785.0).abs() < 60.0);
+    }
+
+    #[test]
+    fn range_prominence_cut_separates_ranges() {
+        // The lower summit's ~1785 m of prominence clears a 400 m cut but not
+        // a 2000 m one.
+        let params = params_with_range_cut(400.0);
+        let mut state = two_summit_ridge(&params);
+        run(&mut state, &params);
+        assert_eq!(state.mountain_ranges.len(), 2);
+        assert_ne!(state.peaks[0].range, state.peaks[1].range);
+        assert!(state.mountain_ranges.iter().all(|r| r.ridges.is_empty()));
+
+        let params = params_with_range_cut(2_000.0);
+        let mut state = two_summit_ridge(&params);
+        run(&mut state, &params);
+        assert_eq!(state.mountain_ranges.len(), 1);
+        let range = &state.mountain_ranges[0];
+        assert_eq!(range.peaks, vec![0, 1]);
+        assert_eq!(range.passes, vec![0]);
+        assert_eq!(range.ridges.len(), 1);
+        assert!(range.area > 0);
+    }
+}
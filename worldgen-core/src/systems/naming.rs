@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+
+use crate::language::LanguageCatalogue;
+use crate::params::GenerationParams;
+use crate::rng::{hash_u64, SplitMix64};
+use crate::state::{PlaceKind, PlaceName, RegionKind, RiverClass, WorldState};
+use crate::systems::base_fields::seed_offset;
+use crate::systems::hydro_finalize;
+
+/// Fresh words drawn before a clashing name is accepted anyway.
+const NAME_ATTEMPTS: usize = 8;
+
+/// A feature awaiting a name.
+struct Feature {
+    kind: PlaceKind,
+    id: u32,
+    size: u32,
+    anchor: [usize; 2],
+    biome: Option<String>,
+}
+
+/// Names regions, lakes, oceans, rivers, mountain ranges, peaks and passes.
+///
+/// Each feature takes the language of the nearest seeded culture centre, and
+/// its rank by size among features of its kind picks the form, so the
+/// largest get the grandest titles.
+pub fn run(state: &mut WorldState, params: &GenerationParams) -> Result<()> {
+    let languages = match &params.naming.languages {
+        Some(path) => LanguageCatalogue::load(path)?,
+        None => LanguageCatalogue::builtin(),
+    };
+
+    let mut features = region_features(state);
+    features.extend(river_features(state));
+    features.extend(mountain_features(state));
+    // Peaks and passes already passed the prominence cut.
+    features.retain(|f| {
+        matches!(f.kind, PlaceKind::Ocean | PlaceKind::Peak | PlaceKind::Pass)
+            || f.size >= params.naming.min_feature_tiles
+    });
+
+    let mut rng = SplitMix64::new(seed_offset(params.seed, 1717));
+    let centres: Vec<[f32; 2]> = (0..languages.len())
+        .map(|_| {
+            [
+                rng.gen_range_f32(0.0, state.width as f32),
+                rng.gen_range_f32(0.0, state.height as f32),
+            ]
+        })
+        .collect();
+
+    let mut by_kind: BTreeMap<PlaceKind, Vec<&Feature>> = BTreeMap::new();
+    for f in &features {
+        by_kind.entry(f.kind).or_default().push(f);
+    }
+
+    let name_seed = seed_offset(params.seed, 1818);
+    let mut used = BTreeSet::new();
+    let mut places = Vec::with_capacity(features.len());
+    for (kind, mut group) in by_kind {
+        group.sort_by_key(|f| (f.size, f.id));
+        let count = group.len();
+        for (rank, f) in group.into_iter().enumerate() {
+            let language = nearest_language(&centres, f.anchor);
+            let lang = languages.language(language);
+            let forms = lang.forms(kind);
+            let form = &forms[(rank * forms.len() / count).min(forms.len() - 1)];
+
+            let mut rng = SplitMix64::new(hash_u64(
+                name_seed ^ (u64::from(kind.as_u8()) << 32) ^ u64::from(f.id),
+            ));
+            let mut name = String::new();
+            for _ in 0..NAME_ATTEMPTS {
+                name = form
+                    .replace("{name}", &lang.word(&mut rng))
+                    .replace("{biome}", f.biome.as_deref().unwrap_or_default());
+                if !used.contains(&name) {
+                    break;
+                }
+            }
+            used.insert(name.clone());
+            places.push(PlaceName {
+                kind,
+                feature_id: f.id,
+                name,
+                language: lang.name.clone(),
+                size: f.size,
+                anchor: f.anchor,
+            });
+        }
+    }
+    places.sort_by_key(|p| (p.kind, p.feature_id));
+
+    for region in &mut state.regions {
+        region.name = None;
+    }
+    for range in &mut state.mountain_ranges {
+        range.name = None;
+    }
+    for place in &places {
+        let id = place.feature_id as usize;
+        let name = Some(place.name.clone());
+        match place.kind {
+            PlaceKind::Region | PlaceKind::Lake | PlaceKind::Ocean => state.regions[id].name = name,
+            PlaceKind::Mountains => state.mountain_ranges[id - 1].name = name,
+            PlaceKind::Peak => state.peaks[id].name = name,
+            PlaceKind::Pass => state.mountain_passes[id].name = name,
+            PlaceKind::River => {}
+        }
+    }
+    state.place_names = places;
+    Ok(())
+}
+
+fn nearest_language(centres: &[[f32; 2]], anchor: [usize; 2]) -> usize {
+    let [ax, ay] = [anchor[0] as f32, anchor[1] as f32];
+    centres
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            let da = (a[0] - ax).powi(2) + (a[1] - ay).powi(2);
+            let db = (b[0] - ax).powi(2) + (b[1] - ay).powi(2);
+            da.total_cmp(&db)
+        })
+        .map_or(0, |(i, _)| i)
+}
+
+/// Regions, lakes and oceans from the region table, anchored on their tile
+/// nearest the centroid.
+fn region_features(state: &WorldState) -> Vec<Feature> {
+    let mut best: Vec<(f32, [usize; 2])> = vec![(f32::INFINITY, [0, 0]); state.regions.len()];
+    for (x, y) in state.region_id.iter_coords() {
+        let id = *state.region_id.get(x, y) as usize;
+        let [cx, cy] = state.regions[id].centroid;
+        let d = (x as f32 - cx).powi(2) + (y as f32 - cy).powi(2);
+        if d < best[id].0 {
+            best[id] = (d, [x, y]);
+        }
+    }
+
+    state
+        .regions
+        .iter()
+        .map(|r| Feature {
+            kind: match r.kind {
+                RegionKind::Land => PlaceKind::Region,
+                RegionKind::Lake => PlaceKind::Lake,
+                RegionKind::Ocean => PlaceKind::Ocean,
+            },
+            id: r.id,
+            size: r.area,
+            anchor: best[r.id as usize].1,
+            biome: Some(state.biome_table.def(r.biome).name.clone()),
+        })
+        .collect()
+}
+
+/// River systems of perennial and major channels: every such land tile
+/// belongs to the system of the last channel tile reached downstream, its
+/// mouth, so named tributaries share the name of the river they join.
+fn river_features(state: &WorldState) -> Vec<Feature> {
+    let n = state.width * state.height;
+    let channel = |idx: usize| {
+        let (x, y) = (idx % state.width, idx / state.width);
+        matches!(
+            *state.river_class.get(x, y),
+            RiverClass::Perennial | RiverClass::Major
+        ) && !*state.ocean_mask.get(x, y)
+            && *state.lake_id.get(x, y) == 0
+    };
+
+    let mut mouth = vec![usize::MAX; n];
+    let mut path = Vec::new();
+    for start in 0..n {
+        if !channel(start) || mouth[start] != usize::MAX {
+            continue;
+        }
+        path.clear();
+        let mut idx = start;
+        let end = loop {
+            if mouth[idx] != usize::MAX {
+                break mouth[idx];
+            }
+            path.push(idx);
+            let next = hydro_finalize::downstream(state, idx % state.width, idx / state.width)
+                .map(|(x, y)| state.elevation.idx(x, y));
+            match next {
+                Some(next) if channel(next) && !path.contains(&next) => idx = next,
+                _ => break idx,
+            }
+        };
+        for &i in &path {
+            mouth[i] = end;
+        }
+    }
+
+    let mut systems: BTreeMap<usize, u32> = BTreeMap::new();
+    for &m in mouth.iter().filter(|&&m| m != usize::MAX) {
+        *systems.entry(m).or_insert(0) += 1;
+    }
+    systems
+        .into_iter()
+        .enumerate()
+        .map(|(i, (m, size))| Feature {
+            kind: PlaceKind::River,
+            id: i as u32,
+            size,
+            anchor: [m % state.width, m / state.width],
+            biome: None,
+        })
+        .collect()
+}
+
+/// Mountain ranges anchored on their highest peak, and every peak and pass,
+/// sized by prominence.
+fn mountain_features(state: &WorldState) -> Vec<Feature> {
+    let ranges = state.mountain_ranges.iter().map(|r| {
+        let top = &state.peaks[r.highest_peak as usize];
+        Feature {
+            kind: PlaceKind::Mountains,
+            id: r.id,
+            size: r.area,
+            anchor: [top.x, top.y],
+            biome: None,
+        }
+    });
+    let peaks = state.peaks.iter().map(|p| Feature {
+        kind: PlaceKind::Peak,
+        id: p.id,
+        size: p.prominence_m.round() as u32,
+        anchor: [p.x, p.y],
+        biome: None,
+    });
+    let passes = state.mountain_passes.iter().map(|p| Feature {
+        kind: PlaceKind::Pass,
+        id: p.id,
+        size: p.prominence_m.round() as u32,
+        anchor: [p.x, p.y],
+        biome: None,
+    });
+    ranges.chain(peaks).chain(passes).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::state::Region;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn names_are_unique_and_the_largest_gets_the_grandest_form() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        let forest = state
+            .biome_table
+            .find("Temperate Forest")
+            .expect("built-in biome");
+        state.regions = (0..40)
+            .map(|i| Region {
+                id: i,
+                kind: RegionKind::Land,
+                biome: forest,
+                lake_id: 0,
+                area: 20 + 10 * i,
+                centroid: [6.0 * i as f32, 6.0 * i as f32],
+                name: None,
+            })
+            .collect();
+
+        run(&mut state, &params).expect("built-in languages");
+
+        assert_eq!(state.place_names.len(), 40);
+        let names: BTreeSet<&str> = state.place_names.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names.len(), 40);
+
+        let largest = state
+            .place_names
+            .iter()
+            .max_by_key(|p| p.size)
+            .expect("named regions");
+        let languages = LanguageCatalogue::builtin();
+        let lang = languages
+            .iter()
+            .find(|l| l.name == largest.language)
+            .expect("builtin language");
+        let grandest = lang.forms(PlaceKind::Region).last().expect("region forms");
+        let grandest = grandest.replace("{biome}", "Temperate Forest");
+        let (prefix, suffix) = grandest.split_once("{name}").expect("{name} placeholder");
+        assert!(
+            largest.name.starts_with(prefix) && largest.name.ends_with(suffix),
+            "{:?} is not in the form {grandest:?}",
+            largest.name
+        );
+        assert_eq!(
+            state.regions[39].name.as_deref(),
+            Some(largest.name.as_str())
+        );
+    }
+}
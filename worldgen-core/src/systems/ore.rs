@@ -0,0 +1,515 @@
+use std::collections::VecDeque;
+
+use crate::catalogue::in_range;
+use crate::params::GenerationParams;
+use crate::state::{DepositKind, Mineral, OreDeposit, RiverClass, RockType, WorldState};
+use crate::strata::vein_span;
+use crate::systems::base_fields::seed_offset;
+use crate::systems::biomes;
+use crate::systems::geology::smooth_uniform;
+use crate::systems::hydro_finalize::{self, DIRS_8};
+
+const ROCK_DENSITY_T_M3: f32 = 2.7;
+const GRAVEL_DENSITY_T_M3: f32 = 2.0;
+const VEIN_WIDTH_M: f32 = 3.0;
+/// Share of a host bed that is ore.
+const STRATIFORM_FILL: f32 = 0.25;
+const PLACER_CHANNEL_WIDTH_M: f32 = 50.0;
+const PLACER_GRAVEL_M: f32 = 2.0;
+/// Tiles a grain travels downstream before it is spent.
+const PLACER_REACH: usize = 60;
+/// Share of placer load kept per tile travelled.
+const PLACER_DECAY: f32 = 0.95;
+const PLACER_MIN_LOAD: f32 = 0.5;
+/// Slope (metres to the lowest neighbour) over which gold stops settling.
+const PLACER_SLOPE_M: f32 = 20.0;
+/// Noise frequency of ore favourability; higher gives smaller bodies.
+const ORE_PATCH_SCALE: f32 = 12.0;
+/// Half-width in tiles, at fault strength 1, of the fault zones veins fill.
+const FAULT_ZONE_TILES: f32 = 3.0;
+
+/// Share of a mineral's typical grade that the richest trace showing outside
+/// a deposit reaches.
+const TRACE_SHARE: f32 = 0.2;
+
+/// Per-mineral working grids, indexed by `Mineral::as_u8`.
+struct OreGrids {
+    ids: Vec<Vec<u32>>,
+    grade: Vec<Vec<f32>>,
+    reserve: Vec<Vec<f32>>,
+}
+
+/// What a body of ore is: its mineral, its style and, for stratiform
+/// bodies, the host layer and rock.
+#[derive(Clone, Copy)]
+struct BodySpec {
+    mineral: Mineral,
+    kind: DepositKind,
+    host: Option<(usize, RockType)>,
+}
+
+/// A tile of a candidate ore body and how far its score clears the
+/// threshold, 0..1.
+struct OreTile {
+    idx: usize,
+    excess: f32,
+}
+
+/// Ore bodies on land as connected deposits of each catalogue mineral: veins
+/// in the zones along fault traces, beds in host strata, and placers washed
+/// downstream along `flow_dir` for minerals with a placer grade.
+/// Favourability is smooth noise plus the catalogue's host-rock and province
+/// bonuses, so deposits are coherent bodies rather than scattered tiles.
+/// Bodies grade from 0.6 to 1.4 times the mineral's typical grade.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let gp = &params.geology;
+    let n = state.width * state.height;
+    let land: Vec<bool> = state
+        .elevation
+        .iter_coords()
+        .map(|(x, y)| {
+            *state.elevation.get(x, y) > params.base.sea_level
+                && !*state.ocean_mask.get(x, y)
+                && *state.lake_id.get(x, y) == 0
+        })
+        .collect();
+    let land_tiles = land.iter().filter(|&&l| l).count();
+    // Fault strength 0 leaves no fault zones to host veins.
+    let zone = FAULT_ZONE_TILES * gp.fault_strength.clamp(0.0, 1.0);
+
+    let catalogue = &state.geology_catalogue;
+    let minerals = catalogue.mineral_count();
+    let mut deposits: Vec<OreDeposit> = Vec::new();
+    let mut grids = OreGrids {
+        ids: vec![vec![0; n]; minerals],
+        grade: vec![vec![0.0; n]; minerals],
+        reserve: vec![vec![0.0; n]; minerals],
+    };
+    for (mineral, def) in catalogue.minerals() {
+        let kind = def.style;
+        let noise = smooth_uniform(
+            state,
+            seed_offset(params.seed, 2300 + u64::from(mineral.as_u8())),
+            ORE_PATCH_SCALE,
+        );
+
+        // Stratiform bodies keep to one layer and rock, their host stratum;
+        // veins cut the whole column and have none.
+        let mut keys: Vec<Option<Option<(usize, RockType)>>> = vec![None; n];
+        let mut score = vec![f32::NEG_INFINITY; n];
+        for (x, y) in state.elevation.iter_coords() {
+            let idx = state.elevation.idx(x, y);
+            if !land[idx] {
+                continue;
+            }
+            let column = state.strata_column(x, y);
+            let province = def.province_bonus(*state.geologic_province.get(x, y));
+            let (key, host) = match kind {
+                DepositKind::Stratiform => {
+                    let Some(l) = column.iter().position(|i| {
+                        catalogue.host_bonus(mineral, i.rock) > 0.0
+                            && in_range(def.depth_m, i.top_m)
+                    }) else {
+                        continue;
+                    };
+                    let rock = column[l].rock;
+                    (Some((l, rock)), catalogue.host_bonus(mineral, rock))
+                }
+                _ => {
+                    if *state.fault_distance.get(x, y) >= zone {
+                        continue;
+                    }
+                    let (top, bottom) = vein_span(state, mineral, x, y);
+                    if top >= bottom {
+                        continue;
+                    }
+                    let host = column
+                        .iter()
+                        .filter(|i| i.clip(top, bottom).is_some())
+                        .map(|i| catalogue.host_bonus(mineral, i.rock))
+                        .fold(0.0, f32::max);
+                    (None, host)
+                }
+            };
+            keys[idx] = Some(key);
+            score[idx] = noise[idx] + host + province;
+        }
+
+        // Only the best-scoring candidates, up to the mineral's share of
+        // land, become ore.
+        let candidate: Vec<bool> = keys.iter().map(Option::is_some).collect();
+        let mut ranked: Vec<usize> = (0..n).filter(|&i| candidate[i]).collect();
+        ranked.sort_by(|&a, &b| score[b].total_cmp(&score[a]).then(a.cmp(&b)));
+        let target = (land_tiles as f32 * def.ore_share(gp.ore_richness)).round() as usize;
+        for &i in ranked.iter().skip(target) {
+            keys[i] = None;
+        }
+        let kept = target.min(ranked.len());
+        let (lo, hi) = if kept > 0 {
+            (score[ranked[kept - 1]], score[ranked[0]])
+        } else {
+            (0.0, 0.0)
+        };
+        let excess = |i: usize| ((score[i] - lo) / (hi - lo).max(1e-6)).clamp(0.0, 1.0);
+
+        for body in components(state, &keys) {
+            if body.len() < gp.min_deposit_tiles as usize {
+                continue;
+            }
+            let host = keys[body[0]].flatten();
+            let tiles: Vec<OreTile> = body
+                .into_iter()
+                .map(|idx| OreTile {
+                    idx,
+                    excess: excess(idx),
+                })
+                .collect();
+            let spec = BodySpec {
+                mineral,
+                kind,
+                host,
+            };
+            push_deposit(state, params, spec, &tiles, &mut deposits, &mut grids);
+        }
+
+        // Candidates left outside a deposit carry trace showings, richer the
+        // closer they came to the cut.
+        let floor = ranked.last().map_or(0.0, |&i| score[i]);
+        let m = usize::from(mineral.as_u8());
+        for &i in &ranked {
+            if grids.ids[m][i] == 0 {
+                let rel = ((score[i] - floor) / (hi - floor).max(1e-6)).clamp(0.0, 1.0);
+                grids.grade[m][i] = def.grade * TRACE_SHARE * rel;
+            }
+        }
+    }
+
+    for (mineral, def) in catalogue.minerals() {
+        if let Some(placer_grade) = def.placer_grade {
+            placers(
+                state,
+                params,
+                mineral,
+                placer_grade,
+                &mut deposits,
+                &mut grids,
+            );
+        }
+    }
+
+    for m in 0..minerals {
+        let mineral = Mineral(m as u8);
+        let def = state.geology_catalogue.mineral(mineral);
+        // Flag tiles at `mask_cutoff` times the leanest typical grade the
+        // mineral forms.
+        let cutoff = def.leanest_grade() * gp.mask_cutoff;
+        let key = state.geology_catalogue.mineral_key(mineral).to_string();
+        if let Some(mask) = state.mineral_masks.get_mut(&key) {
+            for (flag, &g) in mask.as_mut_slice().iter_mut().zip(&grids.grade[m]) {
+                *flag = g > 0.0 && g >= cutoff;
+            }
+        }
+        if let Some(grid) = state.ore_deposit_id.get_mut(&key) {
+            grid.as_mut_slice().copy_from_slice(&grids.ids[m]);
+        }
+        if let Some(grid) = state.mineral_grade.get_mut(&key) {
+            grid.as_mut_slice().copy_from_slice(&grids.grade[m]);
+        }
+        if let Some(grid) = state.mineral_reserve_t.get_mut(&key) {
+            grid.as_mut_slice().copy_from_slice(&grids.reserve[m]);
+        }
+    }
+    state.ore_deposits = deposits;
+}
+
+/// Grains shed by a mineral's bedrock deposits, carried down `flow_dir` and
+/// settling in river channels where the gradient slackens. Tiles already in
+/// a deposit keep it.
+fn placers(
+    state: &WorldState,
+    params: &GenerationParams,
+    mineral: Mineral,
+    typical: f32,
+    deposits: &mut Vec<OreDeposit>,
+    grids: &mut OreGrids,
+) {
+    let n = state.width * state.height;
+    let sea_level = params.base.sea_level;
+    let m = usize::from(mineral.as_u8());
+    let mut load = vec![0.0f32; n];
+    // Every tile of the mineral so far belongs to a bedrock deposit.
+    for src in (0..n).filter(|&i| grids.ids[m][i] > 0) {
+        let mut carried = 1.0;
+        let mut idx = src;
+        for _ in 0..PLACER_REACH {
+            let Some((x, y)) =
+                hydro_finalize::downstream(state, idx % state.width, idx / state.width)
+            else {
+                break;
+            };
+            let next = state.elevation.idx(x, y);
+            if *state.ocean_mask.get(x, y) || *state.lake_id.get(x, y) > 0 {
+                break;
+            }
+            carried *= PLACER_DECAY;
+            if *state.river_class.get(x, y) != RiverClass::None {
+                let slope = biomes::slope_m(state, x, y, sea_level);
+                load[next] += carried * (-slope / PLACER_SLOPE_M).exp();
+            }
+            idx = next;
+        }
+    }
+
+    let keys: Vec<Option<()>> = (0..n)
+        .map(|i| (load[i] >= PLACER_MIN_LOAD && grids.ids[m][i] == 0).then_some(()))
+        .collect();
+    for body in components(state, &keys) {
+        if body.len() < params.geology.min_deposit_tiles as usize {
+            continue;
+        }
+        let tiles: Vec<OreTile> = body
+            .into_iter()
+            .map(|idx| OreTile {
+                idx,
+                excess: (load[idx] / (4.0 * PLACER_MIN_LOAD)).min(1.0),
+            })
+            .collect();
+        let spec = BodySpec {
+            mineral,
+            kind: DepositKind::Placer,
+            host: None,
+        };
+        push_deposit(state, params, spec, &tiles, deposits, grids);
+    }
+
+    // Thin gravels and bodies too small to work are trace showings.
+    for (i, &l) in load.iter().enumerate() {
+        if l > 0.0 && grids.ids[m][i] == 0 {
+            let trace = typical * TRACE_SHARE * (l / PLACER_MIN_LOAD).min(1.0);
+            grids.grade[m][i] = grids.grade[m][i].max(trace);
+        }
+    }
+}
+
+fn push_deposit(
+    state: &WorldState,
+    params: &GenerationParams,
+    spec: BodySpec,
+    tiles: &[OreTile],
+    deposits: &mut Vec<OreDeposit>,
+    grids: &mut OreGrids,
+) {
+    let BodySpec {
+        mineral,
+        kind,
+        host,
+    } = spec;
+    let id = deposits.len() as u32 + 1;
+    let m = usize::from(mineral.as_u8());
+    let size = params.geology.tile_size_m;
+    let typical = state.geology_catalogue.mineral(mineral).typical_grade(kind);
+    let mut tonnage = 0.0;
+    let mut metal = 0.0;
+    let mut top = 0.0;
+    let mut bottom = 0.0;
+    let mut anchor = (f32::MIN, 0);
+    for t in tiles {
+        let (x, y) = (t.idx % state.width, t.idx / state.width);
+        let (tile_top, tile_bottom) = match host {
+            Some((l, _)) => {
+                let column = state.strata_column(x, y);
+                (column[l].top_m, column[l].bottom_m)
+            }
+            None if kind == DepositKind::Placer => (0.0, PLACER_GRAVEL_M),
+            None => vein_span(state, mineral, x, y),
+        };
+        let thickness = tile_bottom - tile_top;
+        let t_ore = match kind {
+            DepositKind::Vein => size * VEIN_WIDTH_M * thickness * ROCK_DENSITY_T_M3,
+            DepositKind::Stratiform => {
+                size * size * thickness * STRATIFORM_FILL * ROCK_DENSITY_T_M3
+            }
+            DepositKind::Placer => size * PLACER_CHANNEL_WIDTH_M * thickness * GRAVEL_DENSITY_T_M3,
+        };
+        let grade = typical * (0.6 + 0.8 * t.excess);
+        tonnage += t_ore;
+        metal += t_ore * grade;
+        top += tile_top;
+        bottom += tile_bottom;
+        if t.excess > anchor.0 {
+            anchor = (t.excess, t.idx);
+        }
+        grids.ids[m][t.idx] = id;
+        grids.grade[m][t.idx] = grade;
+        grids.reserve[m][t.idx] = t_ore * grade;
+    }
+
+    let count = tiles.len() as f32;
+    deposits.push(OreDeposit {
+        id,
+        mineral,
+        kind,
+        grade: if tonnage > 0.0 { metal / tonnage } else { 0.0 },
+        tonnage_t: tonnage,
+        tiles: tiles.len() as u32,
+        anchor: [anchor.1 % state.width, anchor.1 / state.width],
+        top_m: top / count,
+        bottom_m: bottom / count,
+        host: host.map(|(_, rock)| rock),
+    });
+}
+
+/// 8-connected groups of tiles with equal keys, in row-major order of their
+/// first tile.
+fn components<K: Copy + PartialEq>(state: &WorldState, keys: &[Option<K>]) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; keys.len()];
+    let mut out = Vec::new();
+    let mut q = VecDeque::new();
+    for start in 0..keys.len() {
+        let Some(key) = keys[start] else {
+            continue;
+        };
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        q.push_back(start);
+        let mut body = Vec::new();
+        while let Some(idx) = q.pop_front() {
+            body.push(idx);
+            let (x, y) = (idx % state.width, idx / state.width);
+            for (dx, dy) in DIRS_8 {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let nidx = state.elevation.idx(nx as usize, ny as usize);
+                if !seen[nidx] && keys[nidx] == Some(key) {
+                    seen[nidx] = true;
+                    q.push_back(nidx);
+                }
+            }
+        }
+        body.sort_unstable();
+        out.push(body);
+    }
+    out
+}
+
+pub fn deposit_color(kind: DepositKind) -> [u8; 3] {
+    match kind {
+        DepositKind::Vein => [230, 90, 60],
+        DepositKind::Stratiform => [240, 200, 30],
+        DepositKind::Placer => [250, 240, 150],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Step;
+    use crate::{run_step, MapSizePreset};
+
+    /// A generated world up to and including the geology step.
+    fn geology_world(seed: u64) -> WorldState {
+        let mut params = GenerationParams {
+            seed,
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.geology.ore_richness = 1.0;
+        let mut state = WorldState::new(params.clone());
+        for step in Step::ALL.into_iter().take_while(|&s| s <= Step::Geology) {
+            run_step(&mut state, step, &params).expect("step");
+        }
+        state
+    }
+
+    #[test]
+    fn deposits_are_connected_and_placers_lie_downstream() {
+        let state = geology_world(31);
+        let (w, h) = (state.width, state.height);
+        let mut placer_tiles = 0;
+        for (mineral, _) in state.geology_catalogue.minerals() {
+            let key = state.geology_catalogue.mineral_key(mineral);
+            let ids = state.ore_deposit_id[key].as_slice();
+            for deposit in state.ore_deposits.iter().filter(|d| d.mineral == mineral) {
+                let keys: Vec<Option<()>> = ids
+                    .iter()
+                    .map(|&id| (id == deposit.id).then_some(()))
+                    .collect();
+                let bodies = components(&state, &keys);
+                assert_eq!(bodies.len(), 1, "deposit {} is split", deposit.id);
+                assert_eq!(bodies[0].len(), deposit.tiles as usize);
+            }
+
+            // Every placer tile is reached by walking down `flow_dir` from a
+            // bedrock deposit of the same mineral.
+            let placer =
+                |id: u32| id > 0 && state.ore_deposits[id as usize - 1].kind == DepositKind::Placer;
+            let mut reached = vec![false; w * h];
+            for src in (0..w * h).filter(|&i| ids[i] > 0 && !placer(ids[i])) {
+                let (mut x, mut y) = (src % w, src / w);
+                for _ in 0..PLACER_REACH {
+                    let Some(next) = hydro_finalize::downstream(&state, x, y) else {
+                        break;
+                    };
+                    (x, y) = next;
+                    reached[y * w + x] = true;
+                }
+            }
+            for i in (0..w * h).filter(|&i| placer(ids[i])) {
+                assert!(reached[i], "placer tile {i} has no source upstream");
+                placer_tiles += 1;
+            }
+        }
+        assert!(placer_tiles > 0, "no placers formed");
+    }
+
+    #[test]
+    fn grade_grids_follow_deposits_and_cutoff() {
+        let state = geology_world(31);
+        let cutoff_share = state.params.geology.mask_cutoff;
+        let mut traces = 0;
+        for (mineral, def) in state.geology_catalogue.minerals() {
+            let key = state.geology_catalogue.mineral_key(mineral);
+            let ids = state.ore_deposit_id[key].as_slice();
+            let grade = state.mineral_grade[key].as_slice();
+            let reserve = state.mineral_reserve_t[key].as_slice();
+            let mask = state.mineral_masks[key].as_slice();
+            let richest = def.grade.max(def.placer_grade.unwrap_or(0.0));
+            let cutoff = def.leanest_grade() * cutoff_share;
+
+            for (i, &g) in grade.iter().enumerate() {
+                assert_eq!(mask[i], g >= cutoff, "mask at tile {i}");
+                if ids[i] == 0 {
+                    assert!(g <= TRACE_SHARE * richest, "trace {g} at tile {i}");
+                    assert_eq!(reserve[i], 0.0);
+                    traces += usize::from(g > 0.0);
+                    continue;
+                }
+                let deposit = &state.ore_deposits[ids[i] as usize - 1];
+                let typical = def.typical_grade(deposit.kind);
+                assert!(
+                    (0.6 * typical * 0.999..=1.4 * typical * 1.001).contains(&g),
+                    "grade {g} outside the band of {typical}"
+                );
+            }
+
+            for deposit in state.ore_deposits.iter().filter(|d| d.mineral == mineral) {
+                let metal: f64 = (0..ids.len())
+                    .filter(|&i| ids[i] == deposit.id)
+                    .map(|i| f64::from(reserve[i]))
+                    .sum();
+                let expected = f64::from(deposit.tonnage_t) * f64::from(deposit.grade);
+                assert!(
+                    (metal - expected).abs() <= expected * 1e-3,
+                    "deposit {} holds {metal} t, expected {expected} t",
+                    deposit.id
+                );
+            }
+        }
+        assert!(traces > 0, "no trace showings");
+    }
+}
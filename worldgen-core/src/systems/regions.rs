@@ -0,0 +1,269 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+
+use anyhow::Result;
+
+use crate::params::GenerationParams;
+use crate::rng::hash_u64;
+use crate::state::{Biome, Region, RegionKind, WorldState};
+use crate::systems::{mountains, naming};
+
+const DIRS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TileKey {
+    Ocean,
+    Lake(u32),
+    Land(Biome),
+}
+
+impl TileKey {
+    fn kind(self) -> RegionKind {
+        match self {
+            Self::Ocean => RegionKind::Ocean,
+            Self::Lake(_) => RegionKind::Lake,
+            Self::Land(_) => RegionKind::Land,
+        }
+    }
+}
+
+/// Step 7: region segmentation and mountain features, then names for them.
+pub fn run(state: &mut WorldState, params: &GenerationParams) -> Result<()> {
+    segment(state, params);
+    mountains::run(state, params);
+    naming::run(state, params)
+}
+
+/// Connected components of same-biome land, of each lake and of the ocean,
+/// with land slivers below `min_region_tiles` merged into neighbours. Region
+/// ids follow row-major order of each region's first tile.
+fn segment(state: &mut WorldState, params: &GenerationParams) {
+    let keys: Vec<TileKey> = state
+        .elevation
+        .iter_coords()
+        .map(|(x, y)| {
+            let lake = *state.lake_id.get(x, y);
+            if *state.ocean_mask.get(x, y) {
+                TileKey::Ocean
+            } else if lake > 0 {
+                TileKey::Lake(lake)
+            } else {
+                TileKey::Land(*state.biome.get(x, y))
+            }
+        })
+        .collect();
+
+    let (mut labels, kinds) = label_components(state, &keys);
+    merge_small_regions(state, &mut labels, &kinds, params.regions.min_region_tiles);
+    build_table(state, &labels, &keys);
+}
+
+/// 8-connected flood fill over tiles with equal keys.
+fn label_components(state: &WorldState, keys: &[TileKey]) -> (Vec<u32>, Vec<RegionKind>) {
+    let mut labels = vec![u32::MAX; keys.len()];
+    let mut kinds = Vec::new();
+    let mut q = VecDeque::new();
+    for (x, y) in state.elevation.iter_coords() {
+        let start = state.elevation.idx(x, y);
+        if labels[start] != u32::MAX {
+            continue;
+        }
+        let label = kinds.len() as u32;
+        kinds.push(keys[start].kind());
+        labels[start] = label;
+        q.push_back((x, y));
+        while let Some((cx, cy)) = q.pop_front() {
+            for (dx, dy) in DIRS_8 {
+                let nx = cx as isize + dx;
+                let ny = cy as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let nidx = state.elevation.idx(nx as usize, ny as usize);
+                if labels[nidx] == u32::MAX && keys[nidx] == keys[start] {
+                    labels[nidx] = label;
+                    q.push_back((nx as usize, ny as usize));
+                }
+            }
+        }
+    }
+    (labels, kinds)
+}
+
+/// Repeatedly folds each undersized land region into the land neighbour it
+/// shares the most border with. A region only merges into one that is larger
+/// (by area, then label), so merges within a pass never form a cycle.
+fn merge_small_regions(
+    state: &WorldState,
+    labels: &mut [u32],
+    kinds: &[RegionKind],
+    min_tiles: u32,
+) {
+    let count = kinds.len();
+    loop {
+        let mut area = vec![0u32; count];
+        for &l in labels.iter() {
+            area[l as usize] += 1;
+        }
+        let mut borders: Vec<BTreeMap<u32, u32>> = vec![BTreeMap::new(); count];
+        for (x, y) in state.elevation.iter_coords() {
+            let a = labels[state.elevation.idx(x, y)];
+            if kinds[a as usize] != RegionKind::Land {
+                continue;
+            }
+            for (dx, dy) in DIRS_8 {
+                let nx = x as isize + dx;
+                let ny = y as isize + dy;
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let b = labels[state.elevation.idx(nx as usize, ny as usize)];
+                if b != a && kinds[b as usize] == RegionKind::Land {
+                    *borders[a as usize].entry(b).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut target: Vec<u32> = (0..count as u32).collect();
+        let mut merged = false;
+        for r in 0..count {
+            if kinds[r] != RegionKind::Land || area[r] == 0 || area[r] >= min_tiles {
+                continue;
+            }
+            let rank = (area[r], r as u32);
+            let best = borders[r]
+                .iter()
+                .filter(|&(&n, _)| (area[n as usize], n) > rank)
+                .max_by_key(|&(&n, &shared)| (shared, area[n as usize], Reverse(n)));
+            if let Some((&n, _)) = best {
+                target[r] = n;
+                merged = true;
+            }
+        }
+        if !merged {
+            return;
+        }
+
+        for r in 0..count {
+            let mut t = target[r];
+            while target[t as usize] != t {
+                t = target[t as usize];
+            }
+            target[r] = t;
+        }
+        for l in labels.iter_mut() {
+            *l = target[*l as usize];
+        }
+    }
+}
+
+fn build_table(state: &mut WorldState, labels: &[u32], keys: &[TileKey]) {
+    let mut remap: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut area: Vec<u32> = Vec::new();
+    let mut sum: Vec<[f64; 2]> = Vec::new();
+    let mut biomes: Vec<BTreeMap<Biome, u32>> = Vec::new();
+    let mut first: Vec<TileKey> = Vec::new();
+
+    for (x, y) in state.elevation.iter_coords() {
+        let idx = state.elevation.idx(x, y);
+        let id = *remap.entry(labels[idx]).or_insert_with(|| {
+            area.push(0);
+            sum.push([0.0; 2]);
+            biomes.push(BTreeMap::new());
+            first.push(keys[idx]);
+            area.len() as u32 - 1
+        });
+        *state.region_id.get_mut(x, y) = id;
+        let i = id as usize;
+        area[i] += 1;
+        sum[i][0] += x as f64;
+        sum[i][1] += y as f64;
+        *biomes[i].entry(*state.biome.get(x, y)).or_insert(0) += 1;
+    }
+
+    state.regions = (0..area.len())
+        .map(|i| {
+            let biome = biomes[i]
+                .iter()
+                .max_by_key(|&(&b, &n)| (n, Reverse(b)))
+                .map_or(Biome(0), |(&b, _)| b);
+            let n = f64::from(area[i]);
+            Region {
+                id: i as u32,
+                kind: first[i].kind(),
+                biome,
+                lake_id: match first[i] {
+                    TileKey::Lake(id) => id,
+                    _ => 0,
+                },
+                area: area[i],
+                centroid: [(sum[i][0] / n) as f32, (sum[i][1] / n) as f32],
+                name: None,
+            }
+        })
+        .collect();
+}
+
+/// Arbitrary but stable colour for telling neighbouring regions apart; water
+/// regions are tinted blue.
+pub fn region_color(region: &Region) -> [u8; 3] {
+    let h = hash_u64(u64::from(region.id));
+    let [r, g, b] = [h as u8, (h >> 8) as u8, (h >> 16) as u8];
+    match region.kind {
+        RegionKind::Land => [r / 2 + 96, g / 2 + 96, b / 4 + 48],
+        RegionKind::Lake | RegionKind::Ocean => [r / 8, g / 4 + 32, b / 2 + 128],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn small_regions_merge_but_islands_stay() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let state = WorldState::new(params.clone());
+        let idx = |x, y| state.elevation.idx(x, y);
+        // A western continent of one biome holding a 3x3 patch of another,
+        // and a 2x2 island of the continent's biome out at sea.
+        let mut keys = vec![TileKey::Ocean; state.width * state.height];
+        for (x, y) in state.elevation.iter_coords() {
+            if x < 128 {
+                keys[idx(x, y)] = TileKey::Land(Biome(1));
+            }
+        }
+        for y in 50..53 {
+            for x in 50..53 {
+                keys[idx(x, y)] = TileKey::Land(Biome(2));
+            }
+        }
+        for y in 200..202 {
+            for x in 200..202 {
+                keys[idx(x, y)] = TileKey::Land(Biome(1));
+            }
+        }
+
+        let (mut labels, kinds) = label_components(&state, &keys);
+        assert_ne!(labels[idx(51, 51)], labels[idx(10, 10)]);
+        merge_small_regions(&state, &mut labels, &kinds, params.regions.min_region_tiles);
+
+        assert_eq!(labels[idx(51, 51)], labels[idx(10, 10)]);
+        let island = labels[idx(200, 200)];
+        assert_ne!(island, labels[idx(10, 10)]);
+        assert_eq!(labels.iter().filter(|&&l| l == island).count(), 4);
+        assert_ne!(labels[idx(250, 10)], labels[idx(10, 10)]);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::params::GenerationParams;
+use crate::state::{FluvialFeature, IceCover, SoilOrder, WorldState};
+use crate::systems::{biomes, erosion_hydrology};
+
+/// Slope in metres at which residual soil thins to 1/e of its weathered depth.
+const SLOPE_THINNING_M: f32 = 80.0;
+/// Residual soil thinner than this, or on slopes steeper than
+/// `LEPTOSOL_SLOPE_M`, stays a stony leptosol.
+const LEPTOSOL_DEPTH_M: f32 = 0.12;
+const LEPTOSOL_SLOPE_M: f32 = 150.0;
+const WATERLOGGED_SLOPE_M: f32 = 10.0;
+
+/// Soil depth, soil order and a soil-based fertility that replaces the
+/// provisional rainfall proxy from the biome step.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let max_depth = params.ecology.max_soil_depth_m;
+    let mean_rain = erosion_hydrology::mean_land_rainfall(state, sea_level);
+    let inv_mean = if mean_rain > 0.0 {
+        1.0 / mean_rain
+    } else {
+        0.0
+    };
+
+    for y in 0..state.height {
+        for x in 0..state.width {
+            if *state.elevation.get(x, y) <= sea_level || *state.lake_id.get(x, y) > 0 {
+                *state.soil.get_mut(x, y) = SoilOrder::None;
+                *state.soil_depth.get_mut(x, y) = 0.0;
+                *state.fertility.get_mut(x, y) = 0.0;
+                continue;
+            }
+
+            let temp = *state.temperature.get(x, y);
+            let rel_rain = *state.rainfall.get(x, y) * inv_mean;
+            let slope = biomes::slope_m(state, x, y, sea_level);
+            let rock = state
+                .strata
+                .get(x, y)
+                .first()
+                .map_or(*state.rock_type.get(x, y), |layer| layer.rock);
+            let fluvial = *state.fluvial_feature.get(x, y);
+            let glacier = *state.ice_cover.get(x, y) == IceCover::Glacier;
+            let channel = ((*state.accumulation.get(x, y) + 1.0).log10() / 4.0).clamp(0.0, 1.0);
+            // Flat ground that is either very rainy or collects a lot of runoff
+            // stays saturated.
+            let waterlogged = slope < WATERLOGGED_SLOPE_M && (rel_rain >= 1.2 || channel >= 0.6);
+
+            // Residual soil from climate-driven weathering, thinned on slopes,
+            // plus alluvium where rivers deposit sediment.
+            let warm = ((temp + 5.0) / 35.0).clamp(0.0, 1.0);
+            let moist = 1.0 - (-rel_rain).exp();
+            let rock = state.geology_catalogue.rock(rock);
+            let weathered = (0.3 + 2.7 * warm * moist) * rock.weathering;
+            let residual = weathered * (-slope / SLOPE_THINNING_M).exp();
+            let alluvium = match fluvial {
+                FluvialFeature::Floodplain | FluvialFeature::MeanderBelt => 2.0,
+                FluvialFeature::Delta => 3.0,
+                FluvialFeature::Estuary => 1.0,
+                FluvialFeature::None => 0.5 * channel,
+            };
+            let depth = if glacier {
+                0.05
+            } else {
+                (residual + alluvium).clamp(0.0, max_depth)
+            };
+
+            let order = if glacier {
+                SoilOrder::Leptosol
+            } else if fluvial != FluvialFeature::None {
+                SoilOrder::Alluvium
+            } else if rock.volcanic && rel_rain >= 0.3 {
+                SoilOrder::Andosol
+            } else if residual < LEPTOSOL_DEPTH_M || slope >= LEPTOSOL_SLOPE_M {
+                SoilOrder::Leptosol
+            } else if waterlogged && temp < 12.0 {
+                SoilOrder::Peat
+            } else if rel_rain < 0.5 && temp > 0.0 {
+                SoilOrder::Aridisol
+            } else if temp >= 20.0 && rel_rain >= 1.0 {
+                SoilOrder::Laterite
+            } else if temp < 8.0 && rel_rain >= 0.8 {
+                SoilOrder::Podzol
+            } else if temp < 18.0 && rel_rain < 1.2 {
+                SoilOrder::Chernozem
+            } else {
+                SoilOrder::Cambisol
+            };
+
+            *state.soil.get_mut(x, y) = order;
+            *state.soil_depth.get_mut(x, y) = depth;
+
+            let depth_f = (depth / (depth + 0.5)).sqrt();
+            let moisture_f = 0.4 + 0.6 * moist;
+            let temp_f = ((temp + 5.0) / 25.0).clamp(0.2, 1.0);
+            *state.fertility.get_mut(x, y) =
+                (base_fertility(order) * depth_f * moisture_f * temp_f).clamp(0.0, 1.0);
+        }
+    }
+}
+
+fn base_fertility(order: SoilOrder) -> f32 {
+    match order {
+        SoilOrder::None => 0.0,
+        SoilOrder::Leptosol => 0.15,
+        SoilOrder::Aridisol => 0.2,
+        SoilOrder::Podzol => 0.3,
+        SoilOrder::Peat => 0.35,
+        SoilOrder::Laterite => 0.35,
+        SoilOrder::Cambisol => 0.65,
+        SoilOrder::Andosol => 0.85,
+        SoilOrder::Alluvium => 0.9,
+        SoilOrder::Chernozem => 0.95,
+    }
+}
+
+pub fn soil_color(order: SoilOrder) -> [u8; 3] {
+    match order {
+        SoilOrder::None => [20, 20, 20],
+        SoilOrder::Leptosol => [150, 146, 140],
+        SoilOrder::Peat => [60, 48, 36],
+        SoilOrder::Alluvium => [170, 140, 100],
+        SoilOrder::Andosol => [70, 62, 64],
+        SoilOrder::Aridisol => [222, 196, 150],
+        SoilOrder::Laterite => [176, 72, 40],
+        SoilOrder::Podzol => [190, 180, 170],
+        SoilOrder::Chernozem => [40, 34, 30],
+        SoilOrder::Cambisol => [140, 100, 66],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn soil_orders_follow_climate_and_drainage() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        let granite = state
+            .geology_catalogue
+            .find_rock("Granite")
+            .expect("built-in rock");
+        state.elevation.fill(0.6);
+        state.rock_type.fill(granite);
+        state.rainfall.fill(1.0);
+        state.temperature.fill(25.0);
+        *state.temperature.get_mut(20, 20) = 10.0;
+        *state.rainfall.get_mut(50, 50) = 0.2;
+        *state.fluvial_feature.get_mut(30, 30) = FluvialFeature::Floodplain;
+        *state.ice_cover.get_mut(40, 40) = IceCover::Glacier;
+
+        run(&mut state, &params);
+
+        let soil = |x, y| *state.soil.get(x, y);
+        assert_eq!(soil(10, 10), SoilOrder::Laterite);
+        assert_eq!(soil(20, 20), SoilOrder::Chernozem);
+        assert_eq!(soil(30, 30), SoilOrder::Alluvium);
+        assert_eq!(soil(40, 40), SoilOrder::Leptosol);
+        assert_eq!(soil(50, 50), SoilOrder::Aridisol);
+        assert!(*state.soil_depth.get(30, 30) > *state.soil_depth.get(10, 10));
+        assert!(*state.fertility.get(20, 20) > *state.fertility.get(10, 10));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::biome_table::WOODY_CANOPY_M;
+use crate::params::GenerationParams;
+use crate::state::{IceCover, WorldState};
+use crate::systems::base_fields::{fbm, seed_offset};
+use crate::systems::{biomes, erosion_hydrology};
+
+/// Years for a regrowing stand to reach ~63% of its mature canopy height.
+const REGROWTH_YEARS: f32 = 30.0;
+/// Soil depth in metres at which rooting-depth limitation halves growth.
+const HALF_ROOTING_DEPTH_M: f32 = 0.4;
+/// Slope in metres at which growth is halved.
+const HALF_GROWTH_SLOPE_M: f32 = 150.0;
+
+/// Leaf area index, fractional vegetation cover, canopy height and stand age.
+/// Biome potentials come from the biome table via the tile's blend weights and
+/// are limited by soil depth, slope, moisture and time since disturbance.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let sea_level = params.base.sea_level;
+    let mean_rain = erosion_hydrology::mean_land_rainfall(state, sea_level);
+    let inv_mean = if mean_rain > 0.0 {
+        1.0 / mean_rain
+    } else {
+        0.0
+    };
+    let width = state.width as f32;
+    let height = state.height as f32;
+    let age_seed = seed_offset(params.seed, 1212);
+
+    for y in 0..state.height {
+        for x in 0..state.width {
+            let mut max_lai = 0.0;
+            let mut potential_h = 0.0;
+            for (biome, w) in state.biome_blend.get(x, y).iter() {
+                let def = state.biome_table.def(biome);
+                max_lai += def.max_lai * w;
+                potential_h += def.canopy_height_m * w;
+            }
+
+            let land = *state.elevation.get(x, y) > sea_level && *state.lake_id.get(x, y) == 0;
+            if !land || max_lai <= 0.0 {
+                *state.leaf_area_index.get_mut(x, y) = 0.0;
+                *state.vegetation_density.get_mut(x, y) = 0.0;
+                *state.canopy_height.get_mut(x, y) = 0.0;
+                *state.forest_age.get_mut(x, y) = 0.0;
+                continue;
+            }
+
+            let soil = *state.soil_depth.get(x, y);
+            let soil_f = soil / (soil + HALF_ROOTING_DEPTH_M);
+            let slope = biomes::slope_m(state, x, y, sea_level);
+            let slope_f = 1.0 / (1.0 + slope / HALF_GROWTH_SLOPE_M);
+            let moisture_f = 0.5 + 0.5 * (1.0 - (-*state.rainfall.get(x, y) * inv_mean).exp());
+            let ice_f = match *state.ice_cover.get(x, y) {
+                IceCover::Glacier => 0.0,
+                IceCover::Snowpack => 0.3,
+                _ => 1.0,
+            };
+
+            // Exponential waiting time since the last stand-replacing event,
+            // drawn from patchy noise so neighbouring tiles share a history.
+            // Steep ground is disturbed more often by slides and windthrow,
+            // fire-prone ground as often as it burns, and a simulated burn
+            // scar resets the stand outright.
+            let age = if potential_h >= WOODY_CANOPY_M {
+                let u = fbm(
+                    age_seed,
+                    x as f32 / width,
+                    y as f32 / height,
+                    3,
+                    params.ecology.disturbance_patch_scale,
+                );
+                // fbm clusters around 0.5; stretch it towards a uniform spread.
+                let u = ((u - 0.5) * 2.5 + 0.5).clamp(0.0, 0.999);
+                let fire_interval = *state.fire_return_interval.get(x, y);
+                let mut mean_age = params.ecology.mean_stand_age_years * slope_f;
+                if fire_interval > 0.0 {
+                    mean_age = mean_age.min(fire_interval);
+                }
+                let age = -mean_age * (1.0 - u).ln();
+                let scar = *state.burn_scar_age.get(x, y);
+                if scar < params.fire.simulated_years as f32 {
+                    age.min(scar)
+                } else {
+                    age
+                }
+            } else {
+                0.0
+            };
+            let maturity = if potential_h >= WOODY_CANOPY_M {
+                1.0 - (-age / REGROWTH_YEARS).exp()
+            } else {
+                1.0
+            };
+
+            let lai = max_lai * soil_f * slope_f * moisture_f * ice_f * (0.4 + 0.6 * maturity);
+            *state.leaf_area_index.get_mut(x, y) = lai;
+            // Beer-Lambert ground cover with a typical extinction coefficient.
+            *state.vegetation_density.get_mut(x, y) = 1.0 - (-0.5 * lai).exp();
+            *state.canopy_height.get_mut(x, y) =
+                potential_h * soil_f.sqrt() * slope_f * ice_f * maturity;
+            *state.forest_age.get_mut(x, y) = age;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::BiomeBlend;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn growth_follows_soil_and_disturbance() {
+        let params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        let mut state = WorldState::new(params.clone());
+        let forest = state
+            .biome_table
+            .find("Temperate Forest")
+            .expect("built-in biome");
+        state.elevation.fill(0.6);
+        state.rainfall.fill(1.0);
+        state.biome_blend.fill(BiomeBlend::single(forest));
+        state.soil_depth.fill(2.0);
+        state.burn_scar_age.fill(f32::MAX);
+        *state.soil_depth.get_mut(20, 20) = 0.1;
+        *state.burn_scar_age.get_mut(30, 30) = 5.0;
+        *state.elevation.get_mut(100, 100) = 0.4;
+
+        run(&mut state, &params);
+
+        let lai = |x, y| *state.leaf_area_index.get(x, y);
+        let canopy = |x, y| *state.canopy_height.get(x, y);
+        assert!(lai(10, 10) > lai(20, 20));
+        assert!(canopy(10, 10) > canopy(20, 20));
+        assert!(*state.forest_age.get(30, 30) <= 5.0);
+        // Five years of regrowth reach under a fifth of the mature canopy.
+        let mature = state.biome_table.def(forest).canopy_height_m;
+        assert!(canopy(30, 30) < 0.2 * mature);
+        assert_eq!(lai(100, 100), 0.0);
+        assert_eq!(canopy(100, 100), 0.0);
+    }
+}
@@ -0,0 +1,268 @@
+use std::f32::consts::TAU;
+
+use crate::params::GenerationParams;
+use crate::rng::SplitMix64;
+use crate::state::{FaultKind, Volcano, VolcanoActivity, VolcanoKind, WorldState};
+use crate::systems::base_fields::{seed_offset, ELEVATION_SPAN_M};
+
+/// Cone radius in tiles and height in metres of a stratovolcano.
+const STRATO_RADIUS: (f32, f32) = (3.0, 6.0);
+const STRATO_HEIGHT_M: (f32, f32) = (1200.0, 3000.0);
+const SHIELD_RADIUS: (f32, f32) = (8.0, 14.0);
+/// Summit above sea level of the youngest shield in a track.
+const SHIELD_SUMMIT_M: (f32, f32) = (1500.0, 4000.0);
+/// How much lower each older shield of a track stands, as its plate cools
+/// and sinks.
+const SHIELD_SUBSIDENCE_M: f32 = 900.0;
+const MIN_CONE_M: f32 = 300.0;
+/// Lava fields reach this many cone radii from the vent.
+const LAVA_REACH: f32 = 1.5;
+
+/// Lava thickness at the vent, metres; flows thin to nothing at the edge of
+/// the field.
+fn vent_lava_m(kind: VolcanoKind) -> f32 {
+    match kind {
+        VolcanoKind::Stratovolcano => 80.0,
+        VolcanoKind::Shield => 150.0,
+    }
+}
+
+/// Cone height at `r` cone radii from the vent, as a share of the summit.
+fn cone_profile(kind: VolcanoKind, r: f32) -> f32 {
+    let r = r.clamp(0.0, 1.0);
+    match kind {
+        VolcanoKind::Stratovolcano => (1.0 - r).powf(1.6),
+        VolcanoKind::Shield => (1.0 - r * r).powf(1.5),
+    }
+}
+
+/// Stratovolcanoes along the arcs behind boundary thrusts and shield
+/// volcanoes in tracks over seeded hotspots, oldest farthest along the
+/// plate's motion. Cones are built into `elevation`, so hotspots in the
+/// ocean raise island chains whose older members have sunk to seamounts.
+/// Lava fields around each vent are recorded in `lava_thickness_m` for the
+/// geology step to cap the strata with.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let vp = &params.volcanoes;
+    let sea_level = params.base.sea_level;
+    let mut rng = SplitMix64::new(seed_offset(params.seed, 2400));
+    let mut volcanoes: Vec<Volcano> = Vec::new();
+
+    let arc_spacing = vp.arc_spacing_tiles.max(1.0);
+    for fault in state.faults.iter().filter(|f| f.kind == FaultKind::Reverse) {
+        let mut carry = arc_spacing * 0.5;
+        for w in fault.points.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let len = (b[0] - a[0]).hypot(b[1] - a[1]);
+            if len <= 1e-6 {
+                continue;
+            }
+            let dir = [(b[0] - a[0]) / len, (b[1] - a[1]) / len];
+            // Left of the trace, with y pointing down the map.
+            let left = [dir[1], -dir[0]];
+            let side = if fault.upthrown_left { 1.0 } else { -1.0 };
+            while carry <= len {
+                let vent = [
+                    a[0] + dir[0] * carry + left[0] * side * vp.arc_offset_tiles,
+                    a[1] + dir[1] * carry + left[1] * side * vp.arc_offset_tiles,
+                ];
+                carry += arc_spacing;
+                let Some((x, y)) = tile_at(state, vent) else {
+                    continue;
+                };
+                if volcanoes.iter().any(|v| {
+                    (v.x as f32 - x as f32).hypot(v.y as f32 - y as f32) < arc_spacing * 0.5
+                }) {
+                    continue;
+                }
+                let roll = rng.next_f32();
+                let activity = if roll < vp.active_share {
+                    VolcanoActivity::Active
+                } else if roll < vp.active_share + vp.dormant_share {
+                    VolcanoActivity::Dormant
+                } else {
+                    VolcanoActivity::Extinct
+                };
+                let depth_m = (sea_level - *state.elevation.get(x, y)).max(0.0) * ELEVATION_SPAN_M;
+                volcanoes.push(Volcano {
+                    id: volcanoes.len() as u32 + 1,
+                    kind: VolcanoKind::Stratovolcano,
+                    activity,
+                    x,
+                    y,
+                    cone_m: depth_m + rng.gen_range_f32(STRATO_HEIGHT_M.0, STRATO_HEIGHT_M.1),
+                    radius_tiles: rng.gen_range_f32(STRATO_RADIUS.0, STRATO_RADIUS.1),
+                    hotspot: 0,
+                    oceanic: depth_m > 0.0,
+                });
+            }
+            carry -= len;
+        }
+    }
+
+    for hotspot in 1..=vp.hotspots {
+        let start = [
+            rng.gen_range_f32(0.0, state.width as f32 - 1.0),
+            rng.gen_range_f32(0.0, state.height as f32 - 1.0),
+        ];
+        let motion = rng.gen_range_f32(0.0, TAU);
+        let summit_m = rng.gen_range_f32(SHIELD_SUMMIT_M.0, SHIELD_SUMMIT_M.1);
+        for k in 0..vp.chain_length {
+            let along = k as f32 * vp.chain_spacing_tiles;
+            let vent = [
+                start[0] + motion.cos() * along,
+                start[1] + motion.sin() * along,
+            ];
+            let Some((x, y)) = tile_at(state, vent) else {
+                break;
+            };
+            let activity = match k {
+                0 => VolcanoActivity::Active,
+                1 => VolcanoActivity::Dormant,
+                _ => VolcanoActivity::Extinct,
+            };
+            let depth_m = (sea_level - *state.elevation.get(x, y)).max(0.0) * ELEVATION_SPAN_M;
+            let summit = summit_m - k as f32 * SHIELD_SUBSIDENCE_M;
+            volcanoes.push(Volcano {
+                id: volcanoes.len() as u32 + 1,
+                kind: VolcanoKind::Shield,
+                activity,
+                x,
+                y,
+                cone_m: (depth_m + summit).max(MIN_CONE_M),
+                radius_tiles: rng.gen_range_f32(SHIELD_RADIUS.0, SHIELD_RADIUS.1),
+                hotspot,
+                oceanic: depth_m > 0.0,
+            });
+        }
+    }
+
+    state.volcano_id.fill(0);
+    state.lava_thickness_m.fill(0.0);
+    let bases: Vec<f32> = volcanoes
+        .iter()
+        .map(|v| *state.elevation.get(v.x, v.y))
+        .collect();
+    for (v, &base) in volcanoes.iter().zip(&bases) {
+        let reach = v.radius_tiles * LAVA_REACH;
+        let r = reach.ceil() as isize;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (nx, ny) = (v.x as isize + dx, v.y as isize + dy);
+                if !state.elevation.in_bounds(nx, ny) {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                let dist = (dx as f32).hypot(dy as f32);
+                if dist < v.radius_tiles {
+                    let cone = base
+                        + v.cone_m * cone_profile(v.kind, dist / v.radius_tiles) / ELEVATION_SPAN_M;
+                    let h = state.elevation.get_mut(nx, ny);
+                    *h = h.max(cone.min(1.0));
+                }
+                let lava = vent_lava_m(v.kind) * (1.0 - dist / reach);
+                if lava > *state.lava_thickness_m.get(nx, ny) {
+                    *state.lava_thickness_m.get_mut(nx, ny) = lava;
+                    *state.volcano_id.get_mut(nx, ny) = v.id;
+                }
+            }
+        }
+    }
+    state.volcanoes = volcanoes;
+}
+
+fn tile_at(state: &WorldState, p: [f32; 2]) -> Option<(usize, usize)> {
+    let (x, y) = (p[0].round() as isize, p[1].round() as isize);
+    state
+        .elevation
+        .in_bounds(x, y)
+        .then_some((x as usize, y as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Fault;
+    use crate::MapSizePreset;
+
+    fn flat_state(params: &GenerationParams) -> WorldState {
+        let mut state = WorldState::new(params.clone());
+        state.elevation.fill(params.base.sea_level + 0.1);
+        state
+    }
+
+    #[test]
+    fn arc_vents_lie_on_upthrown_side_of_reverse_faults() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.volcanoes.hotspots = 0;
+        let mut state = flat_state(&params);
+        let trace = |kind, upthrown_left, y| Fault {
+            id: 0,
+            kind,
+            points: vec![[20.0, y], [236.0, y]],
+            throw_m: 500.0,
+            upthrown_left,
+        };
+        // Walking east with y down, left is north.
+        state.faults = vec![
+            trace(FaultKind::Reverse, true, 80.0),
+            trace(FaultKind::Reverse, false, 160.0),
+            trace(FaultKind::Normal, true, 220.0),
+        ];
+
+        run(&mut state, &params);
+
+        let offset = params.volcanoes.arc_offset_tiles.round() as usize;
+        assert!(!state.volcanoes.is_empty());
+        for v in &state.volcanoes {
+            assert_eq!(v.kind, VolcanoKind::Stratovolcano);
+            assert!(
+                v.y == 80 - offset || v.y == 160 + offset,
+                "vent at y {} is off the raised blocks",
+                v.y
+            );
+            assert!((20..=236).contains(&v.x));
+        }
+        assert!(state.volcanoes.iter().any(|v| v.y == 80 - offset));
+        assert!(state.volcanoes.iter().any(|v| v.y == 160 + offset));
+    }
+
+    #[test]
+    fn hotspot_chains_age_along_the_track() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.volcanoes.hotspots = 4;
+        let mut state = flat_state(&params);
+
+        run(&mut state, &params);
+
+        let spacing = params.volcanoes.chain_spacing_tiles;
+        for hotspot in 1..=params.volcanoes.hotspots {
+            let chain: Vec<&Volcano> = state
+                .volcanoes
+                .iter()
+                .filter(|v| v.hotspot == hotspot)
+                .collect();
+            assert!(!chain.is_empty());
+            assert_eq!(chain[0].activity, VolcanoActivity::Active);
+            for (k, v) in chain.iter().enumerate().skip(1) {
+                let prev = chain[k - 1];
+                let expected = if k == 1 {
+                    VolcanoActivity::Dormant
+                } else {
+                    VolcanoActivity::Extinct
+                };
+                assert_eq!(v.activity, expected);
+                assert!(v.id > prev.id);
+                assert!(v.cone_m <= prev.cone_m);
+                let step = (v.x as f32 - prev.x as f32).hypot(v.y as f32 - prev.y as f32);
+                assert!((step - spacing).abs() <= 1.5, "step {step} vs {spacing}");
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+
+use crate::params::{GenerationParams, WildernessParams};
+use crate::state::{Alignment, Savagery, WorldState};
+use crate::systems::base_fields::{fbm, lerp, seed_offset};
+
+const DIRS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Land this far above sea level (normalized elevation, ~600 m) still counts
+/// as lowland.
+const LOWLAND_RISE: f32 = 0.08;
+/// Remoteness reaches ~63% this far from fertile lowland, as a fraction of
+/// the map's larger side.
+const REMOTENESS_SCALE: f32 = 1.0 / 12.0;
+
+/// Savagery and good/evil fields: seeded noise pushed towards the extremes
+/// away from fertile lowland, then rank-normalized against land tiles so the
+/// coverage parameters hold exactly.
+pub fn run(state: &mut WorldState, params: &GenerationParams) {
+    let wp = &params.wilderness;
+    let remoteness = remoteness(state, params);
+    let width = state.width as f32;
+    let height = state.height as f32;
+    let savage_seed = seed_offset(params.seed, 1515);
+    let align_seed = seed_offset(params.seed, 1616);
+    let bias = wp.remoteness_bias.clamp(0.0, 1.0);
+
+    let n = state.width * state.height;
+    let mut savage_raw = vec![0.0f32; n];
+    let mut align_raw = vec![0.0f32; n];
+    for (x, y) in state.elevation.iter_coords() {
+        let idx = state.elevation.idx(x, y);
+        let fx = x as f32 / width;
+        let fy = y as f32 / height;
+        let r = remoteness[idx];
+        savage_raw[idx] = lerp(fbm(savage_seed, fx, fy, 4, wp.patch_scale), r, bias);
+        // Good and evil are both tails of one field; remoteness widens it so
+        // either extreme is likelier far from settled land.
+        let spread = lerp(1.0, r, bias);
+        align_raw[idx] = (fbm(align_seed, fx, fy, 4, wp.patch_scale) - 0.5) * spread;
+    }
+
+    let sea_level = params.base.sea_level;
+    let land: Vec<bool> = state
+        .elevation
+        .as_slice()
+        .iter()
+        .map(|&e| e > sea_level)
+        .collect();
+    let savage = land_rank(&savage_raw, &land);
+    let align = land_rank(&align_raw, &land);
+    for i in 0..n {
+        state.savagery.as_mut_slice()[i] = savage[i];
+        state.alignment.as_mut_slice()[i] = align[i] * 2.0 - 1.0;
+    }
+}
+
+/// 0 on fertile lowland, rising towards 1 with BFS distance from it. Fertile
+/// means at least the median fertility of lowland tiles, so it adapts to dry
+/// or cold worlds; with no fertile lowland at all everything is remote.
+fn remoteness(state: &WorldState, params: &GenerationParams) -> Vec<f32> {
+    let sea_level = params.base.sea_level;
+    let n = state.width * state.height;
+    let lowland = |x: usize, y: usize| {
+        let e = *state.elevation.get(x, y);
+        e > sea_level && e <= sea_level + LOWLAND_RISE && *state.lake_id.get(x, y) == 0
+    };
+
+    let mut lowland_fertility: Vec<f32> = state
+        .elevation
+        .iter_coords()
+        .filter(|&(x, y)| lowland(x, y))
+        .map(|(x, y)| *state.fertility.get(x, y))
+        .filter(|&f| f > 0.0)
+        .collect();
+    if lowland_fertility.is_empty() {
+        return vec![1.0; n];
+    }
+    lowland_fertility.sort_by(f32::total_cmp);
+    let fertile = lowland_fertility[lowland_fertility.len() / 2];
+
+    let mut dist = vec![u32::MAX; n];
+    let mut q = VecDeque::new();
+    for (x, y) in state.elevation.iter_coords() {
+        if lowland(x, y) && *state.fertility.get(x, y) >= fertile {
+            dist[state.elevation.idx(x, y)] = 0;
+            q.push_back((x, y));
+        }
+    }
+    while let Some((x, y)) = q.pop_front() {
+        let next = dist[state.elevation.idx(x, y)] + 1;
+        for (dx, dy) in DIRS_8 {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if !state.elevation.in_bounds(nx, ny) {
+                continue;
+            }
+            let nidx = state.elevation.idx(nx as usize, ny as usize);
+            if dist[nidx] == u32::MAX {
+                dist[nidx] = next;
+                q.push_back((nx as usize, ny as usize));
+            }
+        }
+    }
+
+    let scale = state.width.max(state.height) as f32 * REMOTENESS_SCALE;
+    dist.iter()
+        .map(|&d| 1.0 - (-(d as f32) / scale).exp())
+        .collect()
+}
+
+/// Each value's share of land values strictly below it (0..1). Water tiles
+/// are placed on the same land distribution.
+fn land_rank(raw: &[f32], land: &[bool]) -> Vec<f32> {
+    let mut sorted: Vec<f32> = raw
+        .iter()
+        .zip(land)
+        .filter(|(_, &l)| l)
+        .map(|(&v, _)| v)
+        .collect();
+    if sorted.is_empty() {
+        return vec![0.5; raw.len()];
+    }
+    sorted.sort_by(f32::total_cmp);
+    let count = sorted.len() as f32;
+    raw.iter()
+        .map(|&v| sorted.partition_point(|&s| s < v) as f32 / count)
+        .collect()
+}
+
+pub fn savagery_class(savagery: f32, params: &WildernessParams) -> Savagery {
+    if savagery >= 1.0 - params.savage_coverage {
+        Savagery::Savage
+    } else if savagery < params.calm_coverage {
+        Savagery::Calm
+    } else {
+        Savagery::Wild
+    }
+}
+
+pub fn alignment_class(alignment: f32, params: &WildernessParams) -> Alignment {
+    let share = (alignment + 1.0) * 0.5;
+    if share >= 1.0 - params.good_coverage {
+        Alignment::Good
+    } else if share < params.evil_coverage {
+        Alignment::Evil
+    } else {
+        Alignment::Neutral
+    }
+}
+
+/// The Dwarf Fortress region adjective for a savagery/alignment pair; calm
+/// neutral land has none.
+pub fn region_adjective(savagery: Savagery, alignment: Alignment) -> Option<&'static str> {
+    match (alignment, savagery) {
+        (Alignment::Good, Savagery::Calm) => Some("Serene"),
+        (Alignment::Good, Savagery::Wild) => Some("Mirthful"),
+        (Alignment::Good, Savagery::Savage) => Some("Joyous"),
+        (Alignment::Neutral, Savagery::Calm) => None,
+        (Alignment::Neutral, Savagery::Wild) => Some("Wild"),
+        (Alignment::Neutral, Savagery::Savage) => Some("Untamed"),
+        (Alignment::Evil, Savagery::Calm) => Some("Sinister"),
+        (Alignment::Evil, Savagery::Wild) => Some("Haunted"),
+        (Alignment::Evil, Savagery::Savage) => Some("Terrifying"),
+    }
+}
+
+/// Dominant biome name with the tile's region adjective, e.g. "Haunted
+/// Boreal Forest".
+pub fn biome_name(state: &WorldState, x: usize, y: usize) -> String {
+    let name = &state.biome_table.def(*state.biome.get(x, y)).name;
+    let wp = &state.params.wilderness;
+    let savagery = savagery_class(*state.savagery.get(x, y), wp);
+    let alignment = alignment_class(*state.alignment.get(x, y), wp);
+    match region_adjective(savagery, alignment) {
+        Some(adjective) => format!("{adjective} {name}"),
+        None => name.clone(),
+    }
+}
+
+pub fn wilderness_color(savagery: Savagery, alignment: Alignment) -> [u8; 3] {
+    let base: [u8; 3] = match alignment {
+        Alignment::Good => [90, 200, 230],
+        Alignment::Neutral => [120, 170, 90],
+        Alignment::Evil => [150, 60, 140],
+    };
+    let k = match savagery {
+        Savagery::Calm => 0.55,
+        Savagery::Wild => 0.8,
+        Savagery::Savage => 1.0,
+    };
+    base.map(|c| (f32::from(c) * k) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapSizePreset;
+
+    #[test]
+    fn savagery_rises_with_remoteness() {
+        let mut params = GenerationParams {
+            size: MapSizePreset::S256,
+            ..GenerationParams::default()
+        };
+        params.wilderness.remoteness_bias = 1.0;
+        let mut state = WorldState::new(params.clone());
+        // Fertile lowland along the west edge, barren upland to the east.
+        state.elevation.fill(0.7);
+        for y in 0..state.height {
+            for x in 0..16 {
+                *state.elevation.get_mut(x, y) = 0.55;
+                *state.fertility.get_mut(x, y) = 1.0;
+            }
+        }
+
+        let remote = remoteness(&state, &params);
+        let r = |x| remote[state.elevation.idx(x, 100)];
+        assert_eq!(r(8), 0.0);
+        assert!(r(40) < r(120) && r(120) < r(240));
+
+        run(&mut state, &params);
+        let savagery = |x| *state.savagery.get(x, 100);
+        assert!(savagery(8) < savagery(40));
+        assert!(savagery(40) < savagery(120));
+        assert!(savagery(120) < savagery(240));
+    }
+}
@@ -2,7 +2,14 @@ use std::path::PathBuf;
 
 use eframe::egui;
 use worldgen_core::export::export_snapshot;
-use worldgen_core::state::{Biome, GeologicProvince, RiverClass, RockType, Step};
+use worldgen_core::rng::hash_u64;
+use worldgen_core::state::{
+    CaveKind, CaveLayer, CaveOpeningKind, CreatureSiteKind, GeologicProvince, Hazard, Mineral,
+    RiverClass, RockType, Step, VolcanoActivity,
+};
+use worldgen_core::systems::{
+    bathymetry, caves, cryosphere, hydro_finalize, life_zones, regions, soils, wilderness,
+};
 use worldgen_core::{
     run_all_steps, run_next_step, run_step, GenerationParams, MapSizePreset, WorldState,
 };
@@ -21,45 +28,91 @@ enum ViewLayer {
     Elevation,
     Temperature,
     Rainfall,
+    OceanZone,
+    WaveExposure,
     Accumulation,
     Discharge,
     FlowDir,
     RiverClass,
+    RiverNavigation,
+    FluvialFeature,
     Lake,
     OceanMask,
+    Ice,
+    Glacier,
     Biome,
+    BiomeBlend,
+    Whittaker,
+    Holdridge,
+    FireFrequency,
+    BurnScarAge,
+    FireReturnInterval,
     Fertility,
     Province,
     RockType,
-    MineralIron,
-    MineralCopper,
-    MineralGold,
-    MineralTin,
-    MineralCoal,
-    MineralGem,
+    Mineral,
+    Faults,
+    Volcanoes,
+    Caves,
+    Hazard,
+    Soil,
+    SoilDepth,
+    LeafAreaIndex,
+    VegetationDensity,
+    CanopyHeight,
+    ForestAge,
+    Savagery,
+    Alignment,
+    Wilderness,
+    Habitat,
+    Regions,
+    Mountains,
 }
 
 impl ViewLayer {
-    const ALL: [Self; 19] = [
+    const ALL: [Self; 42] = [
         Self::Elevation,
         Self::Temperature,
         Self::Rainfall,
+        Self::OceanZone,
+        Self::WaveExposure,
         Self::Accumulation,
         Self::Discharge,
         Self::FlowDir,
         Self::RiverClass,
+        Self::RiverNavigation,
+        Self::FluvialFeature,
         Self::Lake,
         Self::OceanMask,
+        Self::Ice,
+        Self::Glacier,
         Self::Biome,
+        Self::BiomeBlend,
+        Self::Whittaker,
+        Self::Holdridge,
+        Self::FireFrequency,
+        Self::BurnScarAge,
+        Self::FireReturnInterval,
         Self::Fertility,
         Self::Province,
         Self::RockType,
-        Self::MineralIron,
-        Self::MineralCopper,
-        Self::MineralGold,
-        Self::MineralTin,
-        Self::MineralCoal,
-        Self::MineralGem,
+        Self::Mineral,
+        Self::Faults,
+        Self::Volcanoes,
+        Self::Caves,
+        Self::Hazard,
+        Self::Soil,
+        Self::SoilDepth,
+        Self::LeafAreaIndex,
+        Self::VegetationDensity,
+        Self::CanopyHeight,
+        Self::ForestAge,
+        Self::Savagery,
+        Self::Alignment,
+        Self::Wilderness,
+        Self::Habitat,
+        Self::Regions,
+        Self::Mountains,
     ];
 
     fn label(self) -> &'static str {
@@ -67,22 +120,45 @@ impl ViewLayer {
             Self::Elevation => "Elevation",
             Self::Temperature => "Temperature",
             Self::Rainfall => "Rainfall",
+            Self::OceanZone => "Ocean Zone",
+            Self::WaveExposure => "Wave Exposure",
             Self::Accumulation => "Accumulation",
             Self::Discharge => "Discharge",
             Self::FlowDir => "Flow Dir",
             Self::RiverClass => "River Class",
+            Self::RiverNavigation => "River Navigation",
+            Self::FluvialFeature => "Fluvial Features",
             Self::Lake => "Lake ID",
             Self::OceanMask => "Ocean Mask",
+            Self::Ice => "Ice Cover",
+            Self::Glacier => "Glacier Thickness",
             Self::Biome => "Biome",
+            Self::BiomeBlend => "Biome Blend",
+            Self::Whittaker => "Whittaker Biome",
+            Self::Holdridge => "Holdridge Zone",
+            Self::FireFrequency => "Fire Frequency",
+            Self::BurnScarAge => "Burn Scar Age",
+            Self::FireReturnInterval => "Fire Return Interval",
             Self::Fertility => "Fertility",
             Self::Province => "Geologic Province",
             Self::RockType => "Rock Type",
-            Self::MineralIron => "Mineral Iron",
-            Self::MineralCopper => "Mineral Copper",
-            Self::MineralGold => "Mineral Gold",
-            Self::MineralTin => "Mineral Tin",
-            Self::MineralCoal => "Mineral Coal",
-            Self::MineralGem => "Mineral Gem",
+            Self::Mineral => "Mineral",
+            Self::Faults => "Faults",
+            Self::Volcanoes => "Volcanoes",
+            Self::Caves => "Caves",
+            Self::Hazard => "Hazard",
+            Self::Soil => "Soil Order",
+            Self::SoilDepth => "Soil Depth",
+            Self::LeafAreaIndex => "Leaf Area Index",
+            Self::VegetationDensity => "Vegetation Density",
+            Self::CanopyHeight => "Canopy Height",
+            Self::ForestAge => "Forest Age",
+            Self::Savagery => "Savagery",
+            Self::Alignment => "Good / Evil",
+            Self::Wilderness => "Wilderness",
+            Self::Habitat => "Habitat",
+            Self::Regions => "Regions",
+            Self::Mountains => "Mountains",
         }
     }
 }
@@ -93,6 +169,17 @@ struct WorldgenApp {
     view_layer: ViewLayer,
     texture: Option<egui::TextureHandle>,
     last_error: Option<String>,
+    biome_defs_path: String,
+    creature_catalogue_path: String,
+    languages_path: String,
+    geology_catalogue_path: String,
+    habitat_species: usize,
+    /// Index of the catalogue mineral shown by the mineral view.
+    mineral: usize,
+    /// Index of the depth band shown by the cave view.
+    cave_layer: usize,
+    /// Layer shown by the hazard view.
+    hazard: Hazard,
 }
 
 impl Default for WorldgenApp {
@@ -105,6 +192,14 @@ impl Default for WorldgenApp {
             view_layer: ViewLayer::Elevation,
             texture: None,
             last_error: None,
+            biome_defs_path: String::new(),
+            creature_catalogue_path: String::new(),
+            languages_path: String::new(),
+            geology_catalogue_path: String::new(),
+            habitat_species: 0,
+            mineral: 0,
+            cave_layer: 0,
+            hazard: Hazard::Earthquake,
         }
     }
 }
@@ -152,6 +247,42 @@ impl eframe::App for WorldgenApp {
                         egui::Slider::new(&mut self.params.base.warp_strength, 0.0..=0.12)
                             .text("Warp"),
                     );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.base.shelf_width, 0.0..=0.1)
+                            .text("Shelf Width"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.base.seamount_density, 0.0..=3.0)
+                            .text("Seamounts"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.geology.boundary_faults, 0..=24)
+                            .text("Boundary Faults"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.geology.lineament_faults, 0..=40)
+                            .text("Lineament Faults"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.volcanoes.arc_spacing_tiles, 4.0..=40.0)
+                            .text("Arc Spacing"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.volcanoes.active_share, 0.0..=0.5)
+                            .text("Active Volcanoes"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.volcanoes.dormant_share, 0.0..=0.5)
+                            .text("Dormant Volcanoes"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.volcanoes.hotspots, 0..=10)
+                            .text("Hotspots"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.volcanoes.chain_length, 1..=12)
+                            .text("Chain Length"),
+                    );
                 });
 
                 ui.collapsing("Step 2: Erosion", |ui| {
@@ -167,6 +298,29 @@ impl eframe::App for WorldgenApp {
                         egui::Slider::new(&mut self.params.erosion.thermal_rate, 0.0..=0.08)
                             .text("Thermal"),
                     );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.erosion.runoff_coefficient, 0.2..=4.0)
+                            .text("Runoff"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.params.erosion.evapotranspiration_rate,
+                            0.0..=2.0,
+                        )
+                        .text("Evapotranspiration"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.erosion.channel_loss_rate, 0.0..=0.1)
+                            .text("Channel Loss"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.cryosphere.snowline_temp_c, -20.0..=5.0)
+                            .text("Snowline Temp C"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.cryosphere.sea_ice_temp_c, -20.0..=5.0)
+                            .text("Sea Ice Temp C"),
+                    );
                 });
 
                 ui.collapsing("Step 3: Biomes", |ui| {
@@ -178,6 +332,53 @@ impl eframe::App for WorldgenApp {
                         egui::Slider::new(&mut self.params.biomes.wetness_weight, 0.0..=1.0)
                             .text("Wetness Weight"),
                     );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.biomes.mean_precip_mm, 200.0..=3000.0)
+                            .text("Mean Precip mm"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.biomes.wave_fetch, 0.01..=0.25)
+                            .text("Wave Fetch"),
+                    );
+                    ui.checkbox(&mut self.params.biomes.ecotones, "Ecotone blending");
+                    ui.add(
+                        egui::Slider::new(&mut self.params.biomes.ecotone_width, 0.1..=4.0)
+                            .text("Ecotone Width"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.biomes.blend_slots, 1..=4)
+                            .text("Blend Slots"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.fire.simulated_years, 0..=1000)
+                            .text("Fire Years"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.fire.ignition_rate, 0.0..=0.01)
+                            .text("Ignition Rate"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.fire.spread_rate, 0.0..=1.0)
+                            .text("Fire Spread"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.fire.wind_influence, 0.0..=2.0)
+                            .text("Fire Wind"),
+                    );
+                    ui.checkbox(&mut self.params.biomes.whittaker, "Whittaker layer");
+                    ui.checkbox(&mut self.params.biomes.holdridge, "Holdridge layer");
+                    ui.horizontal(|ui| {
+                        ui.label("Definitions");
+                        if ui
+                            .text_edit_singleline(&mut self.biome_defs_path)
+                            .on_hover_text("Biome JSON file; leave empty for built-in biomes")
+                            .changed()
+                        {
+                            let path = self.biome_defs_path.trim();
+                            self.params.biomes.definitions =
+                                (!path.is_empty()).then(|| PathBuf::from(path));
+                        }
+                    });
                 });
 
                 ui.collapsing("Step 4: Hydro Final", |ui| {
@@ -193,6 +394,10 @@ impl eframe::App for WorldgenApp {
                         egui::Slider::new(&mut self.params.hydro.major_threshold, 120.0..=2000.0)
                             .text("Major Q"),
                     );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.hydro.lake_evaporation, 0.0..=3.0)
+                            .text("Lake Evaporation"),
+                    );
                 });
 
                 ui.collapsing("Step 5: Geology", |ui| {
@@ -208,6 +413,158 @@ impl eframe::App for WorldgenApp {
                         egui::Slider::new(&mut self.params.geology.ore_richness, 0.0..=1.0)
                             .text("Ore Richness"),
                     );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.geology.min_deposit_tiles, 1..=20)
+                            .text("Min Deposit Tiles"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.geology.mask_cutoff, 0.0..=1.5)
+                            .text("Mask Cutoff"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.caves.karst_density, 0.0..=1.0)
+                            .text("Karst Density"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.caves.lava_tubes_per_volcano, 0..=8)
+                            .text("Lava Tubes"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.caves.cavern_coverage, 0.0..=0.8)
+                            .text("Cavern Coverage"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.hazards.quake_reach_tiles, 1.0..=60.0)
+                            .text("Quake Reach"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.hazards.tsunami_height_m, 0.0..=200.0)
+                            .text("Tsunami Height (m)"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.geology.tile_size_m, 100.0..=5000.0)
+                            .text("Tile Size (m)"),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Catalogue");
+                        if ui
+                            .text_edit_singleline(&mut self.geology_catalogue_path)
+                            .on_hover_text(
+                                "Rock and mineral JSON file; leave empty for the built-in catalogue",
+                            )
+                            .changed()
+                        {
+                            let path = self.geology_catalogue_path.trim();
+                            self.params.geology.catalogue =
+                                (!path.is_empty()).then(|| PathBuf::from(path));
+                        }
+                    });
+                });
+
+                ui.collapsing("Step 6: Ecology", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.params.ecology.max_soil_depth_m, 0.5..=12.0)
+                            .text("Max Soil Depth m"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.params.ecology.mean_stand_age_years,
+                            10.0..=500.0,
+                        )
+                        .text("Mean Stand Age"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.params.ecology.disturbance_patch_scale,
+                            4.0..=64.0,
+                        )
+                        .text("Disturbance Patches"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.wilderness.calm_coverage, 0.0..=1.0)
+                            .text("Calm %"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.wilderness.savage_coverage, 0.0..=1.0)
+                            .text("Savage %"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.wilderness.good_coverage, 0.0..=1.0)
+                            .text("Good %"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.wilderness.evil_coverage, 0.0..=1.0)
+                            .text("Evil %"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.wilderness.remoteness_bias, 0.0..=1.0)
+                            .text("Remoteness Bias"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.wilderness.patch_scale, 1.0..=16.0)
+                            .text("Wilderness Patches"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.fauna.site_density, 0.0..=0.05)
+                            .text("Creature Sites"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.fauna.min_site_spacing, 1.0..=32.0)
+                            .text("Site Spacing"),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Creatures");
+                        if ui
+                            .text_edit_singleline(&mut self.creature_catalogue_path)
+                            .on_hover_text("Creature JSON file; leave empty for built-in creatures")
+                            .changed()
+                        {
+                            let path = self.creature_catalogue_path.trim();
+                            self.params.fauna.catalogue =
+                                (!path.is_empty()).then(|| PathBuf::from(path));
+                        }
+                    });
+                });
+
+                ui.collapsing("Step 7: Regions", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.params.regions.min_region_tiles, 1..=200)
+                            .text("Min Region Tiles"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.params.mountains.min_peak_prominence_m,
+                            10.0..=1000.0,
+                        )
+                        .text("Peak Prominence m"),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut self.params.mountains.range_prominence_m,
+                            100.0..=3000.0,
+                        )
+                        .text("Range Prominence m"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.mountains.range_base_m, 0.0..=3000.0)
+                            .text("Range Base m"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut self.params.naming.min_feature_tiles, 1..=500)
+                            .text("Min Named Tiles"),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Languages");
+                        if ui
+                            .text_edit_singleline(&mut self.languages_path)
+                            .on_hover_text("Language JSON file; leave empty for built-in languages")
+                            .changed()
+                        {
+                            let path = self.languages_path.trim();
+                            self.params.naming.languages =
+                                (!path.is_empty()).then(|| PathBuf::from(path));
+                        }
+                    });
                 });
 
                 ui.separator();
@@ -247,10 +604,101 @@ impl eframe::App for WorldgenApp {
                             }
                         }
                     });
+                if self.view_layer == ViewLayer::Habitat {
+                    let catalogue = &self.state.creature_catalogue;
+                    let selected = if self.habitat_species < catalogue.len() {
+                        catalogue.def(self.habitat_species).name.as_str()
+                    } else {
+                        "-"
+                    };
+                    let mut changed = false;
+                    egui::ComboBox::from_label("Species")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (species, def) in catalogue.iter() {
+                                changed |= ui
+                                    .selectable_value(&mut self.habitat_species, species, &def.name)
+                                    .clicked();
+                            }
+                        });
+                    if changed {
+                        self.refresh_texture(ctx);
+                    }
+                }
+                if self.view_layer == ViewLayer::Mineral {
+                    let catalogue = &self.state.geology_catalogue;
+                    let selected = if self.mineral < catalogue.mineral_count() {
+                        catalogue.mineral(Mineral(self.mineral as u8)).name.as_str()
+                    } else {
+                        "-"
+                    };
+                    let mut changed = false;
+                    egui::ComboBox::from_label("Mineral")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (mineral, def) in catalogue.minerals() {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut self.mineral,
+                                        usize::from(mineral.as_u8()),
+                                        &def.name,
+                                    )
+                                    .clicked();
+                            }
+                        });
+                    if changed {
+                        self.refresh_texture(ctx);
+                    }
+                }
+
+                if self.view_layer == ViewLayer::Caves {
+                    let band = |layer: &CaveLayer| {
+                        format!("{:.0}-{:.0} m", layer.top_m, layer.bottom_m)
+                    };
+                    let selected = self
+                        .state
+                        .cave_layers
+                        .get(self.cave_layer)
+                        .map_or_else(|| "-".to_string(), band);
+                    let mut changed = false;
+                    egui::ComboBox::from_label("Depth Band")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (i, layer) in self.state.cave_layers.iter().enumerate() {
+                                changed |= ui
+                                    .selectable_value(&mut self.cave_layer, i, band(layer))
+                                    .clicked();
+                            }
+                        });
+                    if changed {
+                        self.refresh_texture(ctx);
+                    }
+                }
 
+                if self.view_layer == ViewLayer::Hazard {
+                    let mut changed = false;
+                    egui::ComboBox::from_label("Hazard")
+                        .selected_text(format!("{:?}", self.hazard))
+                        .show_ui(ui, |ui| {
+                            for hazard in Hazard::ALL {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut self.hazard,
+                                        hazard,
+                                        format!("{hazard:?}"),
+                                    )
+                                    .clicked();
+                            }
+                        });
+                    if changed {
+                        self.refresh_texture(ctx);
+                    }
+                }
+
+                let total = Step::ALL.len();
                 let step_text = match self.state.current_step {
-                    Some(step) => format!("{}/5 ({:?})", step.index(), step),
-                    None => "0/5 (Not started)".to_string(),
+                    Some(step) => format!("{}/{total} ({:?})", step.index(), step),
+                    None => format!("0/{total} (Not started)"),
                 };
                 ui.label(format!("Step: {step_text}"));
                 ui.label(format!("Checksum: {}", self.state.diagnostics.checksum));
@@ -281,7 +729,132 @@ impl eframe::App for WorldgenApp {
                     let k = avail.y / size.y;
                     size *= k;
                 }
-                ui.image((tex.id(), size));
+                let response = ui.image((tex.id(), size)).interact(egui::Sense::hover());
+                if let Some(pos) = response.hover_pos() {
+                    let rel = (pos - response.rect.min) / response.rect.size();
+                    let x = (rel.x * self.state.width as f32) as usize;
+                    let y = (rel.y * self.state.height as f32) as usize;
+                    if x < self.state.width && y < self.state.height {
+                        let mut text =
+                            format!("({x}, {y}) {}", wilderness::biome_name(&self.state, x, y));
+                        let id = *self.state.region_id.get(x, y) as usize;
+                        if let Some(region) = self.state.regions.get(id) {
+                            text.push_str(&format!(
+                                "\nRegion {} ({:?}, {} tiles)",
+                                region.id, region.kind, region.area
+                            ));
+                            if let Some(name) = &region.name {
+                                text.push_str(&format!("\n{name}"));
+                            }
+                        }
+                        let range = *self.state.mountain_range.get(x, y) as usize;
+                        if let Some(name) = range
+                            .checked_sub(1)
+                            .and_then(|i| self.state.mountain_ranges.get(i))
+                            .and_then(|r| r.name.as_ref())
+                        {
+                            text.push_str(&format!("\n{name}"));
+                        }
+                        let catalogue = &self.state.geology_catalogue;
+                        let key = mineral_key(&self.state, self.view_layer, self.mineral);
+                        if let Some(d) = key
+                            .and_then(|key| self.state.ore_deposit_id.get(key))
+                            .and_then(|ids| (*ids.get(x, y) as usize).checked_sub(1))
+                            .and_then(|i| self.state.ore_deposits.get(i))
+                        {
+                            text.push_str(&format!(
+                                "\nDeposit {} ({:?} {}): grade {:.3e}, {:.2e} t, {:.0}-{:.0} m",
+                                d.id,
+                                d.kind,
+                                catalogue.mineral(d.mineral).name,
+                                d.grade,
+                                d.tonnage_t,
+                                d.top_m,
+                                d.bottom_m
+                            ));
+                        }
+                        if let Some(key) = key {
+                            let grade = self
+                                .state
+                                .mineral_grade
+                                .get(key)
+                                .map_or(0.0, |g| *g.get(x, y));
+                            let reserve = self
+                                .state
+                                .mineral_reserve_t
+                                .get(key)
+                                .map_or(0.0, |g| *g.get(x, y));
+                            text.push_str(&format!("\nGrade {grade:.3e}, reserve {reserve:.3e} t"));
+                        }
+                        let fault = (*self.state.fault_id.get(x, y) as usize)
+                            .checked_sub(1)
+                            .and_then(|i| self.state.faults.get(i));
+                        if let (ViewLayer::Faults, Some(f)) = (self.view_layer, fault) {
+                            text.push_str(&format!(
+                                "\nFault {} ({:?}, throw {:.0} m), {:.1} tiles away; uplift {:.0} m",
+                                f.id,
+                                f.kind,
+                                f.throw_m,
+                                self.state.fault_distance.get(x, y),
+                                self.state.fault_uplift_m.get(x, y)
+                            ));
+                        }
+                        let volcano = (*self.state.volcano_id.get(x, y) as usize)
+                            .checked_sub(1)
+                            .and_then(|i| self.state.volcanoes.get(i));
+                        if let (ViewLayer::Volcanoes, Some(v)) = (self.view_layer, volcano) {
+                            text.push_str(&format!(
+                                "\nVolcano {} ({:?}, {:?}), cone {:.0} m; lava {:.0} m",
+                                v.id,
+                                v.kind,
+                                v.activity,
+                                v.cone_m,
+                                self.state.lava_thickness_m.get(x, y)
+                            ));
+                        }
+                        if self.view_layer == ViewLayer::Hazard {
+                            for hazard in Hazard::ALL {
+                                text.push_str(&format!(
+                                    "\n{hazard:?} {:.2}",
+                                    self.state.hazard(hazard).get(x, y)
+                                ));
+                            }
+                        }
+                        if self.view_layer == ViewLayer::Caves {
+                            if let Some(o) =
+                                self.state.cave_openings.iter().find(|o| (o.x, o.y) == (x, y))
+                            {
+                                text.push_str(&format!(
+                                    "\n{:?} {} (linked {}), discharge {:.0}",
+                                    o.kind, o.id, o.linked, o.discharge
+                                ));
+                            }
+                            for cave in self.state.caves(x, y) {
+                                text.push_str(&format!(
+                                    "\n{:.0}-{:.0} m {:?}",
+                                    cave.top_m, cave.bottom_m, cave.kind
+                                ));
+                            }
+                        }
+                        if matches!(
+                            self.view_layer,
+                            ViewLayer::Province
+                                | ViewLayer::RockType
+                                | ViewLayer::Faults
+                                | ViewLayer::Volcanoes
+                        ) {
+                            for layer in self.state.strata_column(x, y) {
+                                text.push_str(&format!(
+                                    "\n{:.0}-{:.0} m {}",
+                                    layer.top_m,
+                                    layer.bottom_m,
+                                    catalogue.rock(layer.rock).name
+                                ));
+                            }
+                        }
+                        response.on_hover_text(text);
+                    }
+                }
             }
         });
     }
@@ -334,12 +907,33 @@ impl WorldgenApp {
     }
 
     fn refresh_texture(&mut self, ctx: &egui::Context) {
-        let image = layer_image(&self.state, self.view_layer);
+        let image = layer_image(
+            &self.state,
+            self.view_layer,
+            self.habitat_species,
+            self.mineral,
+            self.cave_layer,
+            self.hazard,
+        );
         self.texture = Some(ctx.load_texture("world-layer", image, Default::default()));
     }
 }
 
-fn layer_image(state: &WorldState, layer: ViewLayer) -> egui::ColorImage {
+/// Key into the per-mineral grids when the mineral view is shown.
+fn mineral_key(state: &WorldState, layer: ViewLayer, mineral: usize) -> Option<&str> {
+    (layer == ViewLayer::Mineral && mineral < state.geology_catalogue.mineral_count())
+        .then(|| state.geology_catalogue.mineral_key(Mineral(mineral as u8)))
+}
+
+fn layer_image(
+    state: &WorldState,
+    layer: ViewLayer,
+    species: usize,
+    mineral: usize,
+    cave_layer: usize,
+    hazard: Hazard,
+) -> egui::ColorImage {
+    let key = mineral_key(state, layer, mineral);
     let mut pixels = vec![egui::Color32::BLACK; state.width * state.height];
 
     let to_idx = |x: usize, y: usize| y * state.width + x;
@@ -356,6 +950,23 @@ fn layer_image(state: &WorldState, layer: ViewLayer) -> egui::ColorImage {
         ViewLayer::Accumulation => min_max(state.accumulation.as_slice()),
         ViewLayer::Discharge => min_max(state.discharge.as_slice()),
         ViewLayer::Fertility => min_max(state.fertility.as_slice()),
+        ViewLayer::FireFrequency => min_max(state.fire_frequency.as_slice()),
+        ViewLayer::BurnScarAge => min_max(state.burn_scar_age.as_slice()),
+        ViewLayer::FireReturnInterval => min_max(state.fire_return_interval.as_slice()),
+        ViewLayer::SoilDepth => min_max(state.soil_depth.as_slice()),
+        ViewLayer::LeafAreaIndex => min_max(state.leaf_area_index.as_slice()),
+        ViewLayer::VegetationDensity => min_max(state.vegetation_density.as_slice()),
+        ViewLayer::CanopyHeight => min_max(state.canopy_height.as_slice()),
+        ViewLayer::ForestAge => min_max(state.forest_age.as_slice()),
+        ViewLayer::Alignment => (-1.0, 1.0),
+        ViewLayer::Mountains => min_max(state.elevation.as_slice()),
+        ViewLayer::Glacier => min_max(state.glacier_thickness.as_slice()),
+        ViewLayer::WaveExposure => min_max(state.wave_exposure.as_slice()),
+        ViewLayer::Mineral => key
+            .and_then(|key| state.mineral_grade.get(key))
+            .map_or((0.0, 1.0), |g| min_max(g.as_slice())),
+        ViewLayer::Faults => min_max(state.fault_uplift_m.as_slice()),
+        ViewLayer::Volcanoes => min_max(state.lava_thickness_m.as_slice()),
         _ => (0.0, 1.0),
     };
 
@@ -374,6 +985,14 @@ fn layer_image(state: &WorldState, layer: ViewLayer) -> egui::ColorImage {
                     let v = float_norm(*state.rainfall.get(x, y), fmin, fmax);
                     egui::Color32::from_rgb(20, (v * 255.0) as u8, 200)
                 }
+                ViewLayer::OceanZone => {
+                    let [r, g, b] = bathymetry::ocean_zone_color(*state.ocean_zone.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::WaveExposure => {
+                    let v = float_norm(*state.wave_exposure.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 120.0) as u8, (v * 200.0) as u8, (v * 255.0) as u8)
+                }
                 ViewLayer::Accumulation => {
                     let v = float_norm(
                         (*state.accumulation.get(x, y) + 1.0).ln(),
@@ -404,6 +1023,15 @@ fn layer_image(state: &WorldState, layer: ViewLayer) -> egui::ColorImage {
                     RiverClass::Perennial => egui::Color32::from_rgb(40, 130, 245),
                     RiverClass::Major => egui::Color32::from_rgb(0, 60, 220),
                 },
+                ViewLayer::RiverNavigation => {
+                    let [r, g, b] =
+                        hydro_finalize::navigation_color(*state.river_navigation.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::FluvialFeature => {
+                    let [r, g, b] = hydro_finalize::fluvial_color(*state.fluvial_feature.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
                 ViewLayer::Lake => {
                     let id = *state.lake_id.get(x, y);
                     if id == 0 {
@@ -423,24 +1051,246 @@ fn layer_image(state: &WorldState, layer: ViewLayer) -> egui::ColorImage {
                         egui::Color32::from_rgb(20, 20, 20)
                     }
                 }
-                ViewLayer::Biome => biome_color(*state.biome.get(x, y)),
+                ViewLayer::Ice => {
+                    let [r, g, b] = cryosphere::ice_color(*state.ice_cover.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::Glacier => {
+                    let v = float_norm(*state.glacier_thickness.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 200.0) as u8, (v * 230.0) as u8, (v * 255.0) as u8)
+                }
+                ViewLayer::Biome => {
+                    let [r, g, b] = state.biome_table.color(*state.biome.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::BiomeBlend => {
+                    let [r, g, b] = state.biome_table.blend_color(state.biome_blend.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::Whittaker => {
+                    let [r, g, b] = life_zones::whittaker_color(*state.whittaker_biome.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::Holdridge => {
+                    let [r, g, b] = life_zones::holdridge_color(*state.holdridge_zone.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::FireFrequency => {
+                    let v = float_norm(*state.fire_frequency.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 255.0) as u8, (v * 120.0) as u8, 20)
+                }
+                ViewLayer::BurnScarAge => {
+                    let v = float_norm(*state.burn_scar_age.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb(
+                        (v * 90.0) as u8 + 40,
+                        (v * 200.0) as u8 + 20,
+                        (v * 80.0) as u8,
+                    )
+                }
+                ViewLayer::FireReturnInterval => {
+                    let v = float_norm(*state.fire_return_interval.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb(
+                        (v * 120.0) as u8 + 60,
+                        (v * 200.0) as u8 + 40,
+                        (v * 140.0) as u8,
+                    )
+                }
                 ViewLayer::Fertility => {
                     let v = float_norm(*state.fertility.get(x, y), fmin, fmax);
                     egui::Color32::from_rgb((v * 140.0) as u8, (v * 255.0) as u8, 70)
                 }
                 ViewLayer::Province => province_color(*state.geologic_province.get(x, y)),
-                ViewLayer::RockType => rock_color(*state.rock_type.get(x, y)),
-                ViewLayer::MineralIron => mineral_color(state, x, y, "iron"),
-                ViewLayer::MineralCopper => mineral_color(state, x, y, "copper"),
-                ViewLayer::MineralGold => mineral_color(state, x, y, "gold"),
-                ViewLayer::MineralTin => mineral_color(state, x, y, "tin"),
-                ViewLayer::MineralCoal => mineral_color(state, x, y, "coal"),
-                ViewLayer::MineralGem => mineral_color(state, x, y, "gem"),
+                ViewLayer::RockType => rock_color(state, *state.rock_type.get(x, y)),
+                ViewLayer::Mineral => mineral_color(state, x, y, key.unwrap_or_default(), fmax),
+                ViewLayer::Faults => {
+                    // Brighter near a trace, redder on raised blocks.
+                    let near = (1.0 - *state.fault_distance.get(x, y) / 20.0).clamp(0.0, 1.0);
+                    let up = float_norm(*state.fault_uplift_m.get(x, y), 0.0, fmax);
+                    egui::Color32::from_rgb(
+                        (30.0 + up * 170.0) as u8,
+                        (30.0 + near * 90.0) as u8,
+                        (40.0 + near * 140.0) as u8,
+                    )
+                }
+                ViewLayer::Volcanoes => {
+                    // Relief in grey, lava fields tinted red by thickness.
+                    let e = float_norm(*state.elevation.get(x, y), 0.0, 1.0);
+                    let lava = float_norm(*state.lava_thickness_m.get(x, y), 0.0, fmax);
+                    let grey = 40.0 + e * 150.0;
+                    egui::Color32::from_rgb(
+                        (grey + lava * (230.0 - grey)) as u8,
+                        (grey * (1.0 - lava * 0.6)) as u8,
+                        (grey * (1.0 - lava * 0.8)) as u8,
+                    )
+                }
+                ViewLayer::Caves => {
+                    match state.cave_layers.get(cave_layer).map(|l| *l.cave.get(x, y)) {
+                        Some(kind) if kind != CaveKind::None => {
+                            let [r, g, b] = caves::cave_color(kind);
+                            egui::Color32::from_rgb(r, g, b)
+                        }
+                        _ => {
+                            let e = float_norm(*state.elevation.get(x, y), 0.0, 1.0);
+                            let grey = (20.0 + e * 70.0) as u8;
+                            egui::Color32::from_rgb(grey, grey, grey)
+                        }
+                    }
+                }
+                ViewLayer::Hazard => {
+                    // Green through yellow to red; water stays dark blue.
+                    if *state.ocean_mask.get(x, y) || *state.lake_id.get(x, y) != 0 {
+                        egui::Color32::from_rgb(15, 30, 60)
+                    } else {
+                        let v = state.hazard(hazard).get(x, y).clamp(0.0, 1.0);
+                        egui::Color32::from_rgb(
+                            (60.0 + (v * 2.0).min(1.0) * 195.0) as u8,
+                            (170.0 - (v * 2.0 - 1.0).max(0.0) * 150.0) as u8,
+                            50,
+                        )
+                    }
+                }
+                ViewLayer::Soil => {
+                    let [r, g, b] = soils::soil_color(*state.soil.get(x, y));
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::SoilDepth => {
+                    let v = float_norm(*state.soil_depth.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 150.0) as u8, (v * 110.0) as u8, (v * 70.0) as u8)
+                }
+                ViewLayer::LeafAreaIndex => {
+                    let v = float_norm(*state.leaf_area_index.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 60.0) as u8, (v * 220.0) as u8, (v * 60.0) as u8)
+                }
+                ViewLayer::VegetationDensity => {
+                    let v = float_norm(*state.vegetation_density.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 100.0) as u8, (v * 200.0) as u8, (v * 80.0) as u8)
+                }
+                ViewLayer::CanopyHeight => {
+                    let v = float_norm(*state.canopy_height.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 40.0) as u8, (v * 170.0) as u8, (v * 90.0) as u8)
+                }
+                ViewLayer::ForestAge => {
+                    let v = float_norm(*state.forest_age.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb((v * 200.0) as u8, (v * 160.0) as u8, (v * 90.0) as u8)
+                }
+                ViewLayer::Savagery => {
+                    let v = *state.savagery.get(x, y);
+                    egui::Color32::from_rgb((60.0 + v * 190.0) as u8, (140.0 - v * 100.0) as u8, 40)
+                }
+                ViewLayer::Alignment => {
+                    let v = float_norm(*state.alignment.get(x, y), fmin, fmax);
+                    egui::Color32::from_rgb(
+                        (150.0 - v * 60.0) as u8,
+                        (60.0 + v * 140.0) as u8,
+                        (140.0 + v * 90.0) as u8,
+                    )
+                }
+                ViewLayer::Wilderness => {
+                    let wp = &state.params.wilderness;
+                    let [r, g, b] = wilderness::wilderness_color(
+                        wilderness::savagery_class(*state.savagery.get(x, y), wp),
+                        wilderness::alignment_class(*state.alignment.get(x, y), wp),
+                    );
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::Regions => {
+                    let [r, g, b] = state
+                        .regions
+                        .get(*state.region_id.get(x, y) as usize)
+                        .map_or([0, 0, 0], regions::region_color);
+                    egui::Color32::from_rgb(r, g, b)
+                }
+                ViewLayer::Mountains => {
+                    let v = float_norm(*state.elevation.get(x, y), fmin, fmax);
+                    let grey = (30.0 + v * 90.0) as u8;
+                    match *state.mountain_range.get(x, y) {
+                        0 => egui::Color32::from_rgb(grey, grey, grey),
+                        id => {
+                            let h = hash_u64(u64::from(id));
+                            egui::Color32::from_rgb(
+                                grey / 2 + (h as u8) / 2,
+                                grey / 2 + ((h >> 8) as u8) / 2,
+                                grey / 2 + ((h >> 16) as u8) / 2,
+                            )
+                        }
+                    }
+                }
+                ViewLayer::Habitat => {
+                    let s = state
+                        .habitat_suitability
+                        .get(species)
+                        .map_or(0.0, |grid| *grid.get(x, y));
+                    let base = if *state.elevation.get(x, y) > state.params.base.sea_level {
+                        40.0
+                    } else {
+                        15.0
+                    };
+                    egui::Color32::from_rgb(
+                        (base + s * 200.0) as u8,
+                        (base + s * 120.0) as u8,
+                        (base + s * 20.0) as u8,
+                    )
+                }
             };
             pixels[to_idx(x, y)] = color;
         }
     }
 
+    if layer == ViewLayer::Mountains {
+        for pass in &state.mountain_passes {
+            pixels[to_idx(pass.x, pass.y)] = egui::Color32::YELLOW;
+        }
+        for peak in &state.peaks {
+            pixels[to_idx(peak.x, peak.y)] = egui::Color32::WHITE;
+        }
+    }
+
+    if layer == ViewLayer::Faults {
+        for fault in &state.faults {
+            for w in fault.points.windows(2) {
+                let (a, b) = (w[0], w[1]);
+                let samples = ((b[0] - a[0]).hypot(b[1] - a[1]) * 2.0).ceil().max(1.0) as usize;
+                for j in 0..=samples {
+                    let t = j as f32 / samples as f32;
+                    let x = (a[0] + (b[0] - a[0]) * t).round() as usize;
+                    let y = (a[1] + (b[1] - a[1]) * t).round() as usize;
+                    if x < state.width && y < state.height {
+                        pixels[to_idx(x, y)] = egui::Color32::from_rgb(255, 70, 40);
+                    }
+                }
+            }
+        }
+    }
+
+    if layer == ViewLayer::Volcanoes {
+        for v in &state.volcanoes {
+            pixels[to_idx(v.x, v.y)] = match v.activity {
+                VolcanoActivity::Active => egui::Color32::from_rgb(255, 60, 20),
+                VolcanoActivity::Dormant => egui::Color32::from_rgb(255, 200, 40),
+                VolcanoActivity::Extinct => egui::Color32::WHITE,
+            };
+        }
+    }
+
+    if layer == ViewLayer::Caves && cave_layer == 0 {
+        for o in &state.cave_openings {
+            pixels[to_idx(o.x, o.y)] = match o.kind {
+                CaveOpeningKind::Sinkhole => egui::Color32::from_rgb(20, 30, 160),
+                CaveOpeningKind::Spring => egui::Color32::WHITE,
+            };
+        }
+    }
+
+    if layer == ViewLayer::Habitat {
+        for site in state.creature_sites.iter().filter(|s| s.species == species) {
+            let color = match site.kind {
+                CreatureSiteKind::Population => egui::Color32::WHITE,
+                CreatureSiteKind::Lair => egui::Color32::RED,
+            };
+            pixels[to_idx(site.x, site.y)] = color;
+        }
+    }
+
     egui::ColorImage {
         size: [state.width, state.height],
         pixels,
@@ -453,25 +1303,6 @@ fn min_max(slice: &[f32]) -> (f32, f32) {
     (min, max)
 }
 
-fn biome_color(b: Biome) -> egui::Color32 {
-    match b {
-        Biome::Ocean => egui::Color32::from_rgb(18, 52, 120),
-        Biome::Lake => egui::Color32::from_rgb(42, 98, 190),
-        Biome::PolarDesert => egui::Color32::from_rgb(230, 230, 218),
-        Biome::Tundra => egui::Color32::from_rgb(176, 190, 156),
-        Biome::BorealForest => egui::Color32::from_rgb(66, 102, 63),
-        Biome::TemperateGrassland => egui::Color32::from_rgb(158, 186, 102),
-        Biome::TemperateForest => egui::Color32::from_rgb(74, 136, 80),
-        Biome::Mediterranean => egui::Color32::from_rgb(146, 152, 84),
-        Biome::Savanna => egui::Color32::from_rgb(186, 172, 78),
-        Biome::TropicalSeasonalForest => egui::Color32::from_rgb(88, 144, 70),
-        Biome::TropicalRainforest => egui::Color32::from_rgb(42, 116, 52),
-        Biome::HotDesert => egui::Color32::from_rgb(214, 188, 126),
-        Biome::Alpine => egui::Color32::from_rgb(136, 136, 140),
-        Biome::Wetland => egui::Color32::from_rgb(76, 138, 120),
-    }
-}
-
 fn province_color(p: GeologicProvince) -> egui::Color32 {
     match p {
         GeologicProvince::Oceanic => egui::Color32::from_rgb(30, 80, 130),
@@ -482,25 +1313,42 @@ fn province_color(p: GeologicProvince) -> egui::Color32 {
     }
 }
 
-fn rock_color(r: RockType) -> egui::Color32 {
-    match r {
-        RockType::Basalt => egui::Color32::from_rgb(65, 65, 72),
-        RockType::Gabbro => egui::Color32::from_rgb(50, 58, 66),
-        RockType::Granite => egui::Color32::from_rgb(186, 175, 164),
-        RockType::Sandstone => egui::Color32::from_rgb(194, 168, 120),
-        RockType::Limestone => egui::Color32::from_rgb(204, 198, 160),
-        RockType::Schist => egui::Color32::from_rgb(130, 122, 140),
-        RockType::Gneiss => egui::Color32::from_rgb(144, 136, 128),
-        RockType::Shale => egui::Color32::from_rgb(104, 102, 94),
-        RockType::Rhyolite => egui::Color32::from_rgb(176, 130, 120),
-    }
+fn rock_color(state: &WorldState, r: RockType) -> egui::Color32 {
+    let [red, g, b] = state.geology_catalogue.rock(r).color;
+    egui::Color32::from_rgb(red, g, b)
 }
 
-fn mineral_color(state: &WorldState, x: usize, y: usize, key: &str) -> egui::Color32 {
-    if let Some(mask) = state.mineral_masks.get(key) {
-        if *mask.get(x, y) {
-            return egui::Color32::from_rgb(240, 200, 30);
-        }
+/// Grade on a log scale over three decades below the richest tile, black to
+/// red to yellow; deposit tiles are outlined white.
+fn mineral_color(
+    state: &WorldState,
+    x: usize,
+    y: usize,
+    key: &str,
+    max_grade: f32,
+) -> egui::Color32 {
+    let grade = state.mineral_grade.get(key).map_or(0.0, |g| *g.get(x, y));
+    if grade <= 0.0 || max_grade <= 0.0 {
+        return egui::Color32::BLACK;
     }
-    egui::Color32::BLACK
+    let in_deposit = |x: isize, y: isize| {
+        state
+            .mineral_masks
+            .get(key)
+            .is_some_and(|m| m.in_bounds(x, y) && *m.get(x as usize, y as usize))
+    };
+    let (xi, yi) = (x as isize, y as isize);
+    if in_deposit(xi, yi)
+        && [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .any(|(dx, dy)| !in_deposit(xi + dx, yi + dy))
+    {
+        return egui::Color32::WHITE;
+    }
+    let t = (1.0 + (grade / max_grade).log10() / 3.0).clamp(0.0, 1.0);
+    egui::Color32::from_rgb(
+        (t * 2.0).min(1.0).mul_add(235.0, 20.0) as u8,
+        ((t - 0.5).max(0.0) * 2.0 * 220.0) as u8,
+        20,
+    )
 }